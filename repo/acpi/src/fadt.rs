@@ -0,0 +1,201 @@
+//! REF: https://uefi.org/specs/ACPI/6.5/05_ACPI_Software_Programming_Model.html#fixed-acpi-description-table-fadt
+
+use crate::{
+    sdt::{read_u16, read_u32, read_u64, read_u8, HEADER_SIZE},
+    AcpiResult, GenericAddress, Sdt,
+};
+
+/// Fixed ACPI Description Table.
+/// Fields added by later revisions are `None` when the table is too short to contain them.
+#[derive(Debug, Clone, Copy)]
+pub struct Fadt {
+    pub firmware_ctrl: u32,
+    pub dsdt: u32,
+    pub preferred_pm_profile: u8,
+    pub sci_int: u16,
+    pub smi_cmd: u32,
+    pub acpi_enable: u8,
+    pub acpi_disable: u8,
+    pub pm1a_evt_blk: u32,
+    pub pm1b_evt_blk: u32,
+    pub pm1a_cnt_blk: u32,
+    pub pm1b_cnt_blk: u32,
+    pub pm_tmr_blk: u32,
+    pub pm1_evt_len: u8,
+    pub pm1_cnt_len: u8,
+    pub pm_tmr_len: u8,
+    pub century: u8,
+    pub iapc_boot_arch: u16,
+    pub flags: u32,
+    pub reset_reg: Option<GenericAddress>,
+    pub reset_value: Option<u8>,
+    pub x_firmware_ctrl: Option<u64>,
+    pub x_dsdt: Option<u64>,
+    pub x_pm1a_evt_blk: Option<GenericAddress>,
+    pub x_pm1a_cnt_blk: Option<GenericAddress>,
+    pub x_pm_tmr_blk: Option<GenericAddress>,
+}
+
+impl Fadt {
+    pub const SIGNATURE: &'static [u8; 4] = b"FACP";
+
+    /// IAPC_BOOT_ARCH: The motherboard supports user-visible devices on the LPC or ISA bus.
+    pub const BOOT_ARCH_LEGACY_DEVICES: u16 = 1 << 0;
+    /// IAPC_BOOT_ARCH: The motherboard contains support for port 60 and 64, usually an 8042.
+    pub const BOOT_ARCH_8042: u16 = 1 << 1;
+    /// IAPC_BOOT_ARCH: CMOS RTC is not present.
+    pub const BOOT_ARCH_CMOS_RTC_NOT_PRESENT: u16 = 1 << 5;
+
+    /// Flags: The PM timer is 32 bits wide instead of 24 bits.
+    pub const TMR_VAL_EXT: u32 = 1 << 8;
+    /// Flags: The reset register is supported.
+    pub const RESET_REG_SUP: u32 = 1 << 10;
+    /// Flags: The platform is hardware-reduced and has no PM timer or fixed hardware.
+    pub const HW_REDUCED_ACPI: u32 = 1 << 20;
+
+    pub fn parse(sdt: Sdt<'_>) -> AcpiResult<Self> {
+        if sdt.signature() != Self::SIGNATURE {
+            return Err("Invalid FADT signature");
+        }
+
+        let bytes = sdt.bytes();
+        Ok(Self {
+            firmware_ctrl: required(read_u32(bytes, HEADER_SIZE))?,
+            dsdt: required(read_u32(bytes, 40))?,
+            preferred_pm_profile: required(read_u8(bytes, 45))?,
+            sci_int: required(read_u16(bytes, 46))?,
+            smi_cmd: required(read_u32(bytes, 48))?,
+            acpi_enable: required(read_u8(bytes, 52))?,
+            acpi_disable: required(read_u8(bytes, 53))?,
+            pm1a_evt_blk: required(read_u32(bytes, 56))?,
+            pm1b_evt_blk: required(read_u32(bytes, 60))?,
+            pm1a_cnt_blk: required(read_u32(bytes, 64))?,
+            pm1b_cnt_blk: required(read_u32(bytes, 68))?,
+            pm_tmr_blk: required(read_u32(bytes, 76))?,
+            pm1_evt_len: required(read_u8(bytes, 88))?,
+            pm1_cnt_len: required(read_u8(bytes, 89))?,
+            pm_tmr_len: required(read_u8(bytes, 91))?,
+            century: required(read_u8(bytes, 108))?,
+            iapc_boot_arch: required(read_u16(bytes, 109))?,
+            flags: required(read_u32(bytes, 112))?,
+            reset_reg: GenericAddress::read(bytes, 116),
+            reset_value: read_u8(bytes, 128),
+            x_firmware_ctrl: read_u64(bytes, 132),
+            x_dsdt: read_u64(bytes, 140),
+            x_pm1a_evt_blk: GenericAddress::read(bytes, 148),
+            x_pm1a_cnt_blk: GenericAddress::read(bytes, 172),
+            x_pm_tmr_blk: GenericAddress::read(bytes, 208),
+        })
+    }
+
+    /// Physical address of the DSDT, preferring the 64-bit field.
+    pub fn dsdt_address(&self) -> u64 {
+        match self.x_dsdt {
+            Some(address) if address != 0 => address,
+            _ => self.dsdt as u64,
+        }
+    }
+
+    /// I/O port of the PM timer, which counts at 3.579545 MHz.
+    pub fn pm_timer_port(&self) -> Option<u16> {
+        if self.flags & Self::HW_REDUCED_ACPI != 0 {
+            return None;
+        }
+
+        match self.x_pm_tmr_blk {
+            Some(gas) if gas.address != 0 => {
+                (gas.address_space_id == GenericAddress::SYSTEM_IO).then_some(gas.address as u16)
+            }
+            _ => (self.pm_tmr_blk != 0).then_some(self.pm_tmr_blk as u16),
+        }
+    }
+}
+
+fn required<T>(value: Option<T>) -> AcpiResult<T> {
+    value.ok_or("FADT is too short")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sdt::test::table;
+
+    #[test]
+    fn acpi_6() {
+        let flags = Fadt::TMR_VAL_EXT | Fadt::RESET_REG_SUP;
+        let bytes = table::<276>(
+            Fadt::SIGNATURE,
+            &[
+                (40, &0x7FF0_0000u32.to_le_bytes()),
+                (46, &9u16.to_le_bytes()),
+                (76, &0x608u32.to_le_bytes()),
+                (91, &[4]),
+                (109, &Fadt::BOOT_ARCH_8042.to_le_bytes()),
+                (112, &flags.to_le_bytes()),
+                (116, &[1, 8, 0, 1]),
+                (120, &0xCF9u64.to_le_bytes()),
+                (128, &[0x06]),
+                (140, &0x1_0000_0000u64.to_le_bytes()),
+                (208, &[1, 32, 0, 3]),
+                (212, &0x608u64.to_le_bytes()),
+            ],
+        );
+        let fadt = Fadt::parse(Sdt::new(&bytes).unwrap()).unwrap();
+        assert_eq!(fadt.sci_int, 9);
+        assert_eq!(fadt.pm_tmr_len, 4);
+        assert_eq!(fadt.iapc_boot_arch, Fadt::BOOT_ARCH_8042);
+        assert_eq!(fadt.flags, flags);
+        assert_eq!(fadt.reset_reg.unwrap().address, 0xCF9);
+        assert_eq!(
+            fadt.reset_reg.unwrap().address_space_id,
+            GenericAddress::SYSTEM_IO
+        );
+        assert_eq!(fadt.reset_value, Some(0x06));
+        // The 64-bit fields take precedence.
+        assert_eq!(fadt.dsdt_address(), 0x1_0000_0000);
+        assert_eq!(fadt.pm_timer_port(), Some(0x608));
+    }
+
+    #[test]
+    fn acpi_1() {
+        // Ends before the reset register.
+        let bytes = table::<116>(
+            Fadt::SIGNATURE,
+            &[
+                (40, &0x7FF0_0000u32.to_le_bytes()),
+                (76, &0xB008u32.to_le_bytes()),
+            ],
+        );
+        let fadt = Fadt::parse(Sdt::new(&bytes).unwrap()).unwrap();
+        assert!(fadt.reset_reg.is_none() && fadt.x_dsdt.is_none() && fadt.x_pm_tmr_blk.is_none());
+        assert_eq!(fadt.dsdt_address(), 0x7FF0_0000);
+        assert_eq!(fadt.pm_timer_port(), Some(0xB008));
+    }
+
+    #[test]
+    fn hardware_reduced() {
+        let bytes = table::<276>(
+            Fadt::SIGNATURE,
+            &[
+                (76, &0x608u32.to_le_bytes()),
+                (112, &Fadt::HW_REDUCED_ACPI.to_le_bytes()),
+            ],
+        );
+        let fadt = Fadt::parse(Sdt::new(&bytes).unwrap()).unwrap();
+        assert_eq!(fadt.pm_timer_port(), None);
+    }
+
+    #[test]
+    fn invalid() {
+        let short = table::<112>(Fadt::SIGNATURE, &[]);
+        assert_eq!(
+            Fadt::parse(Sdt::new(&short).unwrap()).err(),
+            Some("FADT is too short")
+        );
+        let other = table::<116>(b"FACS", &[]);
+        assert_eq!(
+            Fadt::parse(Sdt::new(&other).unwrap()).err(),
+            Some("Invalid FADT signature")
+        );
+    }
+}
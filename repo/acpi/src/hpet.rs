@@ -0,0 +1,95 @@
+//! REF: IA-PC HPET (High Precision Event Timers) Specification 1.0a, 3.2.4 The ACPI 2.0 HPET Description Table
+
+use crate::{
+    sdt::{read_u16, read_u32, read_u8, HEADER_SIZE},
+    AcpiResult, GenericAddress, Sdt,
+};
+
+/// HPET Description Table
+#[derive(Debug, Clone, Copy)]
+pub struct Hpet {
+    pub event_timer_block_id: u32,
+    pub base_address: GenericAddress,
+    pub hpet_number: u8,
+    /// Minimum clock ticks for periodic mode without lost interrupts.
+    pub minimum_clock_tick: u16,
+    pub page_protection: u8,
+}
+
+impl Hpet {
+    pub const SIGNATURE: &'static [u8; 4] = b"HPET";
+
+    pub fn parse(sdt: Sdt<'_>) -> AcpiResult<Self> {
+        if sdt.signature() != Self::SIGNATURE {
+            return Err("Invalid HPET signature");
+        }
+
+        let bytes = sdt.bytes();
+        Ok(Self {
+            event_timer_block_id: required(read_u32(bytes, HEADER_SIZE))?,
+            base_address: required(GenericAddress::read(bytes, 40))?,
+            hpet_number: required(read_u8(bytes, 52))?,
+            minimum_clock_tick: required(read_u16(bytes, 53))?,
+            page_protection: required(read_u8(bytes, 55))?,
+        })
+    }
+
+    /// Number of comparators in the timer block.
+    pub fn comparator_count(&self) -> u8 {
+        (((self.event_timer_block_id >> 8) & 0x1F) + 1) as u8
+    }
+
+    pub fn pci_vendor_id(&self) -> u16 {
+        (self.event_timer_block_id >> 16) as u16
+    }
+}
+
+fn required<T>(value: Option<T>) -> AcpiResult<T> {
+    value.ok_or("HPET is too short")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sdt::test::table;
+
+    #[test]
+    fn parse() {
+        // As QEMU describes its HPET.
+        let bytes = table::<56>(
+            Hpet::SIGNATURE,
+            &[
+                (36, &0x8086_A201u32.to_le_bytes()),
+                (40, &[0, 64, 0, 0]),
+                (44, &0xFED0_0000u64.to_le_bytes()),
+                (53, &0x80u16.to_le_bytes()),
+            ],
+        );
+        let hpet = Hpet::parse(Sdt::new(&bytes).unwrap()).unwrap();
+        assert_eq!(hpet.comparator_count(), 3);
+        assert_eq!(hpet.pci_vendor_id(), 0x8086);
+        assert_eq!(
+            hpet.base_address,
+            GenericAddress {
+                address_space_id: GenericAddress::SYSTEM_MEMORY,
+                register_bit_width: 64,
+                register_bit_offset: 0,
+                access_size: 0,
+                address: 0xFED0_0000,
+            }
+        );
+        assert_eq!(hpet.hpet_number, 0);
+        assert_eq!(hpet.minimum_clock_tick, 0x80);
+
+        let short = table::<52>(Hpet::SIGNATURE, &[]);
+        assert_eq!(
+            Hpet::parse(Sdt::new(&short).unwrap()).err(),
+            Some("HPET is too short")
+        );
+        let other = table::<56>(b"APIC", &[]);
+        assert_eq!(
+            Hpet::parse(Sdt::new(&other).unwrap()).err(),
+            Some("Invalid HPET signature")
+        );
+    }
+}
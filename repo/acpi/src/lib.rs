@@ -0,0 +1,159 @@
+#![no_std]
+#![feature(offset_of)]
+
+pub mod fadt;
+pub mod hpet;
+pub mod madt;
+pub mod mcfg;
+pub mod rsdp;
+pub mod sdt;
+
+pub use fadt::Fadt;
+pub use hpet::Hpet;
+pub use madt::Madt;
+pub use mcfg::Mcfg;
+pub use rsdp::Rsdp;
+pub use sdt::{GenericAddress, Sdt};
+
+pub type AcpiResult<T> = core::result::Result<T, &'static str>;
+
+/// System description tables reachable from the XSDT.
+#[derive(Debug, Clone, Copy)]
+pub struct Acpi<'a> {
+    xsdt: Sdt<'a>,
+}
+
+impl Acpi<'static> {
+    /// Validates the RSDP at `rsdp_address` and the XSDT it points to.
+    ///
+    /// # Safety
+    /// `rsdp_address` must be the address of the ACPI 2.0 RSDP, e.g. from the EFI configuration table,
+    /// and the tables must be mapped at their physical addresses.
+    pub unsafe fn from_rsdp(rsdp_address: usize) -> AcpiResult<Self> {
+        let rsdp = Rsdp::from_address(rsdp_address)?;
+        let xsdt = Sdt::from_address(rsdp.xsdt_address() as usize)?;
+        Self::new(xsdt)
+    }
+}
+
+impl<'a> Acpi<'a> {
+    pub fn new(xsdt: Sdt<'a>) -> AcpiResult<Self> {
+        if xsdt.signature() != b"XSDT" {
+            return Err("Invalid XSDT signature");
+        }
+        Ok(Self { xsdt })
+    }
+
+    pub fn xsdt(&self) -> Sdt<'a> {
+        self.xsdt
+    }
+
+    /// Physical addresses of the tables listed in the XSDT.
+    pub fn table_addresses(&self) -> impl Iterator<Item = u64> + 'a {
+        self.xsdt
+            .data()
+            .chunks_exact(8)
+            .map(|entry| u64::from_le_bytes(entry.try_into().unwrap()))
+    }
+}
+
+impl Acpi<'static> {
+    /// Tables listed in the XSDT. Tables with invalid checksums are skipped.
+    ///
+    /// # Safety
+    /// The tables must be mapped at their physical addresses.
+    pub unsafe fn tables(&self) -> impl Iterator<Item = Sdt<'static>> {
+        self.table_addresses()
+            .filter_map(|address| Sdt::from_address(address as usize).ok())
+    }
+
+    /// # Safety
+    /// The tables must be mapped at their physical addresses.
+    pub unsafe fn find_table(&self, signature: &[u8; 4]) -> Option<Sdt<'static>> {
+        self.tables().find(|table| table.signature() == signature)
+    }
+
+    /// # Safety
+    /// The tables must be mapped at their physical addresses.
+    pub unsafe fn madt(&self) -> AcpiResult<Madt<'static>> {
+        Madt::new(self.find_table(Madt::SIGNATURE).ok_or("MADT not found")?)
+    }
+
+    /// # Safety
+    /// The tables must be mapped at their physical addresses.
+    pub unsafe fn fadt(&self) -> AcpiResult<Fadt> {
+        Fadt::parse(self.find_table(Fadt::SIGNATURE).ok_or("FADT not found")?)
+    }
+
+    /// # Safety
+    /// The tables must be mapped at their physical addresses.
+    pub unsafe fn hpet(&self) -> AcpiResult<Hpet> {
+        Hpet::parse(self.find_table(Hpet::SIGNATURE).ok_or("HPET not found")?)
+    }
+
+    /// # Safety
+    /// The tables must be mapped at their physical addresses.
+    pub unsafe fn mcfg(&self) -> AcpiResult<Mcfg<'static>> {
+        Mcfg::new(self.find_table(Mcfg::SIGNATURE).ok_or("MCFG not found")?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{rsdp::test::rsdp, sdt::test::table};
+
+    fn hpet() -> [u8; 56] {
+        table(
+            Hpet::SIGNATURE,
+            &[
+                (36, &0x8086_A201u32.to_le_bytes()),
+                (40, &[0, 64, 0, 0]),
+                (44, &0xFED0_0000u64.to_le_bytes()),
+                (53, &0x80u16.to_le_bytes()),
+            ],
+        )
+    }
+
+    #[test]
+    fn from_rsdp() {
+        let hpet = hpet();
+        let mut corrupted = table::<36>(b"BAD ", &[]);
+        corrupted[9] ^= 1;
+        let xsdt = table::<52>(
+            b"XSDT",
+            &[
+                (36, &(corrupted.as_ptr() as u64).to_le_bytes()),
+                (44, &(hpet.as_ptr() as u64).to_le_bytes()),
+            ],
+        );
+        let rsdp = rsdp(2, xsdt.as_ptr() as u64);
+
+        let acpi = unsafe { Acpi::from_rsdp(rsdp.as_ptr() as usize) }.unwrap();
+        assert_eq!(acpi.table_addresses().count(), 2);
+        // The table with an invalid checksum is skipped.
+        assert_eq!(unsafe { acpi.tables() }.count(), 1);
+        assert!(unsafe { acpi.find_table(b"BAD ") }.is_none());
+
+        let hpet = unsafe { acpi.hpet() }.unwrap();
+        assert_eq!(hpet.base_address.address, 0xFED0_0000);
+        assert_eq!(unsafe { acpi.madt() }.err(), Some("MADT not found"));
+    }
+
+    #[test]
+    fn invalid_xsdt() {
+        let rsdt = table::<36>(b"RSDT", &[]);
+        assert_eq!(
+            Acpi::new(Sdt::new(&rsdt).unwrap()).err(),
+            Some("Invalid XSDT signature")
+        );
+
+        let mut xsdt = table::<44>(b"XSDT", &[(36, &0x1000u64.to_le_bytes())]);
+        xsdt[40] ^= 1;
+        let rsdp = rsdp(2, xsdt.as_ptr() as u64);
+        assert_eq!(
+            unsafe { Acpi::from_rsdp(rsdp.as_ptr() as usize) }.err(),
+            Some("Invalid table checksum")
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! REF: https://uefi.org/specs/ACPI/6.5/05_ACPI_Software_Programming_Model.html#multiple-apic-description-table-madt
+
+use crate::{
+    sdt::{read_u16, read_u32, read_u64, read_u8},
+    AcpiResult, Sdt,
+};
+
+/// Multiple APIC Description Table
+#[derive(Debug, Clone, Copy)]
+pub struct Madt<'a> {
+    sdt: Sdt<'a>,
+}
+
+impl<'a> Madt<'a> {
+    pub const SIGNATURE: &'static [u8; 4] = b"APIC";
+
+    /// The system also has a PC-AT-compatible dual-8259 setup.
+    pub const PCAT_COMPAT: u32 = 1;
+
+    pub fn new(sdt: Sdt<'a>) -> AcpiResult<Self> {
+        if sdt.signature() != Self::SIGNATURE {
+            return Err("Invalid MADT signature");
+        }
+        if sdt.data().len() < 8 {
+            return Err("MADT is too short");
+        }
+        Ok(Self { sdt })
+    }
+
+    /// 32-bit physical address of the local APIC. It may be overridden by [`MadtEntry::LocalApicAddressOverride`].
+    pub fn local_apic_address(&self) -> u32 {
+        read_u32(self.sdt.data(), 0).unwrap()
+    }
+
+    pub fn flags(&self) -> u32 {
+        read_u32(self.sdt.data(), 4).unwrap()
+    }
+
+    /// Physical address of the local APIC after applying the address override.
+    pub fn effective_local_apic_address(&self) -> u64 {
+        self.entries()
+            .find_map(|entry| match entry {
+                MadtEntry::LocalApicAddressOverride { address } => Some(address),
+                _ => None,
+            })
+            .unwrap_or(self.local_apic_address() as u64)
+    }
+
+    pub fn entries(&self) -> MadtEntries<'a> {
+        MadtEntries {
+            bytes: &self.sdt.data()[8..],
+        }
+    }
+
+    pub fn local_apics(&self) -> impl Iterator<Item = LocalApic> + 'a {
+        self.entries().filter_map(|entry| match entry {
+            MadtEntry::LocalApic(apic) => Some(apic),
+            _ => None,
+        })
+    }
+
+    pub fn io_apics(&self) -> impl Iterator<Item = IoApic> + 'a {
+        self.entries().filter_map(|entry| match entry {
+            MadtEntry::IoApic(apic) => Some(apic),
+            _ => None,
+        })
+    }
+
+    pub fn interrupt_source_overrides(&self) -> impl Iterator<Item = InterruptSourceOverride> + 'a {
+        self.entries().filter_map(|entry| match entry {
+            MadtEntry::InterruptSourceOverride(iso) => Some(iso),
+            _ => None,
+        })
+    }
+}
+
+/// Processor Local APIC, or Processor Local x2APIC when `x2apic` is true.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalApic {
+    pub processor_uid: u32,
+    pub apic_id: u32,
+    pub flags: u32,
+    pub x2apic: bool,
+}
+
+impl LocalApic {
+    pub const ENABLED: u32 = 1;
+    pub const ONLINE_CAPABLE: u32 = 2;
+
+    /// The processor can be used, either right away or after being brought online by the OS.
+    pub fn is_usable(&self) -> bool {
+        self.flags & (Self::ENABLED | Self::ONLINE_CAPABLE) != 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoApic {
+    pub io_apic_id: u8,
+    pub address: u32,
+    pub global_system_interrupt_base: u32,
+}
+
+/// Mapping of an ISA interrupt to a global system interrupt that differs from the identity mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterruptSourceOverride {
+    pub bus: u8,
+    pub source: u8,
+    pub global_system_interrupt: u32,
+    pub flags: MpsIntiFlags,
+}
+
+/// REF: https://uefi.org/specs/ACPI/6.5/05_ACPI_Software_Programming_Model.html#mps-inti-flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MpsIntiFlags(pub u16);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Polarity {
+    ConformsToBus,
+    ActiveHigh,
+    ActiveLow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerMode {
+    ConformsToBus,
+    Edge,
+    Level,
+}
+
+impl MpsIntiFlags {
+    pub fn polarity(&self) -> Polarity {
+        match self.0 & 0b11 {
+            0b01 => Polarity::ActiveHigh,
+            0b11 => Polarity::ActiveLow,
+            _ => Polarity::ConformsToBus,
+        }
+    }
+
+    pub fn trigger_mode(&self) -> TriggerMode {
+        match (self.0 >> 2) & 0b11 {
+            0b01 => TriggerMode::Edge,
+            0b11 => TriggerMode::Level,
+            _ => TriggerMode::ConformsToBus,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MadtEntry {
+    LocalApic(LocalApic),
+    IoApic(IoApic),
+    InterruptSourceOverride(InterruptSourceOverride),
+    NmiSource {
+        flags: MpsIntiFlags,
+        global_system_interrupt: u32,
+    },
+    /// `processor_uid` of 0xFF (or 0xFFFFFFFF for x2APIC) applies to all processors.
+    LocalApicNmi {
+        processor_uid: u32,
+        flags: MpsIntiFlags,
+        lint: u8,
+    },
+    LocalApicAddressOverride {
+        address: u64,
+    },
+    /// Entry type this parser does not interpret.
+    Other {
+        entry_type: u8,
+    },
+}
+
+pub struct MadtEntries<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for MadtEntries<'a> {
+    type Item = MadtEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry_type = read_u8(self.bytes, 0)?;
+        let length = read_u8(self.bytes, 1)? as usize;
+        if length < 2 || self.bytes.len() < length {
+            self.bytes = &[];
+            return None;
+        }
+
+        let entry = &self.bytes[..length];
+        self.bytes = &self.bytes[length..];
+
+        let parsed = match entry_type {
+            0 => Some(MadtEntry::LocalApic(LocalApic {
+                processor_uid: read_u8(entry, 2)? as u32,
+                apic_id: read_u8(entry, 3)? as u32,
+                flags: read_u32(entry, 4)?,
+                x2apic: false,
+            })),
+            1 => Some(MadtEntry::IoApic(IoApic {
+                io_apic_id: read_u8(entry, 2)?,
+                address: read_u32(entry, 4)?,
+                global_system_interrupt_base: read_u32(entry, 8)?,
+            })),
+            2 => Some(MadtEntry::InterruptSourceOverride(
+                InterruptSourceOverride {
+                    bus: read_u8(entry, 2)?,
+                    source: read_u8(entry, 3)?,
+                    global_system_interrupt: read_u32(entry, 4)?,
+                    flags: MpsIntiFlags(read_u16(entry, 8)?),
+                },
+            )),
+            3 => Some(MadtEntry::NmiSource {
+                flags: MpsIntiFlags(read_u16(entry, 2)?),
+                global_system_interrupt: read_u32(entry, 4)?,
+            }),
+            4 => Some(MadtEntry::LocalApicNmi {
+                processor_uid: read_u8(entry, 2)? as u32,
+                flags: MpsIntiFlags(read_u16(entry, 3)?),
+                lint: read_u8(entry, 5)?,
+            }),
+            5 => Some(MadtEntry::LocalApicAddressOverride {
+                address: read_u64(entry, 4)?,
+            }),
+            9 => Some(MadtEntry::LocalApic(LocalApic {
+                apic_id: read_u32(entry, 4)?,
+                flags: read_u32(entry, 8)?,
+                processor_uid: read_u32(entry, 12)?,
+                x2apic: true,
+            })),
+            10 => Some(MadtEntry::LocalApicNmi {
+                flags: MpsIntiFlags(read_u16(entry, 2)?),
+                processor_uid: read_u32(entry, 4)?,
+                lint: read_u8(entry, 8)?,
+            }),
+            _ => None,
+        };
+
+        Some(parsed.unwrap_or(MadtEntry::Other { entry_type }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn table(entries: &[&[u8]]) -> [u8; 128] {
+        let mut bytes = [0u8; 128];
+        bytes[0..4].copy_from_slice(b"APIC");
+        bytes[36..40].copy_from_slice(&0xFEE0_0000u32.to_le_bytes());
+        bytes[40..44].copy_from_slice(&Madt::PCAT_COMPAT.to_le_bytes());
+        let mut length = 44;
+        for entry in entries {
+            bytes[length..length + entry.len()].copy_from_slice(entry);
+            length += entry.len();
+        }
+        bytes[4..8].copy_from_slice(&(length as u32).to_le_bytes());
+        let sum = bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+        bytes[9] = 0u8.wrapping_sub(sum);
+        bytes
+    }
+
+    #[test]
+    fn parse_entries() {
+        let bytes = table(&[
+            &[0, 8, 0, 0, 1, 0, 0, 0],
+            &[0, 8, 1, 1, 0, 0, 0, 0],
+            &[1, 12, 0, 0, 0x00, 0x00, 0xC0, 0xFE, 0, 0, 0, 0],
+            &[2, 10, 0, 0, 2, 0, 0, 0, 0, 0],
+            &[2, 10, 0, 9, 9, 0, 0, 0, 0x0D, 0],
+            &[0x7F, 4, 0, 0],
+        ]);
+        let madt = Madt::new(Sdt::new(&bytes).unwrap()).unwrap();
+
+        assert_eq!(madt.local_apic_address(), 0xFEE0_0000);
+        assert_eq!(madt.local_apics().filter(|a| a.is_usable()).count(), 1);
+        assert_eq!(
+            madt.io_apics().next(),
+            Some(IoApic {
+                io_apic_id: 0,
+                address: 0xFEC0_0000,
+                global_system_interrupt_base: 0,
+            })
+        );
+
+        let sci = madt.interrupt_source_overrides().nth(1).unwrap();
+        assert_eq!(sci.global_system_interrupt, 9);
+        assert_eq!(sci.flags.polarity(), Polarity::ActiveHigh);
+        assert_eq!(sci.flags.trigger_mode(), TriggerMode::Level);
+
+        assert_eq!(
+            madt.entries().last(),
+            Some(MadtEntry::Other { entry_type: 0x7F })
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! REF: PCI Firmware Specification 3.0, 4.1.2 MCFG Table Description
+
+use crate::{
+    sdt::{read_u16, read_u64, read_u8},
+    AcpiResult, Sdt,
+};
+
+/// PCI Express memory mapped configuration space base address Description Table
+#[derive(Debug, Clone, Copy)]
+pub struct Mcfg<'a> {
+    sdt: Sdt<'a>,
+}
+
+impl<'a> Mcfg<'a> {
+    pub const SIGNATURE: &'static [u8; 4] = b"MCFG";
+
+    pub fn new(sdt: Sdt<'a>) -> AcpiResult<Self> {
+        if sdt.signature() != Self::SIGNATURE {
+            return Err("Invalid MCFG signature");
+        }
+        if sdt.data().len() < 8 {
+            return Err("MCFG is too short");
+        }
+        Ok(Self { sdt })
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = McfgEntry> + 'a {
+        // 8 reserved bytes precede the entries.
+        self.sdt.data()[8..]
+            .chunks_exact(16)
+            .map(|entry| McfgEntry {
+                base_address: read_u64(entry, 0).unwrap(),
+                pci_segment_group: read_u16(entry, 8).unwrap(),
+                start_bus: read_u8(entry, 10).unwrap(),
+                end_bus: read_u8(entry, 11).unwrap(),
+            })
+    }
+}
+
+/// ECAM region of a PCI segment group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct McfgEntry {
+    pub base_address: u64,
+    pub pci_segment_group: u16,
+    pub start_bus: u8,
+    pub end_bus: u8,
+}
+
+impl McfgEntry {
+    /// Physical address of the configuration space of a function.
+    pub fn config_address(&self, bus: u8, device: u8, function: u8) -> Option<u64> {
+        if bus < self.start_bus || bus > self.end_bus || device >= 32 || function >= 8 {
+            return None;
+        }
+
+        let offset =
+            ((bus - self.start_bus) as u64) << 20 | (device as u64) << 15 | (function as u64) << 12;
+        Some(self.base_address + offset)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sdt::test::table;
+
+    #[test]
+    fn entries() {
+        let bytes = table::<76>(
+            Mcfg::SIGNATURE,
+            &[
+                (44, &0xB000_0000u64.to_le_bytes()),
+                (54, &[0x00, 0xFF]),
+                (60, &0xC000_0000u64.to_le_bytes()),
+                (68, &1u16.to_le_bytes()),
+                (70, &[0x10, 0x1F]),
+            ],
+        );
+        let mcfg = Mcfg::new(Sdt::new(&bytes).unwrap()).unwrap();
+        let entries: [McfgEntry; 2] = core::array::from_fn(|i| mcfg.entries().nth(i).unwrap());
+        assert_eq!(mcfg.entries().count(), 2);
+        assert_eq!(
+            entries[1],
+            McfgEntry {
+                base_address: 0xC000_0000,
+                pci_segment_group: 1,
+                start_bus: 0x10,
+                end_bus: 0x1F,
+            }
+        );
+
+        assert_eq!(
+            entries[0].config_address(1, 2, 3),
+            Some(0xB000_0000 | 1 << 20 | 2 << 15 | 3 << 12)
+        );
+        // Buses are numbered from the start bus of the region.
+        assert_eq!(entries[1].config_address(0x11, 0, 0), Some(0xC010_0000));
+        assert_eq!(entries[1].config_address(0x0F, 0, 0), None);
+        assert_eq!(entries[0].config_address(0, 32, 0), None);
+        assert_eq!(entries[0].config_address(0, 0, 8), None);
+    }
+
+    #[test]
+    fn invalid() {
+        let short = table::<40>(Mcfg::SIGNATURE, &[]);
+        assert_eq!(
+            Mcfg::new(Sdt::new(&short).unwrap()).err(),
+            Some("MCFG is too short")
+        );
+        let other = table::<44>(b"HPET", &[]);
+        assert_eq!(
+            Mcfg::new(Sdt::new(&other).unwrap()).err(),
+            Some("Invalid MCFG signature")
+        );
+    }
+}
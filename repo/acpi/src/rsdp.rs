@@ -0,0 +1,123 @@
+//! REF: https://uefi.org/specs/ACPI/6.5/05_ACPI_Software_Programming_Model.html#root-system-description-pointer-rsdp-structure
+
+use core::mem::{offset_of, size_of};
+
+use crate::{sdt::checksum, AcpiResult};
+
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Rsdp {
+    signature: [u8; 8],
+    checksum: u8,
+    oem_id: [u8; 6],
+    revision: u8,
+    rsdt_address: u32,
+    length: u32,
+    xsdt_address: u64,
+    extended_checksum: u8,
+    _reserved: [u8; 3],
+}
+
+const _: () = assert!(size_of::<Rsdp>() == 36);
+
+impl Rsdp {
+    pub const SIGNATURE: &'static [u8; 8] = b"RSD PTR ";
+
+    /// Reads the RSDP and validates both checksums. Only ACPI 2.0 or later is accepted since the XSDT is required.
+    ///
+    /// # Safety
+    /// `address` must point to readable memory of at least 36 bytes.
+    pub unsafe fn from_address(address: usize) -> AcpiResult<&'static Self> {
+        let rsdp = &*(address as *const Self);
+        let bytes = core::slice::from_raw_parts(address as *const u8, size_of::<Self>());
+        rsdp.validate(bytes)?;
+        Ok(rsdp)
+    }
+
+    fn validate(&self, bytes: &[u8]) -> AcpiResult<()> {
+        if &self.signature != Self::SIGNATURE {
+            return Err("Invalid RSDP signature");
+        }
+        if checksum(&bytes[..offset_of!(Self, length)]) != 0 {
+            return Err("Invalid RSDP checksum");
+        }
+        if self.revision < 2 {
+            return Err("RSDP revision is older than ACPI 2.0");
+        }
+        if checksum(&bytes[..size_of::<Self>()]) != 0 {
+            return Err("Invalid RSDP extended checksum");
+        }
+        Ok(())
+    }
+
+    pub fn oem_id(&self) -> [u8; 6] {
+        self.oem_id
+    }
+
+    pub fn revision(&self) -> u8 {
+        self.revision
+    }
+
+    pub fn rsdt_address(&self) -> u32 {
+        self.rsdt_address
+    }
+
+    pub fn xsdt_address(&self) -> u64 {
+        self.xsdt_address
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    /// ACPI 2.0 RSDP bytes with valid checksums.
+    pub(crate) fn rsdp(revision: u8, xsdt_address: u64) -> [u8; 36] {
+        let mut bytes = [0; 36];
+        bytes[0..8].copy_from_slice(Rsdp::SIGNATURE);
+        bytes[9..15].copy_from_slice(b"MOOSFW");
+        bytes[15] = revision;
+        bytes[16..20].copy_from_slice(&0x7FF0_0000u32.to_le_bytes());
+        bytes[20..24].copy_from_slice(&36u32.to_le_bytes());
+        bytes[24..32].copy_from_slice(&xsdt_address.to_le_bytes());
+        bytes[8] = 0u8.wrapping_sub(checksum(&bytes[..20]));
+        bytes[32] = 0u8.wrapping_sub(checksum(&bytes));
+        bytes
+    }
+
+    fn parse(bytes: &[u8; 36]) -> AcpiResult<&Rsdp> {
+        unsafe { Rsdp::from_address(bytes.as_ptr() as usize) }
+    }
+
+    #[test]
+    fn valid() {
+        let bytes = rsdp(2, 0x7FF1_0000);
+        let rsdp = parse(&bytes).unwrap();
+        assert_eq!(&rsdp.oem_id(), b"MOOSFW");
+        assert_eq!(rsdp.revision(), 2);
+        assert_eq!(rsdp.rsdt_address(), 0x7FF0_0000);
+        assert_eq!(rsdp.xsdt_address(), 0x7FF1_0000);
+    }
+
+    #[test]
+    fn invalid() {
+        let mut bytes = rsdp(2, 0x7FF1_0000);
+        bytes[0] = b'X';
+        assert_eq!(parse(&bytes).err(), Some("Invalid RSDP signature"));
+
+        // The first checksum covers the ACPI 1.0 fields.
+        let mut bytes = rsdp(2, 0x7FF1_0000);
+        bytes[16] ^= 1;
+        assert_eq!(parse(&bytes).err(), Some("Invalid RSDP checksum"));
+
+        // The extended checksum covers the whole structure.
+        let mut bytes = rsdp(2, 0x7FF1_0000);
+        bytes[24] ^= 1;
+        assert_eq!(parse(&bytes).err(), Some("Invalid RSDP extended checksum"));
+
+        assert_eq!(
+            parse(&rsdp(0, 0)).err(),
+            Some("RSDP revision is older than ACPI 2.0")
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! REF: https://uefi.org/specs/ACPI/6.5/05_ACPI_Software_Programming_Model.html#system-description-table-header
+
+use crate::AcpiResult;
+
+pub(crate) const HEADER_SIZE: usize = 36;
+
+/// Sum of bytes, which must be zero for a valid table.
+pub(crate) fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |sum, b| sum.wrapping_add(*b))
+}
+
+pub(crate) fn read_u8(bytes: &[u8], offset: usize) -> Option<u8> {
+    bytes.get(offset).copied()
+}
+
+pub(crate) fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+pub(crate) fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+pub(crate) fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        bytes.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+/// System description table with a validated header and checksum.
+#[derive(Debug, Clone, Copy)]
+pub struct Sdt<'a> {
+    bytes: &'a [u8],
+}
+
+impl Sdt<'static> {
+    /// # Safety
+    /// `address` must point to a mapped table whose length field can be trusted to be readable.
+    pub unsafe fn from_address(address: usize) -> AcpiResult<Self> {
+        let header = core::slice::from_raw_parts(address as *const u8, HEADER_SIZE);
+        let length = read_u32(header, 4).unwrap() as usize;
+        Self::new(core::slice::from_raw_parts(address as *const u8, length))
+    }
+}
+
+impl<'a> Sdt<'a> {
+    pub fn new(bytes: &'a [u8]) -> AcpiResult<Self> {
+        let length = read_u32(bytes, 4).ok_or("Table is shorter than its header")? as usize;
+        if length < HEADER_SIZE || bytes.len() < length {
+            return Err("Invalid table length");
+        }
+
+        let bytes = &bytes[..length];
+        if checksum(bytes) != 0 {
+            return Err("Invalid table checksum");
+        }
+
+        Ok(Self { bytes })
+    }
+
+    pub fn signature(&self) -> &'a [u8; 4] {
+        self.bytes[0..4].try_into().unwrap()
+    }
+
+    pub fn length(&self) -> u32 {
+        self.bytes.len() as u32
+    }
+
+    pub fn revision(&self) -> u8 {
+        self.bytes[8]
+    }
+
+    pub fn oem_id(&self) -> &'a [u8; 6] {
+        self.bytes[10..16].try_into().unwrap()
+    }
+
+    pub fn oem_table_id(&self) -> &'a [u8; 8] {
+        self.bytes[16..24].try_into().unwrap()
+    }
+
+    pub fn oem_revision(&self) -> u32 {
+        read_u32(self.bytes, 24).unwrap()
+    }
+
+    /// Entire table including the header.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Table contents following the header.
+    pub fn data(&self) -> &'a [u8] {
+        &self.bytes[HEADER_SIZE..]
+    }
+}
+
+/// REF: https://uefi.org/specs/ACPI/6.5/05_ACPI_Software_Programming_Model.html#generic-address-structure-gas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenericAddress {
+    pub address_space_id: u8,
+    pub register_bit_width: u8,
+    pub register_bit_offset: u8,
+    pub access_size: u8,
+    pub address: u64,
+}
+
+impl GenericAddress {
+    pub const SYSTEM_MEMORY: u8 = 0;
+    pub const SYSTEM_IO: u8 = 1;
+    pub const PCI_CONFIGURATION: u8 = 2;
+
+    pub(crate) fn read(bytes: &[u8], offset: usize) -> Option<Self> {
+        Some(Self {
+            address_space_id: read_u8(bytes, offset)?,
+            register_bit_width: read_u8(bytes, offset + 1)?,
+            register_bit_offset: read_u8(bytes, offset + 2)?,
+            access_size: read_u8(bytes, offset + 3)?,
+            address: read_u64(bytes, offset + 4)?,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    /// Table of `N` bytes with `fields` written at their offsets, and a valid length and checksum.
+    pub(crate) fn table<const N: usize>(signature: &[u8; 4], fields: &[(usize, &[u8])]) -> [u8; N] {
+        let mut bytes = [0; N];
+        bytes[0..4].copy_from_slice(signature);
+        bytes[4..8].copy_from_slice(&(N as u32).to_le_bytes());
+        for (offset, field) in fields {
+            bytes[*offset..offset + field.len()].copy_from_slice(field);
+        }
+        bytes[9] = 0u8.wrapping_sub(checksum(&bytes));
+        bytes
+    }
+
+    #[test]
+    fn header() {
+        let bytes = table::<40>(
+            b"TEST",
+            &[
+                (8, &[3]),
+                (10, b"MOOSFW"),
+                (16, b"MOOSTEST"),
+                (24, &7u32.to_le_bytes()),
+                (36, &[1, 2, 3, 4]),
+            ],
+        );
+        // Bytes past the length are not part of the table.
+        let mut padded = [0xFF; 48];
+        padded[..40].copy_from_slice(&bytes);
+
+        let sdt = Sdt::new(&padded).unwrap();
+        assert_eq!(sdt.signature(), b"TEST");
+        assert_eq!(sdt.length(), 40);
+        assert_eq!(sdt.revision(), 3);
+        assert_eq!(sdt.oem_id(), b"MOOSFW");
+        assert_eq!(sdt.oem_table_id(), b"MOOSTEST");
+        assert_eq!(sdt.oem_revision(), 7);
+        assert_eq!(sdt.data(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn invalid_tables() {
+        let mut bytes = table::<40>(b"TEST", &[]);
+        assert_eq!(
+            Sdt::new(&bytes[..3]).err(),
+            Some("Table is shorter than its header")
+        );
+        assert_eq!(Sdt::new(&bytes[..39]).err(), Some("Invalid table length"));
+
+        bytes[36] = 1;
+        assert_eq!(Sdt::new(&bytes).err(), Some("Invalid table checksum"));
+
+        let mut short = table::<36>(b"TEST", &[]);
+        short[4] = 8;
+        assert_eq!(Sdt::new(&short).err(), Some("Invalid table length"));
+    }
+}
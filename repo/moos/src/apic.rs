@@ -0,0 +1,549 @@
+//! Local APIC of the processor and IO APICs found through the MADT, which deliver interrupts
+//! in place of the 8259s.
+//!
+//! The local APIC runs in x2APIC mode when the processor supports it, with its registers as MSRs,
+//! and in xAPIC mode otherwise, with its registers in memory. ISA IRQs arrive at the IO APICs on
+//! their own number, unless an interrupt source override of the MADT moves them.
+//!
+//! REF: Intel SDM Vol. 3A, 11 Advanced Programmable Interrupt Controller (APIC)
+//! REF: https://pdos.csail.mit.edu/6.828/2016/readings/ia32/ioapic.pdf
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use acpi::{
+    madt::{InterruptSourceOverride, MadtEntry, MpsIntiFlags, Polarity, TriggerMode},
+    Acpi, Madt,
+};
+use uefi::{efi_configuration_table::guid, EfiSystemTable};
+
+use crate::{
+    cpu,
+    interrupts::{self, Handler},
+    pic,
+    sync::SpinLock,
+};
+
+/// Raised by the local APIC when the interrupt it was delivering went away. It takes no EOI.
+pub const SPURIOUS_VECTOR: u8 = 0xFF;
+/// Vector of ISA IRQ 0, followed by those of the others.
+#[allow(dead_code)]
+pub const ISA_IRQ_VECTOR: u8 = 0x30;
+pub const ISA_IRQ_COUNT: usize = 16;
+
+const MAX_IO_APICS: usize = 8;
+
+const IA32_APIC_BASE: u32 = 0x1B;
+const APIC_BASE_X2APIC: u64 = 1 << 10;
+const APIC_BASE_ENABLE: u64 = 1 << 11;
+/// In x2APIC mode, each register is an MSR from this one, in the order of the xAPIC registers.
+const X2APIC_MSR_BASE: u32 = 0x800;
+
+// Features in CPUID leaf 1.
+const CPUID_EDX_APIC: u32 = 1 << 9;
+const CPUID_ECX_X2APIC: u32 = 1 << 21;
+
+// Offsets of the local APIC registers in xAPIC mode.
+const ID: u32 = 0x20;
+const TASK_PRIORITY: u32 = 0x80;
+const END_OF_INTERRUPT: u32 = 0xB0;
+const SPURIOUS_INTERRUPT: u32 = 0xF0;
+const INTERRUPT_COMMAND: u32 = 0x300;
+const INTERRUPT_COMMAND_HIGH: u32 = 0x310;
+const LVT_TIMER: u32 = 0x320;
+const LVT_LINT0: u32 = 0x350;
+const LVT_LINT1: u32 = 0x360;
+const LVT_ERROR: u32 = 0x370;
+
+const SPURIOUS_INTERRUPT_ENABLE: u32 = 1 << 8;
+// Bits shared by LVT entries, interrupt commands and IO APIC redirection entries.
+const DELIVERY_NMI: u32 = 0b100 << 8;
+const ACTIVE_LOW: u32 = 1 << 13;
+const LEVEL_TRIGGERED: u32 = 1 << 15;
+const MASKED: u32 = 1 << 16;
+/// Destination shorthand of an interrupt command to the sending processor.
+const TO_SELF: u32 = 0b01 << 18;
+
+// IO APIC registers, reached through a select register and a window onto the selected one.
+const IO_REGISTER_SELECT: usize = 0x00;
+const IO_WINDOW: usize = 0x10;
+const IO_APIC_VERSION: u32 = 0x01;
+const IO_REDIRECTION_TABLE: u32 = 0x10;
+
+#[derive(Debug, Clone, Copy)]
+enum LocalApic {
+    XApic { base: usize },
+    X2Apic,
+}
+
+impl LocalApic {
+    fn read(&self, register: u32) -> u32 {
+        match *self {
+            Self::XApic { base } => unsafe {
+                ((base + register as usize) as *const u32).read_volatile()
+            },
+            Self::X2Apic => unsafe { cpu::read_msr(X2APIC_MSR_BASE + register / 16) as u32 },
+        }
+    }
+
+    fn write(&self, register: u32, value: u32) {
+        match *self {
+            Self::XApic { base } => unsafe {
+                ((base + register as usize) as *mut u32).write_volatile(value)
+            },
+            Self::X2Apic => unsafe {
+                cpu::write_msr(X2APIC_MSR_BASE + register / 16, value.into())
+            },
+        }
+    }
+
+    fn id(&self) -> u32 {
+        match self {
+            Self::XApic { .. } => self.read(ID) >> 24,
+            Self::X2Apic => self.read(ID),
+        }
+    }
+
+    fn send_interrupt_command(&self, command: u32, destination: u32) {
+        match self {
+            // Writing the low half sends the command.
+            Self::XApic { .. } => {
+                self.write(INTERRUPT_COMMAND_HIGH, destination << 24);
+                self.write(INTERRUPT_COMMAND, command);
+            }
+            // A single 64-bit register in x2APIC mode.
+            Self::X2Apic => unsafe {
+                cpu::write_msr(
+                    X2APIC_MSR_BASE + INTERRUPT_COMMAND / 16,
+                    u64::from(destination) << 32 | u64::from(command),
+                )
+            },
+        }
+    }
+}
+
+/// LVT entry delivering a local interrupt pin as an NMI, which is always edge triggered.
+fn nmi_entry(flags: MpsIntiFlags) -> u32 {
+    match flags.polarity() {
+        Polarity::ActiveLow => DELIVERY_NMI | ACTIVE_LOW,
+        Polarity::ActiveHigh | Polarity::ConformsToBus => DELIVERY_NMI,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct IoApic {
+    base: usize,
+    /// Global system interrupt of the first input.
+    first_interrupt: u32,
+    input_count: u32,
+}
+
+impl IoApic {
+    /// # Safety
+    /// An IO APIC must be at `base`, mapped at its physical address, and nothing else may use it.
+    unsafe fn new(base: usize, first_interrupt: u32) -> Self {
+        let mut io_apic = Self {
+            base,
+            first_interrupt,
+            input_count: 0,
+        };
+        // The highest input is in bits 16 to 23.
+        io_apic.input_count = (io_apic.read(IO_APIC_VERSION) >> 16 & 0xFF) + 1;
+        io_apic
+    }
+
+    fn read(&self, register: u32) -> u32 {
+        unsafe {
+            ((self.base + IO_REGISTER_SELECT) as *mut u32).write_volatile(register);
+            ((self.base + IO_WINDOW) as *const u32).read_volatile()
+        }
+    }
+
+    fn write(&mut self, register: u32, value: u32) {
+        unsafe {
+            ((self.base + IO_REGISTER_SELECT) as *mut u32).write_volatile(register);
+            ((self.base + IO_WINDOW) as *mut u32).write_volatile(value);
+        }
+    }
+
+    fn handles(&self, global_system_interrupt: u32) -> bool {
+        (self.first_interrupt..self.first_interrupt + self.input_count)
+            .contains(&global_system_interrupt)
+    }
+
+    /// # Panics
+    /// If the IO APIC does not handle `global_system_interrupt`.
+    fn set_redirection(&mut self, global_system_interrupt: u32, entry: u64) {
+        assert!(self.handles(global_system_interrupt));
+        let register = IO_REDIRECTION_TABLE + 2 * (global_system_interrupt - self.first_interrupt);
+        // Masked while the halves disagree.
+        self.write(register, MASKED);
+        self.write(register + 1, (entry >> 32) as u32);
+        self.write(register, entry as u32);
+    }
+
+    fn mask_all(&mut self) {
+        for input in 0..self.input_count {
+            self.set_redirection(self.first_interrupt + input, MASKED.into());
+        }
+    }
+}
+
+/// Where an ISA IRQ arrives at the IO APICs, and how it is signaled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Route {
+    global_system_interrupt: u32,
+    active_low: bool,
+    level_triggered: bool,
+}
+
+impl Route {
+    /// Redirection entry delivering the interrupt to `vector` of the local APIC with ID `destination`.
+    fn redirection_entry(&self, vector: u8, destination: u8) -> u64 {
+        let mut low = u32::from(vector);
+        if self.active_low {
+            low |= ACTIVE_LOW;
+        }
+        if self.level_triggered {
+            low |= LEVEL_TRIGGERED;
+        }
+        u64::from(destination) << 56 | u64::from(low)
+    }
+}
+
+/// ISA IRQs are active high and edge triggered on the input of their number, unless overridden.
+fn isa_routes(overrides: impl Iterator<Item = InterruptSourceOverride>) -> [Route; ISA_IRQ_COUNT] {
+    let mut routes = [Route {
+        global_system_interrupt: 0,
+        active_low: false,
+        level_triggered: false,
+    }; ISA_IRQ_COUNT];
+    for (irq, route) in routes.iter_mut().enumerate() {
+        route.global_system_interrupt = irq as u32;
+    }
+
+    for source_override in overrides {
+        let irq = source_override.source as usize;
+        // Bus 0 is ISA.
+        if source_override.bus != 0 || irq >= ISA_IRQ_COUNT {
+            continue;
+        }
+        routes[irq] = Route {
+            global_system_interrupt: source_override.global_system_interrupt,
+            active_low: source_override.flags.polarity() == Polarity::ActiveLow,
+            level_triggered: source_override.flags.trigger_mode() == TriggerMode::Level,
+        };
+    }
+    routes
+}
+
+/// 0 before `init`, `X2APIC` in x2APIC mode, or else the page aligned address of the xAPIC
+/// registers. Interrupt handlers read it without a lock to send their EOI.
+static LOCAL_APIC: AtomicUsize = AtomicUsize::new(0);
+const X2APIC: usize = 1;
+
+fn local_apic() -> Option<LocalApic> {
+    match LOCAL_APIC.load(Ordering::Acquire) {
+        0 => None,
+        X2APIC => Some(LocalApic::X2Apic),
+        base => Some(LocalApic::XApic { base }),
+    }
+}
+
+struct Routing {
+    io_apics: [Option<IoApic>; MAX_IO_APICS],
+    isa: [Route; ISA_IRQ_COUNT],
+    /// Local APIC ID of the processor receiving the IRQs.
+    destination: u32,
+}
+
+static ROUTING: SpinLock<Option<Routing>> = SpinLock::new(None);
+
+/// Finds the MADT through the ACPI 2.0 RSDP in the configuration table.
+pub fn find_madt(system_table: &EfiSystemTable) -> Result<Madt<'static>, &'static str> {
+    let rsdp = system_table
+        .find_configuration_table(&guid::EFI_ACPI_20_TABLE_GUID)
+        .ok_or("Failed to find the ACPI 2.0 RSDP")?;
+    // The firmware identity-maps the ACPI tables.
+    unsafe { Acpi::from_rsdp(rsdp.as_ptr() as usize)?.madt() }
+}
+
+/// Disables the 8259s, enables the local APIC of this processor with the NMI pins of the MADT,
+/// and masks every input of the IO APICs until `register_isa_irq`. Interrupts stay disabled.
+pub fn init(madt: &Madt) -> Result<(), &'static str> {
+    if local_apic().is_some() {
+        return Err("APICs are already initialized");
+    }
+    let features = unsafe { core::arch::x86_64::__cpuid(1) };
+    if features.edx & CPUID_EDX_APIC == 0 {
+        return Err("Processor has no local APIC");
+    }
+    if madt.io_apics().next().is_none() {
+        return Err("MADT lists no IO APIC");
+    }
+
+    pic::disable();
+
+    let apic_base = unsafe { cpu::read_msr(IA32_APIC_BASE) };
+    let local_apic = if features.ecx & CPUID_ECX_X2APIC != 0 {
+        // x2APIC mode can only be entered from an enabled xAPIC.
+        unsafe {
+            if apic_base & APIC_BASE_X2APIC == 0 {
+                cpu::write_msr(IA32_APIC_BASE, apic_base | APIC_BASE_ENABLE);
+            }
+            cpu::write_msr(
+                IA32_APIC_BASE,
+                apic_base | APIC_BASE_ENABLE | APIC_BASE_X2APIC,
+            );
+        }
+        LocalApic::X2Apic
+    } else {
+        unsafe { cpu::write_msr(IA32_APIC_BASE, apic_base | APIC_BASE_ENABLE) };
+        LocalApic::XApic {
+            base: madt.effective_local_apic_address() as usize,
+        }
+    };
+
+    local_apic.write(TASK_PRIORITY, 0);
+    for register in [LVT_TIMER, LVT_LINT0, LVT_LINT1, LVT_ERROR] {
+        local_apic.write(register, MASKED);
+    }
+    let id = local_apic.id();
+    let processor_uid = madt
+        .local_apics()
+        .find(|apic| apic.apic_id == id)
+        .map(|apic| apic.processor_uid);
+    for entry in madt.entries() {
+        let MadtEntry::LocalApicNmi {
+            processor_uid: nmi_processor_uid,
+            flags,
+            lint,
+        } = entry
+        else {
+            continue;
+        };
+        // 0xFF, or 0xFFFFFFFF in x2APIC entries, stands for every processor.
+        let applies = matches!(nmi_processor_uid, 0xFF | u32::MAX)
+            || Some(nmi_processor_uid) == processor_uid;
+        let register = match lint {
+            0 => LVT_LINT0,
+            1 => LVT_LINT1,
+            _ => continue,
+        };
+        if applies {
+            local_apic.write(register, nmi_entry(flags));
+        }
+    }
+    local_apic.write(
+        SPURIOUS_INTERRUPT,
+        SPURIOUS_INTERRUPT_ENABLE | u32::from(SPURIOUS_VECTOR),
+    );
+
+    let mut io_apics = [None; MAX_IO_APICS];
+    for (slot, io_apic) in io_apics.iter_mut().zip(madt.io_apics()) {
+        let mut io_apic = unsafe {
+            IoApic::new(
+                io_apic.address as usize,
+                io_apic.global_system_interrupt_base,
+            )
+        };
+        io_apic.mask_all();
+        *slot = Some(io_apic);
+    }
+    *ROUTING.lock() = Some(Routing {
+        io_apics,
+        isa: isa_routes(madt.interrupt_source_overrides()),
+        destination: id,
+    });
+
+    let encoded = match local_apic {
+        LocalApic::XApic { base } => base,
+        LocalApic::X2Apic => X2APIC,
+    };
+    LOCAL_APIC.store(encoded, Ordering::Release);
+    Ok(())
+}
+
+pub fn is_x2apic() -> bool {
+    matches!(local_apic(), Some(LocalApic::X2Apic))
+}
+
+/// Local APIC ID of this processor, once `init` succeeded.
+#[allow(dead_code)]
+pub fn id() -> Option<u32> {
+    local_apic().map(|apic| apic.id())
+}
+
+/// Tells the local APIC that the interrupt being handled is done, letting lower priority ones in.
+pub fn end_of_interrupt() {
+    if let Some(apic) = local_apic() {
+        apic.write(END_OF_INTERRUPT, 0);
+    }
+}
+
+/// Raises `vector` on this processor.
+#[allow(dead_code)]
+pub fn send_self_interrupt(vector: u8) -> Result<(), &'static str> {
+    let apic = local_apic().ok_or("APICs are not initialized")?;
+    apic.send_interrupt_command(u32::from(vector) | TO_SELF, 0);
+    Ok(())
+}
+
+/// Routes ISA `irq` to this processor and calls `handler` on it. Returns the vector of the IRQ.
+#[allow(dead_code)]
+pub fn register_isa_irq(irq: u8, handler: Handler) -> Result<u8, &'static str> {
+    if irq as usize >= ISA_IRQ_COUNT {
+        return Err("Not an ISA IRQ");
+    }
+    let vector = ISA_IRQ_VECTOR + irq;
+
+    let mut routing = ROUTING.lock();
+    let routing = routing.as_mut().ok_or("APICs are not initialized")?;
+    let route = routing.isa[irq as usize];
+    // Physical destinations of IO APICs have 8 bits; more needs interrupt remapping.
+    let destination = u8::try_from(routing.destination)
+        .map_err(|_| "Local APIC ID is too large for the IO APIC")?;
+    let io_apic = routing
+        .io_apics
+        .iter_mut()
+        .flatten()
+        .find(|io_apic| io_apic.handles(route.global_system_interrupt))
+        .ok_or("Failed to find the IO APIC of the IRQ")?;
+
+    interrupts::set_handler(vector, Some(handler));
+    io_apic.set_redirection(
+        route.global_system_interrupt,
+        route.redirection_entry(vector, destination),
+    );
+    Ok(vector)
+}
+
+/// Masks ISA `irq` and forgets its handler.
+#[allow(dead_code)]
+pub fn unregister_isa_irq(irq: u8) -> Result<(), &'static str> {
+    if irq as usize >= ISA_IRQ_COUNT {
+        return Err("Not an ISA IRQ");
+    }
+
+    let mut routing = ROUTING.lock();
+    let routing = routing.as_mut().ok_or("APICs are not initialized")?;
+    let route = routing.isa[irq as usize];
+    if let Some(io_apic) = routing
+        .io_apics
+        .iter_mut()
+        .flatten()
+        .find(|io_apic| io_apic.handles(route.global_system_interrupt))
+    {
+        io_apic.set_redirection(route.global_system_interrupt, MASKED.into());
+    }
+    interrupts::set_handler(ISA_IRQ_VECTOR + irq, None);
+    Ok(())
+}
+
+#[cfg(all(test, not(target_os = "uefi")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn routes() {
+        // As in the MADT of QEMU: the PIT moved to input 2, and a level-triggered PCI interrupt.
+        let overrides = [
+            InterruptSourceOverride {
+                bus: 0,
+                source: 0,
+                global_system_interrupt: 2,
+                flags: MpsIntiFlags(0),
+            },
+            InterruptSourceOverride {
+                bus: 0,
+                source: 9,
+                global_system_interrupt: 9,
+                flags: MpsIntiFlags(0b1101),
+            },
+        ];
+        let routes = isa_routes(overrides.into_iter());
+        assert_eq!(
+            routes[0],
+            Route {
+                global_system_interrupt: 2,
+                active_low: false,
+                level_triggered: false,
+            }
+        );
+        assert_eq!(routes[4].global_system_interrupt, 4);
+        assert!(routes[9].level_triggered && !routes[9].active_low);
+
+        assert_eq!(routes[0].redirection_entry(0x30, 1), 0x0100_0000_0000_0030);
+        let active_low_level = Route {
+            global_system_interrupt: 11,
+            active_low: true,
+            level_triggered: true,
+        };
+        assert_eq!(active_low_level.redirection_entry(0x3B, 0), 0xA03B);
+    }
+
+    #[test]
+    fn nmi_entries() {
+        assert_eq!(nmi_entry(MpsIntiFlags(0)), 0x400);
+        assert_eq!(nmi_entry(MpsIntiFlags(0b0111)), 0x2400);
+    }
+}
+
+#[cfg(all(test, target_os = "uefi"))]
+mod qemu_test {
+    use core::sync::atomic::AtomicUsize;
+
+    use super::*;
+    use crate::{interrupts::Context, port::outb};
+
+    /// Vector for the tests, above those of the ISA IRQs.
+    const TEST_VECTOR: u8 = 0x40;
+
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    fn count(_context: &mut Context) {
+        COUNT.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Enables interrupts until `count` ran, or gives up after a while.
+    fn wait_for_count() -> usize {
+        cpu::enable_interrupts();
+        for _ in 0..100_000_000 {
+            if COUNT.load(Ordering::Relaxed) > 0 {
+                break;
+            }
+            core::hint::spin_loop();
+        }
+        cpu::disable_interrupts();
+        COUNT.swap(0, Ordering::Relaxed)
+    }
+
+    /// efi_main sets up the APICs before running the tests.
+    #[test_case]
+    fn self_interrupt() {
+        assert!(id().is_some());
+        interrupts::set_handler(TEST_VECTOR, Some(count));
+        send_self_interrupt(TEST_VECTOR).unwrap();
+        let count = wait_for_count();
+        interrupts::set_handler(TEST_VECTOR, None);
+        assert_eq!(count, 1);
+    }
+
+    /// The PIT is on ISA IRQ 0, which QEMU overrides to input 2 of the IO APIC.
+    #[test_case]
+    fn isa_irq() {
+        const PIT_CHANNEL_0: u16 = 0x40;
+        const PIT_COMMAND: u16 = 0x43;
+        /// Channel 0, low then high byte of the count, mode 0: interrupt on terminal count.
+        const ONE_SHOT: u8 = 0x30;
+
+        assert_eq!(register_isa_irq(0, count), Ok(ISA_IRQ_VECTOR));
+        unsafe {
+            outb(PIT_COMMAND, ONE_SHOT);
+            outb(PIT_CHANNEL_0, 0x00);
+            outb(PIT_CHANNEL_0, 0x10);
+        }
+        let count = wait_for_count();
+        unregister_isa_irq(0).unwrap();
+        assert!(count >= 1);
+    }
+}
@@ -0,0 +1,492 @@
+//! Stack backtraces following the chain of saved frame pointers, which every function keeps since
+//! the workspace builds with `-Cforce-frame-pointers`.
+//!
+//! Functions are named from the `.symbols` section that scripts/embed_symbols.py appends to the
+//! image before it boots. Without it, return addresses are shown as offsets into the image, which
+//! `llvm-symbolizer --obj=moos.efi` resolves with the PDB.
+
+use core::{
+    fmt::{self, Write},
+    mem::size_of,
+    ops::Range,
+};
+
+/// Frames beyond this are not shown, which also ends walks through a corrupted chain.
+const MAX_DEPTH: usize = 64;
+/// Largest distance between two frame pointers that is believed to be a stack frame.
+const MAX_FRAME_SIZE: usize = 1 << 20;
+
+/// The PE loader maps the headers on the first page of the image.
+const HEADERS_SIZE: usize = 0x1000;
+const SECTION_HEADER_SIZE: usize = 40;
+const SYMBOLS_SECTION: &[u8; 8] = b".symbols";
+const SYMBOLS_MAGIC: &[u8; 4] = b"MSYM";
+const SYMBOL_ENTRY_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+    /// RBP in the frame, where the RBP of the caller is saved.
+    pub frame_pointer: usize,
+    /// Where the frame returns to in its caller, just after the call.
+    pub return_address: usize,
+}
+
+/// Frames from the innermost outwards.
+///
+/// The first frame pointer must be above the stack pointer by less than `MAX_FRAME_SIZE`, and each
+/// step checks that the saved frame pointer is aligned and above the current one by at most
+/// `MAX_FRAME_SIZE`, as the stack grows down, so a corrupted chain ends the walk instead of
+/// reading arbitrary memory.
+#[derive(Debug, Clone)]
+pub struct Frames {
+    frame_pointer: usize,
+    depth: usize,
+}
+
+impl Frames {
+    /// Starts from `frame_pointer` if it lies within `MAX_FRAME_SIZE` above `stack_pointer`,
+    /// which code without frame pointers may leave RBP anywhere but.
+    ///
+    /// # Safety
+    /// `stack_pointer` must be on a stack mapped for `MAX_FRAME_SIZE` above it. Frames whose
+    /// callers leave that stack must fail the checks of the walk.
+    pub unsafe fn new(frame_pointer: usize, stack_pointer: usize) -> Self {
+        let on_stack = frame_pointer
+            .checked_sub(stack_pointer)
+            .is_some_and(|offset| offset < MAX_FRAME_SIZE);
+        Self {
+            frame_pointer: if on_stack { frame_pointer } else { 0 },
+            depth: 0,
+        }
+    }
+
+    /// Frames of the caller and its callers.
+    #[inline(always)]
+    pub fn current() -> Self {
+        let (frame_pointer, stack_pointer): (usize, usize);
+        unsafe {
+            core::arch::asm!("mov {}, rbp", out(reg) frame_pointer, options(nomem, nostack, preserves_flags));
+            core::arch::asm!("mov {}, rsp", out(reg) stack_pointer, options(nomem, nostack, preserves_flags));
+            Self::new(frame_pointer, stack_pointer)
+        }
+    }
+}
+
+impl Iterator for Frames {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        let frame_pointer = self.frame_pointer;
+        if frame_pointer == 0
+            || frame_pointer % size_of::<usize>() != 0
+            || frame_pointer > usize::MAX - 2 * size_of::<usize>()
+            || self.depth >= MAX_DEPTH
+        {
+            return None;
+        }
+
+        // `push rbp; mov rbp, rsp` in the prologue leaves the return address right above the saved RBP.
+        let slots = frame_pointer as *const usize;
+        let (caller, return_address) = unsafe { (slots.read(), slots.add(1).read()) };
+        if return_address == 0 {
+            return None;
+        }
+
+        self.depth += 1;
+        self.frame_pointer = match caller.checked_sub(frame_pointer) {
+            Some(1..=MAX_FRAME_SIZE) => caller,
+            _ => 0,
+        };
+        Some(Frame {
+            frame_pointer,
+            return_address,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol<'a> {
+    pub name: &'a str,
+    /// Offset of the function from the image base.
+    pub start: usize,
+    pub size: usize,
+}
+
+/// Functions of the image, in the format written by scripts/embed_symbols.py:
+///
+/// ```text
+/// "MSYM", count: u32
+/// count * (start: u32, size: u32, name_offset: u32, name_length: u32), sorted by start
+/// names in UTF-8, name_offset being relative to the end of the entries
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SymbolMap<'a> {
+    entries: &'a [u8],
+    names: &'a [u8],
+}
+
+impl<'a> SymbolMap<'a> {
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, &'static str> {
+        if bytes.get(..4) != Some(SYMBOLS_MAGIC) {
+            return Err("Failed to find the magic of the symbol map");
+        }
+        let count = read_u32(bytes, 4).ok_or("Symbol map is truncated")? as usize;
+        let entries = count
+            .checked_mul(SYMBOL_ENTRY_SIZE)
+            .and_then(|size| bytes.get(8..8 + size))
+            .ok_or("Symbol map is truncated")?;
+        Ok(Self {
+            entries,
+            names: &bytes[8 + entries.len()..],
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len() / SYMBOL_ENTRY_SIZE
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Function containing `offset` from the image base.
+    pub fn lookup(&self, offset: usize) -> Option<Symbol<'a>> {
+        // Index of the last function starting at or before `offset`.
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let middle = (low + high) / 2;
+            if self.entry(middle)?.0 as usize <= offset {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        let (start, size, name_offset, name_length) = self.entry(low.checked_sub(1)?)?;
+        let (start, size) = (start as usize, size as usize);
+        if offset >= start + size {
+            return None;
+        }
+
+        let name_offset = name_offset as usize;
+        let name = self
+            .names
+            .get(name_offset..name_offset.checked_add(name_length as usize)?)?;
+        Some(Symbol {
+            name: core::str::from_utf8(name).ok()?,
+            start,
+            size,
+        })
+    }
+
+    fn entry(&self, index: usize) -> Option<(u32, u32, u32, u32)> {
+        let offset = index * SYMBOL_ENTRY_SIZE;
+        Some((
+            read_u32(self.entries, offset)?,
+            read_u32(self.entries, offset + 4)?,
+            read_u32(self.entries, offset + 8)?,
+            read_u32(self.entries, offset + 12)?,
+        ))
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    let bytes = bytes.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Size of the image and the offsets of its `.symbols` section, from its PE headers.
+///
+/// REF: https://learn.microsoft.com/en-us/windows/win32/debug/pe-format
+fn parse_headers(headers: &[u8]) -> Result<(usize, Option<Range<usize>>), &'static str> {
+    const INVALID: &str = "Failed to parse the PE headers of the image";
+    if headers.get(..2) != Some(b"MZ") {
+        return Err(INVALID);
+    }
+    let pe = read_u32(headers, 0x3C).ok_or(INVALID)? as usize;
+    if headers.get(pe..pe + 4) != Some(b"PE\0\0") {
+        return Err(INVALID);
+    }
+    let section_count = read_u16(headers, pe + 6).ok_or(INVALID)? as usize;
+    let optional_header_size = read_u16(headers, pe + 20).ok_or(INVALID)? as usize;
+    let optional_header = pe + 24;
+    // PE32+, as for every x86-64 image.
+    if read_u16(headers, optional_header) != Some(0x20B) {
+        return Err(INVALID);
+    }
+    let image_size = read_u32(headers, optional_header + 56).ok_or(INVALID)? as usize;
+
+    let section_headers = optional_header + optional_header_size;
+    for i in 0..section_count {
+        let header = section_headers + i * SECTION_HEADER_SIZE;
+        if headers.get(header..header + 8) != Some(SYMBOLS_SECTION) {
+            continue;
+        }
+        let size = read_u32(headers, header + 8).ok_or(INVALID)? as usize;
+        let start = read_u32(headers, header + 12).ok_or(INVALID)? as usize;
+        if start + size > image_size {
+            return Err(INVALID);
+        }
+        return Ok((image_size, Some(start..start + size)));
+    }
+    Ok((image_size, None))
+}
+
+/// The kernel as loaded in memory, to tell return addresses into it and name them.
+#[derive(Debug, Clone, Copy)]
+pub struct Image {
+    base: usize,
+    size: usize,
+    symbols: Option<SymbolMap<'static>>,
+}
+
+impl Image {
+    /// The running image, located through the `__ImageBase` symbol of the linker.
+    pub fn current() -> Result<Self, &'static str> {
+        extern "C" {
+            static __ImageBase: u8;
+        }
+        unsafe { Self::from_base(core::ptr::addr_of!(__ImageBase) as usize) }
+    }
+
+    /// # Safety
+    /// A PE image must be loaded at `base` and stay there.
+    unsafe fn from_base(base: usize) -> Result<Self, &'static str> {
+        let headers = core::slice::from_raw_parts(base as *const u8, HEADERS_SIZE);
+        let (size, symbols) = parse_headers(headers)?;
+        let symbols = match symbols {
+            Some(range) => {
+                let bytes =
+                    core::slice::from_raw_parts((base + range.start) as *const u8, range.len());
+                Some(SymbolMap::from_bytes(bytes)?)
+            }
+            None => None,
+        };
+        Ok(Self {
+            base,
+            size,
+            symbols,
+        })
+    }
+
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    pub fn contains(&self, address: usize) -> bool {
+        (self.base..self.base + self.size).contains(&address)
+    }
+
+    #[allow(dead_code)]
+    pub fn has_symbols(&self) -> bool {
+        self.symbols.is_some()
+    }
+
+    /// Function containing `address`, if the image has symbols.
+    pub fn symbol(&self, address: usize) -> Option<Symbol<'static>> {
+        if !self.contains(address) {
+            return None;
+        }
+        self.symbols?.lookup(address - self.base)
+    }
+}
+
+/// Writes one line per frame returning into `image`, up to the first one that does not.
+///
+/// Frames outside the image belong to the firmware, which may not keep frame pointers.
+pub fn write(writer: &mut impl Write, image: &Image, frames: Frames) -> fmt::Result {
+    writeln!(writer, "Backtrace:")?;
+    for (i, frame) in frames
+        .take_while(|frame| image.contains(frame.return_address))
+        .enumerate()
+    {
+        let address = frame.return_address;
+        write!(writer, "{:>4}: {:#018x} ", i, address)?;
+        // A call to a function that never returns may be the last instruction of the caller,
+        // in which case the return address is already past its end.
+        match image.symbol(address - 1) {
+            Some(symbol) => writeln!(
+                writer,
+                "{}+{:#x}",
+                symbol.name,
+                address - image.base - symbol.start
+            )?,
+            None => writeln!(writer, "moos.efi+{:#x}", address - image.base)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(test, not(target_os = "uefi")))]
+mod test {
+    use super::*;
+    use std::{string::String, vec, vec::Vec};
+
+    fn symbol_map(symbols: &[(u32, u32, &str)]) -> Vec<u8> {
+        let mut entries = Vec::new();
+        let mut names = Vec::new();
+        for &(start, size, name) in symbols {
+            for value in [start, size, names.len() as u32, name.len() as u32] {
+                entries.extend_from_slice(&value.to_le_bytes());
+            }
+            names.extend_from_slice(name.as_bytes());
+        }
+        let mut bytes = SYMBOLS_MAGIC.to_vec();
+        bytes.extend_from_slice(&(symbols.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&entries);
+        bytes.extend_from_slice(&names);
+        bytes
+    }
+
+    #[test]
+    fn frames() {
+        // Slots of a stack, with frames at 2, 6 and 8 saving the RBP of their caller and the return address.
+        let mut stack = [0usize; 12];
+        let base = stack.as_ptr() as usize;
+        let slot = |i: usize| base + i * size_of::<usize>();
+        stack[2] = slot(6);
+        stack[3] = 0x1111;
+        stack[6] = slot(8);
+        stack[7] = 0x2222;
+        // The outermost frame saved an RBP below itself, which ends the walk.
+        stack[8] = slot(4);
+        stack[9] = 0x3333;
+
+        let frames: Vec<_> = unsafe { Frames::new(slot(2), base) }.collect();
+        assert_eq!(
+            frames,
+            [
+                Frame {
+                    frame_pointer: slot(2),
+                    return_address: 0x1111
+                },
+                Frame {
+                    frame_pointer: slot(6),
+                    return_address: 0x2222
+                },
+                Frame {
+                    frame_pointer: slot(8),
+                    return_address: 0x3333
+                },
+            ]
+        );
+
+        // Misaligned or null frame pointers are not followed.
+        stack[6] = slot(8) + 1;
+        assert_eq!(unsafe { Frames::new(slot(2), base) }.count(), 2);
+        assert_eq!(unsafe { Frames::new(0, 0) }.count(), 0);
+
+        // The first frame pointer must be on the stack, not below it or far above it.
+        assert_eq!(unsafe { Frames::new(slot(2), slot(3)) }.count(), 0);
+        assert_eq!(
+            unsafe { Frames::new(slot(2) + MAX_FRAME_SIZE, slot(2)) }.count(),
+            0
+        );
+
+        // A frame pointing to itself would loop forever.
+        stack[2] = slot(2);
+        assert_eq!(unsafe { Frames::new(slot(2), base) }.count(), 1);
+    }
+
+    #[test]
+    fn symbols() {
+        let bytes = symbol_map(&[
+            (0x1000, 0x20, "moos::efi_main"),
+            (0x1040, 0x10, "moos::hlt"),
+        ]);
+        let map = SymbolMap::from_bytes(&bytes).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.lookup(0x0FFF), None);
+        assert_eq!(
+            map.lookup(0x101F),
+            Some(Symbol {
+                name: "moos::efi_main",
+                start: 0x1000,
+                size: 0x20
+            })
+        );
+        assert_eq!(map.lookup(0x1020), None);
+        assert_eq!(map.lookup(0x1040).unwrap().name, "moos::hlt");
+        assert_eq!(map.lookup(0x1050), None);
+
+        assert!(SymbolMap::from_bytes(b"MSYM").is_err());
+        assert!(SymbolMap::from_bytes(&bytes[..bytes.len() - 1]).is_ok());
+        assert!(SymbolMap::from_bytes(&bytes[..20]).is_err());
+    }
+
+    #[test]
+    fn image() {
+        // PE headers with one `.symbols` section at 0x1000, followed by the section.
+        const PE: usize = 0x80;
+        const OPTIONAL_HEADER: usize = PE + 24;
+        const OPTIONAL_HEADER_SIZE: usize = 240;
+        const SECTION_HEADER: usize = OPTIONAL_HEADER + OPTIONAL_HEADER_SIZE;
+        let symbols = symbol_map(&[(0x200, 0x100, "moos::boot")]);
+        let mut memory = vec![0u8; 0x2000];
+        memory[..2].copy_from_slice(b"MZ");
+        memory[0x3C..0x40].copy_from_slice(&(PE as u32).to_le_bytes());
+        memory[PE..PE + 4].copy_from_slice(b"PE\0\0");
+        memory[PE + 6..PE + 8].copy_from_slice(&1u16.to_le_bytes());
+        memory[PE + 20..PE + 22].copy_from_slice(&(OPTIONAL_HEADER_SIZE as u16).to_le_bytes());
+        memory[OPTIONAL_HEADER..OPTIONAL_HEADER + 2].copy_from_slice(&0x20Bu16.to_le_bytes());
+        memory[OPTIONAL_HEADER + 56..OPTIONAL_HEADER + 60]
+            .copy_from_slice(&0x2000u32.to_le_bytes());
+        memory[SECTION_HEADER..SECTION_HEADER + 8].copy_from_slice(SYMBOLS_SECTION);
+        memory[SECTION_HEADER + 8..SECTION_HEADER + 12]
+            .copy_from_slice(&(symbols.len() as u32).to_le_bytes());
+        memory[SECTION_HEADER + 12..SECTION_HEADER + 16].copy_from_slice(&0x1000u32.to_le_bytes());
+        memory[0x1000..0x1000 + symbols.len()].copy_from_slice(&symbols);
+
+        let base = memory.as_ptr() as usize;
+        let image = unsafe { Image::from_base(base) }.unwrap();
+        assert!(image.has_symbols());
+        assert!(image.contains(base + 0x1FFF));
+        assert!(!image.contains(base + 0x2000));
+        assert_eq!(image.symbol(base + 0x210).unwrap().name, "moos::boot");
+
+        // The frame returning to the end of moos::boot is still inside it, and the firmware ends the walk.
+        let mut stack = [0usize; 6];
+        let stack_base = stack.as_ptr() as usize;
+        let slot = |i: usize| stack_base + i * size_of::<usize>();
+        stack.copy_from_slice(&[slot(2), base + 0x300, slot(4), base + 0x1234, 0, 0xF000]);
+        let mut output = String::new();
+        write(&mut output, &image, unsafe {
+            Frames::new(slot(0), stack_base)
+        })
+        .unwrap();
+        assert_eq!(
+            output,
+            std::format!(
+                "Backtrace:\n   0: {:#018x} moos::boot+0x100\n   1: {:#018x} moos.efi+0x1234\n",
+                base + 0x300,
+                base + 0x1234
+            )
+        );
+
+        memory[PE] = 0;
+        assert!(unsafe { Image::from_base(base) }.is_err());
+    }
+}
+
+#[cfg(all(test, target_os = "uefi"))]
+mod qemu_test {
+    use super::*;
+
+    #[test_case]
+    fn own_stack() {
+        let image = Image::current().unwrap();
+        let mut frames = Frames::current().take_while(|frame| image.contains(frame.return_address));
+        let caller = frames.next().unwrap();
+        // The runner and efi_main are further out.
+        assert!(frames.count() >= 2);
+        // Symbols are only there if the runner could embed them.
+        if let Some(symbol) = image.symbol(caller.return_address - 1) {
+            assert!(symbol.name.starts_with("moos::testing::"));
+        }
+    }
+}
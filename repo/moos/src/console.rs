@@ -0,0 +1,437 @@
+//! Text console drawn with a bitmap font, which keeps working after ExitBootServices.
+//!
+//! Understands the ANSI escape sequences for colors (SGR), cursor position (CUP) and erasing (ED, EL).
+//!
+//! REF: https://en.wikipedia.org/wiki/ANSI_escape_code
+
+use core::fmt::{self, Write};
+
+use crate::{
+    font::Font,
+    framebuffer::{Color, Framebuffer},
+    graphics::{PixelWriter, Rect},
+    sync::SpinLock,
+};
+
+/// The 16 ANSI colors, in the shades of the VGA text mode.
+pub const PALETTE: [Color; 16] = [
+    Color::from_rgb(0x000000),
+    Color::from_rgb(0xAA0000),
+    Color::from_rgb(0x00AA00),
+    Color::from_rgb(0xAA5500),
+    Color::from_rgb(0x0000AA),
+    Color::from_rgb(0xAA00AA),
+    Color::from_rgb(0x00AAAA),
+    Color::from_rgb(0xAAAAAA),
+    Color::from_rgb(0x555555),
+    Color::from_rgb(0xFF5555),
+    Color::from_rgb(0x55FF55),
+    Color::from_rgb(0xFFFF55),
+    Color::from_rgb(0x5555FF),
+    Color::from_rgb(0xFF55FF),
+    Color::from_rgb(0x55FFFF),
+    Color::from_rgb(0xFFFFFF),
+];
+
+const DEFAULT_FOREGROUND: Color = PALETTE[7];
+const DEFAULT_BACKGROUND: Color = PALETTE[0];
+const TAB_WIDTH: usize = 8;
+const MAX_PARAMETERS: usize = 4;
+
+/// Console of the kernel, set up by `init`.
+static CONSOLE: SpinLock<Option<Console<Framebuffer>>> = SpinLock::new(None);
+
+/// Progress through an escape sequence.
+#[derive(Debug, Clone, Copy)]
+enum Escape {
+    None,
+    /// After ESC.
+    Start,
+    /// After ESC [, collecting numeric parameters.
+    Csi {
+        parameters: [u16; MAX_PARAMETERS],
+        count: usize,
+    },
+}
+
+/// Grid of character cells on a `PixelWriter`, scrolling up when the cursor goes past the last row.
+pub struct Console<W> {
+    writer: W,
+    font: Font<'static>,
+    columns: usize,
+    rows: usize,
+    column: usize,
+    row: usize,
+    foreground: Color,
+    background: Color,
+    escape: Escape,
+}
+
+impl<W: PixelWriter> Console<W> {
+    /// Clears `writer` and puts the cursor at the top left.
+    pub fn new(writer: W, font: Font<'static>) -> Self {
+        let mut console = Self {
+            columns: writer.width() / font.width(),
+            rows: writer.height() / font.height(),
+            writer,
+            font,
+            column: 0,
+            row: 0,
+            foreground: DEFAULT_FOREGROUND,
+            background: DEFAULT_BACKGROUND,
+            escape: Escape::None,
+        };
+        console.clear();
+        console
+    }
+
+    #[allow(dead_code)]
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    #[allow(dead_code)]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Column and row where the next character goes.
+    /// The column equals `columns()` when a full line waits for the next character to wrap.
+    #[allow(dead_code)]
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.column, self.row)
+    }
+
+    pub fn set_cursor(&mut self, column: usize, row: usize) {
+        self.column = column.min(self.columns.saturating_sub(1));
+        self.row = row.min(self.rows.saturating_sub(1));
+    }
+
+    pub fn set_colors(&mut self, foreground: Color, background: Color) {
+        self.foreground = foreground;
+        self.background = background;
+    }
+
+    pub fn clear(&mut self) {
+        let rect = Rect::new(0, 0, self.writer.width(), self.writer.height());
+        self.writer.fill_rect(rect, self.background);
+        self.column = 0;
+        self.row = 0;
+    }
+
+    pub fn write_char(&mut self, c: char) {
+        match &mut self.escape {
+            Escape::None => match c {
+                '\x1B' => self.escape = Escape::Start,
+                '\n' => self.new_line(),
+                '\r' => self.column = 0,
+                '\t' => {
+                    let stop = (self.column / TAB_WIDTH + 1) * TAB_WIDTH;
+                    self.column = stop.min(self.columns);
+                }
+                '\x08' => self.column = self.column.min(self.columns).saturating_sub(1),
+                _ => self.put(c),
+            },
+            Escape::Start => {
+                self.escape = match c {
+                    '[' => Escape::Csi {
+                        parameters: [0; MAX_PARAMETERS],
+                        count: 0,
+                    },
+                    _ => Escape::None,
+                }
+            }
+            Escape::Csi { parameters, count } => match c {
+                '0'..='9' => {
+                    *count = (*count).max(1);
+                    let parameter = &mut parameters[*count - 1];
+                    let digit = c as u16 - '0' as u16;
+                    *parameter = parameter.saturating_mul(10).saturating_add(digit);
+                }
+                ';' => *count = ((*count).max(1) + 1).min(MAX_PARAMETERS),
+                // Private markers and intermediate bytes, which no supported sequence uses.
+                '\x20'..='\x2F' | '\x3A'..='\x3F' => {}
+                '\x40'..='\x7E' => {
+                    let (parameters, count) = (*parameters, *count);
+                    self.escape = Escape::None;
+                    self.execute(c, &parameters[..count]);
+                }
+                _ => self.escape = Escape::None,
+            },
+        }
+    }
+
+    /// Runs the control sequence ending with `command`.
+    fn execute(&mut self, command: char, parameters: &[u16]) {
+        let parameter = |index: usize| parameters.get(index).copied().unwrap_or(0);
+        match command {
+            'm' => {
+                if parameters.is_empty() {
+                    self.select_graphic_rendition(0);
+                }
+                for &parameter in parameters {
+                    self.select_graphic_rendition(parameter);
+                }
+            }
+            // Positions are 1-based, 0 standing for 1.
+            'H' | 'f' => self.set_cursor(
+                (parameter(1) as usize).saturating_sub(1),
+                (parameter(0) as usize).saturating_sub(1),
+            ),
+            'J' if parameter(0) == 2 => {
+                let (column, row) = (self.column, self.row);
+                self.clear();
+                self.set_cursor(column, row);
+            }
+            'J' if parameter(0) == 0 => {
+                self.erase_line_from(self.column);
+                let top = (self.row + 1) * self.font.height();
+                let rect = Rect::new(0, top as isize, self.writer.width(), self.writer.height());
+                self.writer.fill_rect(rect, self.background);
+            }
+            'K' if parameter(0) == 0 => self.erase_line_from(self.column),
+            _ => {}
+        }
+    }
+
+    fn select_graphic_rendition(&mut self, parameter: u16) {
+        let parameter = parameter as usize;
+        match parameter {
+            0 => self.set_colors(DEFAULT_FOREGROUND, DEFAULT_BACKGROUND),
+            30..=37 => self.foreground = PALETTE[parameter - 30],
+            39 => self.foreground = DEFAULT_FOREGROUND,
+            40..=47 => self.background = PALETTE[parameter - 40],
+            49 => self.background = DEFAULT_BACKGROUND,
+            90..=97 => self.foreground = PALETTE[parameter - 90 + 8],
+            100..=107 => self.background = PALETTE[parameter - 100 + 8],
+            _ => {}
+        }
+    }
+
+    fn put(&mut self, c: char) {
+        if self.columns == 0 || self.rows == 0 {
+            return;
+        }
+        if self.column == self.columns {
+            self.new_line();
+        }
+
+        let glyph = self.font.glyph(c).or_else(|| self.font.glyph('?'));
+        let left = self.column * self.font.width();
+        let top = self.row * self.font.height();
+        for y in 0..self.font.height() {
+            for x in 0..self.font.width() {
+                let color = match glyph {
+                    Some(glyph) if glyph.pixel(x, y) => self.foreground,
+                    _ => self.background,
+                };
+                self.writer.write(left + x, top + y, color);
+            }
+        }
+        self.column += 1;
+    }
+
+    fn new_line(&mut self) {
+        self.column = 0;
+        if self.row + 1 < self.rows {
+            self.row += 1;
+        } else {
+            self.writer.scroll_up(self.font.height(), self.background);
+        }
+    }
+
+    fn erase_line_from(&mut self, column: usize) {
+        let left = column * self.font.width();
+        let top = self.row * self.font.height();
+        let rect = Rect::new(
+            left as isize,
+            top as isize,
+            self.writer.width().saturating_sub(left),
+            self.font.height(),
+        );
+        self.writer.fill_rect(rect, self.background);
+    }
+}
+
+impl<W: PixelWriter> Write for Console<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            self.write_char(c);
+        }
+        Ok(())
+    }
+}
+
+/// Makes `framebuffer` the console of `kprint!` and `kprintln!`.
+pub fn init(framebuffer: Framebuffer) {
+    *CONSOLE.lock() = Some(Console::new(framebuffer, Font::builtin()));
+}
+
+/// Releases the console of the kernel, even if someone is writing to it.
+///
+/// # Safety
+/// Whoever holds it must never run again, as after a panic.
+pub unsafe fn force_unlock() {
+    CONSOLE.force_unlock();
+}
+
+/// Runs `f` on the console of the kernel, if there is one, holding it so that the output stays together.
+pub fn write_all(f: impl FnOnce(&mut dyn Write) -> fmt::Result) -> fmt::Result {
+    CONSOLE.lock().as_mut().map_or(Ok(()), |console| f(console))
+}
+
+/// Like [`write_all`], but fails instead of waiting while the console is held.
+/// Interrupt handlers must use this, since the code they interrupted may hold it.
+pub fn try_write_all(f: impl FnOnce(&mut dyn Write) -> fmt::Result) -> fmt::Result {
+    CONSOLE
+        .try_lock()
+        .ok_or(fmt::Error)?
+        .as_mut()
+        .map_or(Ok(()), |console| f(console))
+}
+
+/// Writes to the console of the kernel, if there is one.
+pub struct KernelConsole;
+
+impl Write for KernelConsole {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match CONSOLE.lock().as_mut() {
+            Some(console) => console.write_str(s),
+            None => Ok(()),
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn _print(args: fmt::Arguments) {
+    let _ = KernelConsole.write_fmt(args);
+}
+
+/// Prints to the framebuffer console. Output before `console::init` is dropped.
+#[macro_export]
+macro_rules! kprint {
+    ($($arg:tt)*) => {
+        $crate::console::_print(format_args!($($arg)*))
+    };
+}
+
+/// Prints to the framebuffer console, with a newline.
+#[macro_export]
+macro_rules! kprintln {
+    () => {
+        $crate::kprint!("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::kprint!("{}\n", format_args!($($arg)*))
+    };
+}
+
+#[cfg(all(test, not(target_os = "uefi")))]
+mod test {
+    use super::*;
+    use crate::graphics::Canvas;
+    use std::vec;
+
+    const COLUMNS: usize = 3;
+    const ROWS: usize = 2;
+    const WIDTH: usize = COLUMNS * 8;
+    const HEIGHT: usize = ROWS * 16;
+
+    /// Whether the cell shows `c` in `foreground` on `background`.
+    fn shows(
+        console: &Console<Canvas>,
+        (column, row): (usize, usize),
+        c: char,
+        foreground: Color,
+        background: Color,
+    ) -> bool {
+        let glyph = Font::builtin().glyph(c).unwrap();
+        (0..16).all(|y| {
+            (0..8).all(|x| {
+                let expected = if glyph.pixel(x, y) {
+                    foreground
+                } else {
+                    background
+                };
+                console.writer.read(column * 8 + x, row * 16 + y) == Some(expected)
+            })
+        })
+    }
+
+    fn shows_text(console: &Console<Canvas>, lines: [&str; ROWS]) -> bool {
+        lines.iter().enumerate().all(|(row, line)| {
+            line.chars().enumerate().all(|(column, c)| {
+                shows(
+                    console,
+                    (column, row),
+                    c,
+                    DEFAULT_FOREGROUND,
+                    DEFAULT_BACKGROUND,
+                )
+            })
+        })
+    }
+
+    #[test]
+    fn wrap_and_scroll() {
+        let mut pixels = vec![0xFFFFFF; WIDTH * HEIGHT];
+        let mut console = Console::new(Canvas::new(&mut pixels, WIDTH, HEIGHT), Font::builtin());
+        assert_eq!((console.columns(), console.rows()), (COLUMNS, ROWS));
+
+        write!(console, "abc").unwrap();
+        assert_eq!(console.cursor(), (3, 0));
+        write!(console, "d").unwrap();
+        assert_eq!(console.cursor(), (1, 1));
+        assert!(shows_text(&console, ["abc", "d  "]));
+
+        write!(console, "\nxy\tz").unwrap();
+        assert!(shows_text(&console, ["xy ", "z  "]));
+        assert_eq!(console.cursor(), (1, 1));
+
+        write!(console, "\x08\x1B[K!\r?").unwrap();
+        assert!(shows_text(&console, ["xy ", "?  "]));
+    }
+
+    #[test]
+    fn escape_sequences() {
+        let mut pixels = vec![0; WIDTH * HEIGHT];
+        let mut console = Console::new(Canvas::new(&mut pixels, WIDTH, HEIGHT), Font::builtin());
+
+        write!(console, "\x1B[31;104mA\x1B[39mB\x1B[0mC").unwrap();
+        assert!(shows(&console, (0, 0), 'A', PALETTE[1], PALETTE[12]));
+        assert!(shows(
+            &console,
+            (1, 0),
+            'B',
+            DEFAULT_FOREGROUND,
+            PALETTE[12]
+        ));
+        assert!(shows(
+            &console,
+            (2, 0),
+            'C',
+            DEFAULT_FOREGROUND,
+            DEFAULT_BACKGROUND
+        ));
+
+        write!(console, "\x1B[2;2HD\x1B[HE").unwrap();
+        assert!(shows(
+            &console,
+            (0, 0),
+            'E',
+            DEFAULT_FOREGROUND,
+            DEFAULT_BACKGROUND
+        ));
+        assert!(shows(
+            &console,
+            (1, 1),
+            'D',
+            DEFAULT_FOREGROUND,
+            DEFAULT_BACKGROUND
+        ));
+
+        // Unsupported sequences are swallowed rather than printed.
+        write!(console, "\x1B[2J\x1B[?25l\x1B[1;99mF").unwrap();
+        assert!(shows_text(&console, [" F ", "   "]));
+    }
+}
@@ -0,0 +1,143 @@
+//! Control registers and instructions of the x86-64 processor.
+
+use core::arch::asm;
+
+/// Address of the last page fault.
+pub fn cr2() -> u64 {
+    let value;
+    unsafe { asm!("mov {}, cr2", out(reg) value, options(nomem, nostack, preserves_flags)) };
+    value
+}
+
+/// Physical address of the top-level page table, with flags in the low bits.
+pub fn cr3() -> u64 {
+    let value;
+    unsafe { asm!("mov {}, cr3", out(reg) value, options(nomem, nostack, preserves_flags)) };
+    value
+}
+
+pub fn disable_interrupts() {
+    unsafe { asm!("cli", options(nomem, nostack)) };
+}
+
+pub fn enable_interrupts() {
+    unsafe { asm!("sti", options(nomem, nostack)) };
+}
+
+/// Whether IF is set in RFLAGS.
+#[allow(dead_code)]
+pub fn interrupts_enabled() -> bool {
+    let rflags: u64;
+    unsafe { asm!("pushfq", "pop {}", out(reg) rflags, options(nomem, preserves_flags)) };
+    rflags & (1 << 9) != 0
+}
+
+/// Runs `f` with interrupts disabled, enabling them again afterwards if they were.
+#[allow(dead_code)]
+pub fn without_interrupts<R>(f: impl FnOnce() -> R) -> R {
+    let enabled = interrupts_enabled();
+    disable_interrupts();
+    let result = f();
+    if enabled {
+        enable_interrupts();
+    }
+    result
+}
+
+/// Feature flags of CPUID leaf 1, EDX in the high half and ECX in the low half.
+/// OSXSAVE is left out, since it follows CR4 of the processor rather than what the processor supports.
+pub fn features() -> u64 {
+    const CPUID_ECX_OSXSAVE: u32 = 1 << 27;
+    let features = unsafe { core::arch::x86_64::__cpuid(1) };
+    u64::from(features.edx) << 32 | u64::from(features.ecx & !CPUID_ECX_OSXSAVE)
+}
+
+/// # Safety
+/// `msr` must exist on the processor.
+pub unsafe fn read_msr(msr: u32) -> u64 {
+    let (low, high): (u32, u32);
+    asm!("rdmsr", in("ecx") msr, out("eax") low, out("edx") high, options(nomem, nostack, preserves_flags));
+    u64::from(high) << 32 | u64::from(low)
+}
+
+/// # Safety
+/// `msr` must exist on the processor, and `value` must not break what relies on it.
+pub unsafe fn write_msr(msr: u32, value: u64) {
+    let (low, high) = (value as u32, (value >> 32) as u32);
+    asm!("wrmsr", in("ecx") msr, in("eax") low, in("edx") high, options(nostack, preserves_flags));
+}
+
+/// Operand of LGDT and LIDT.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct DescriptorTablePointer {
+    /// Size of the table in bytes, minus one.
+    pub limit: u16,
+    pub base: u64,
+}
+
+/// # Safety
+/// `pointer` must describe a valid GDT that stays in memory, and the segment registers must be
+/// reloaded with selectors of that table.
+pub unsafe fn load_gdt(pointer: &DescriptorTablePointer) {
+    asm!("lgdt [{}]", in(reg) pointer, options(readonly, nostack, preserves_flags));
+}
+
+/// # Safety
+/// `pointer` must describe a valid IDT that stays in memory.
+pub unsafe fn load_idt(pointer: &DescriptorTablePointer) {
+    asm!("lidt [{}]", in(reg) pointer, options(readonly, nostack, preserves_flags));
+}
+
+/// Loads CS with a far return, since there is no `mov cs`.
+///
+/// # Safety
+/// `selector` must refer to a 64-bit code segment of the current GDT.
+pub unsafe fn set_code_segment(selector: u16) {
+    asm!(
+        "push {selector}",
+        "lea {tmp}, [rip + 2f]",
+        "push {tmp}",
+        "retfq",
+        "2:",
+        selector = in(reg) u64::from(selector),
+        tmp = lateout(reg) _,
+        options(preserves_flags),
+    );
+}
+
+/// Loads SS, DS, ES, FS and GS.
+///
+/// # Safety
+/// `selector` must refer to a writable data segment of the current GDT, at the current privilege level.
+pub unsafe fn set_data_segments(selector: u16) {
+    asm!(
+        "mov ss, {0:x}",
+        "mov ds, {0:x}",
+        "mov es, {0:x}",
+        "mov fs, {0:x}",
+        "mov gs, {0:x}",
+        in(reg) selector,
+        options(nostack, preserves_flags),
+    );
+}
+
+/// # Safety
+/// `selector` must refer to an available TSS of the current GDT that stays in memory.
+pub unsafe fn load_task_register(selector: u16) {
+    asm!("ltr {0:x}", in(reg) selector, options(nostack, preserves_flags));
+}
+
+#[allow(dead_code)]
+pub fn code_segment() -> u16 {
+    let selector;
+    unsafe { asm!("mov {0:x}, cs", out(reg) selector, options(nomem, nostack, preserves_flags)) };
+    selector
+}
+
+#[allow(dead_code)]
+pub fn task_register() -> u16 {
+    let selector;
+    unsafe { asm!("str {0:x}", out(reg) selector, options(nomem, nostack, preserves_flags)) };
+    selector
+}
@@ -0,0 +1,333 @@
+//! Handlers of the 32 CPU exceptions.
+//!
+//! Traps such as breakpoints are logged and resumed. Faults and aborts stop the kernel with a
+//! report of the interrupted context, unless a handler set with `set_handler` resolves them.
+//!
+//! REF: Intel SDM Vol. 3A, 6.15 Exception and Interrupt Reference
+
+use core::fmt;
+
+use crate::{
+    backtrace::{Frames, Image},
+    cpu,
+    interrupts::{Context, EXCEPTION_COUNT},
+    panic,
+    sync::SpinLock,
+};
+
+#[allow(dead_code)]
+pub const DEBUG: usize = 1;
+pub const NMI: usize = 2;
+#[allow(dead_code)]
+pub const BREAKPOINT: usize = 3;
+pub const DOUBLE_FAULT: usize = 8;
+#[allow(dead_code)]
+pub const GENERAL_PROTECTION: usize = 13;
+pub const PAGE_FAULT: usize = 14;
+pub const MACHINE_CHECK: usize = 18;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Reported before the faulting instruction, which runs again on return.
+    Fault,
+    /// Reported after the instruction, so returning continues with the next one.
+    Trap,
+    /// Leaves no reliable state to return to.
+    Abort,
+    /// Signaled by hardware rather than caused by an instruction.
+    Interrupt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Exception {
+    pub mnemonic: &'static str,
+    pub name: &'static str,
+    pub kind: Kind,
+    pub has_error_code: bool,
+}
+
+const fn exception(
+    mnemonic: &'static str,
+    name: &'static str,
+    kind: Kind,
+    has_error_code: bool,
+) -> Exception {
+    Exception {
+        mnemonic,
+        name,
+        kind,
+        has_error_code,
+    }
+}
+
+/// Vectors that the processor does not use yet, which are not expected to arrive.
+const RESERVED: Exception = exception("#RES", "Reserved", Kind::Abort, false);
+
+/// By vector. `has_error_code` must match the stubs in interrupts.rs.
+pub const EXCEPTIONS: [Exception; EXCEPTION_COUNT] = [
+    exception("#DE", "Divide Error", Kind::Fault, false),
+    exception("#DB", "Debug", Kind::Trap, false),
+    exception("NMI", "Non-Maskable Interrupt", Kind::Interrupt, false),
+    exception("#BP", "Breakpoint", Kind::Trap, false),
+    exception("#OF", "Overflow", Kind::Trap, false),
+    exception("#BR", "BOUND Range Exceeded", Kind::Fault, false),
+    exception("#UD", "Invalid Opcode", Kind::Fault, false),
+    exception("#NM", "Device Not Available", Kind::Fault, false),
+    exception("#DF", "Double Fault", Kind::Abort, true),
+    exception("CSO", "Coprocessor Segment Overrun", Kind::Fault, false),
+    exception("#TS", "Invalid TSS", Kind::Fault, true),
+    exception("#NP", "Segment Not Present", Kind::Fault, true),
+    exception("#SS", "Stack-Segment Fault", Kind::Fault, true),
+    exception("#GP", "General Protection", Kind::Fault, true),
+    exception("#PF", "Page Fault", Kind::Fault, true),
+    RESERVED,
+    exception("#MF", "x87 Floating-Point Error", Kind::Fault, false),
+    exception("#AC", "Alignment Check", Kind::Fault, true),
+    exception("#MC", "Machine Check", Kind::Abort, false),
+    exception("#XM", "SIMD Floating-Point", Kind::Fault, false),
+    exception("#VE", "Virtualization", Kind::Fault, false),
+    exception("#CP", "Control Protection", Kind::Fault, true),
+    RESERVED,
+    RESERVED,
+    RESERVED,
+    RESERVED,
+    RESERVED,
+    RESERVED,
+    exception("#HV", "Hypervisor Injection", Kind::Fault, false),
+    exception("#VC", "VMM Communication", Kind::Fault, true),
+    exception("#SX", "Security", Kind::Fault, true),
+    RESERVED,
+];
+
+/// Error code of a page fault, describing the access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageFaultError(pub u64);
+
+impl PageFaultError {
+    const PROTECTION_VIOLATION: u64 = 1 << 0;
+    const WRITE: u64 = 1 << 1;
+    const USER: u64 = 1 << 2;
+    const RESERVED_BIT: u64 = 1 << 3;
+    const INSTRUCTION_FETCH: u64 = 1 << 4;
+    const PROTECTION_KEY: u64 = 1 << 5;
+    const SHADOW_STACK: u64 = 1 << 6;
+
+    /// Whether the page was present, and the access not allowed. Otherwise, it was not present.
+    pub fn is_protection_violation(&self) -> bool {
+        self.0 & Self::PROTECTION_VIOLATION != 0
+    }
+
+    pub fn is_write(&self) -> bool {
+        self.0 & Self::WRITE != 0
+    }
+
+    pub fn is_user(&self) -> bool {
+        self.0 & Self::USER != 0
+    }
+
+    pub fn is_instruction_fetch(&self) -> bool {
+        self.0 & Self::INSTRUCTION_FETCH != 0
+    }
+}
+
+impl fmt::Display for PageFaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let access = if self.is_instruction_fetch() {
+            "Instruction fetch from"
+        } else if self.is_write() {
+            "Write to"
+        } else {
+            "Read from"
+        };
+        let page = match self.is_protection_violation() {
+            true => "a protected",
+            false => "a non-present",
+        };
+        let mode = match self.is_user() {
+            true => "user",
+            false => "kernel",
+        };
+        write!(f, "{} {} page in {} mode", access, page, mode)?;
+        for (bit, meaning) in [
+            (Self::RESERVED_BIT, "reserved bit set"),
+            (Self::PROTECTION_KEY, "protection key"),
+            (Self::SHADOW_STACK, "shadow stack"),
+        ] {
+            if self.0 & bit != 0 {
+                write!(f, ", {}", meaning)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Gets the first chance at an exception. Returns whether it resolved it, e.g. by mapping the
+/// missing page, in which case the interrupted code resumes with the context as left by the handler.
+pub type Handler = fn(&mut Context) -> bool;
+
+static HANDLERS: SpinLock<[Option<Handler>; EXCEPTION_COUNT]> =
+    SpinLock::new([None; EXCEPTION_COUNT]);
+
+#[allow(dead_code)]
+pub fn set_handler(vector: usize, handler: Option<Handler>) {
+    HANDLERS.lock()[vector] = handler;
+}
+
+/// What the kernel shows when it stops on an exception.
+struct Report<'a> {
+    exception: &'a Exception,
+    context: &'a Context,
+    cr2: u64,
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Exception { mnemonic, name, .. } = self.exception;
+        write!(f, "CPU EXCEPTION: {} ({})", name, mnemonic)?;
+        if self.exception.has_error_code {
+            write!(f, ", error code {:#x}", self.context.error_code)?;
+        }
+        if self.context.vector as usize == PAGE_FAULT {
+            let error = PageFaultError(self.context.error_code);
+            write!(f, "\n{} at {:#x}", error, self.cr2)?;
+        }
+
+        let rip = self.context.frame.rip as usize;
+        let symbol = Image::current().ok().and_then(|image| {
+            let symbol = image.symbol(rip)?;
+            Some((symbol.name, rip - image.base() - symbol.start))
+        });
+        if let Some((name, offset)) = symbol {
+            write!(f, "\nIn {}+{:#x}", name, offset)?;
+        }
+        write!(f, "\n\n{}", self.context)
+    }
+}
+
+/// Called by `interrupts::dispatch` for vectors below 32.
+pub fn handle(context: &mut Context) {
+    // Before anything else, since a page fault in the handler would overwrite it.
+    let cr2 = cpu::cr2();
+    let vector = context.vector as usize;
+    let exception = &EXCEPTIONS[vector];
+
+    // Only the interrupted code can hold the lock, so waiting for it would never end.
+    let handler = HANDLERS.try_lock().and_then(|handlers| handlers[vector]);
+    if handler.is_some_and(|handler| handler(context)) {
+        return;
+    }
+    if exception.kind == Kind::Trap {
+        crate::try_log!(
+            crate::log::Level::Warn,
+            "{} at {:#x}",
+            exception.name,
+            context.frame.rip
+        );
+        return;
+    }
+
+    let report = Report {
+        exception,
+        context,
+        cr2,
+    };
+    // The backtrace starts from the caller of the interrupted function, which RIP already shows.
+    // Code without frame pointers, like the firmware, may leave anything in RBP, so the walk only
+    // starts if RBP is on the interrupted stack.
+    let frames = unsafe { Frames::new(context.registers.rbp as usize, context.frame.rsp as usize) };
+    panic::stop(&report, frames)
+}
+
+#[cfg(all(test, not(target_os = "uefi")))]
+mod test {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn exceptions() {
+        let with_error_code: std::vec::Vec<_> = (0..EXCEPTION_COUNT)
+            .filter(|&vector| EXCEPTIONS[vector].has_error_code)
+            .collect();
+        assert_eq!(with_error_code, [8, 10, 11, 12, 13, 14, 17, 21, 29, 30]);
+        assert_eq!(EXCEPTIONS[BREAKPOINT].kind, Kind::Trap);
+        assert_eq!(EXCEPTIONS[PAGE_FAULT].mnemonic, "#PF");
+        assert_eq!(EXCEPTIONS[DOUBLE_FAULT].kind, Kind::Abort);
+    }
+
+    #[test]
+    fn page_fault_errors() {
+        assert_eq!(
+            format!("{}", PageFaultError(0)),
+            "Read from a non-present page in kernel mode"
+        );
+        assert_eq!(
+            format!("{}", PageFaultError(0b111)),
+            "Write to a protected page in user mode"
+        );
+        assert_eq!(
+            format!("{}", PageFaultError(0b11001)),
+            "Instruction fetch from a protected page in kernel mode, reserved bit set"
+        );
+    }
+}
+
+#[cfg(all(test, target_os = "uefi"))]
+mod qemu_test {
+    use core::{
+        arch::asm,
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    use super::*;
+
+    /// Canonical, but above everything the firmware maps.
+    const UNMAPPED: u64 = 0xFFFF_8000_0000_0000;
+
+    static RIP: AtomicU64 = AtomicU64::new(0);
+    static ERROR_CODE: AtomicU64 = AtomicU64::new(u64::MAX);
+
+    #[test_case]
+    fn breakpoint() {
+        fn record(context: &mut Context) -> bool {
+            RIP.store(context.frame.rip, Ordering::Relaxed);
+            true
+        }
+
+        set_handler(BREAKPOINT, Some(record));
+        let after: u64;
+        unsafe { asm!("lea {}, [rip + 2f]", "int3", "2:", out(reg) after) };
+        set_handler(BREAKPOINT, None);
+        // A trap resumes after the instruction.
+        assert_eq!(RIP.load(Ordering::Relaxed), after);
+    }
+
+    #[test_case]
+    fn page_fault() {
+        /// Resumes where the faulting code left RSI, instead of retrying the access.
+        fn skip(context: &mut Context) -> bool {
+            if cpu::cr2() != UNMAPPED {
+                return false;
+            }
+            ERROR_CODE.store(context.error_code, Ordering::Relaxed);
+            context.frame.rip = context.registers.rsi;
+            true
+        }
+
+        set_handler(PAGE_FAULT, Some(skip));
+        unsafe {
+            asm!(
+                "lea rsi, [rip + 2f]",
+                "mov {value}, qword ptr [{address}]",
+                "2:",
+                address = in(reg) UNMAPPED,
+                value = out(reg) _,
+                out("rsi") _,
+            )
+        };
+        set_handler(PAGE_FAULT, None);
+
+        let error = PageFaultError(ERROR_CODE.load(Ordering::Relaxed));
+        assert!(!error.is_protection_violation());
+        assert!(!error.is_write() && !error.is_user() && !error.is_instruction_fetch());
+    }
+}
@@ -0,0 +1,277 @@
+//! Bitmap fonts: the built-in 8x16 ASCII font and PC Screen Font version 2 files.
+//!
+//! REF: https://www.win.tue.nl/~aeb/linux/kbd/font-formats-1.html
+
+use core::mem::size_of;
+
+const PSF2_MAGIC: u32 = 0x864A_B572;
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+const PSF2_SEPARATOR: u8 = 0xFF;
+const PSF2_START_SEQUENCE: u8 = 0xFE;
+
+/// Monospaced font with one bit per pixel, rows padded to whole bytes.
+#[derive(Debug, Clone, Copy)]
+pub struct Font<'a> {
+    glyphs: &'a [u8],
+    count: usize,
+    width: usize,
+    height: usize,
+    // Characters of each glyph. Without it, glyph `n` is the character `first + n`.
+    unicode_table: Option<&'a [u8]>,
+    first: u32,
+}
+
+impl Font<'static> {
+    /// Printable ASCII, 8x16.
+    pub fn builtin() -> Self {
+        Self {
+            glyphs: &BUILTIN_GLYPHS,
+            count: BUILTIN_GLYPHS.len() / 16,
+            width: 8,
+            height: 16,
+            unicode_table: None,
+            first: u32::from(b' '),
+        }
+    }
+}
+
+impl<'a> Font<'a> {
+    /// Parses a PSF2 file, such as the console fonts of Linux distributions.
+    #[allow(dead_code)]
+    pub fn from_psf2(bytes: &'a [u8]) -> Result<Self, &'static str> {
+        let field = |index: usize| -> Result<u32, &'static str> {
+            let offset = index * size_of::<u32>();
+            bytes
+                .get(offset..offset + size_of::<u32>())
+                .map(|field| u32::from_le_bytes(field.try_into().unwrap()))
+                .ok_or("PSF2 header is truncated")
+        };
+        if field(0)? != PSF2_MAGIC {
+            return Err("Not a PSF2 font");
+        }
+        let header_size = field(2)? as usize;
+        let flags = field(3)?;
+        let count = field(4)? as usize;
+        let glyph_size = field(5)? as usize;
+        let height = field(6)? as usize;
+        let width = field(7)? as usize;
+
+        if width == 0 || height == 0 || glyph_size != height * width.div_ceil(8) {
+            return Err("PSF2 glyph size does not match its dimensions");
+        }
+        let glyphs_end = count
+            .checked_mul(glyph_size)
+            .and_then(|size| size.checked_add(header_size))
+            .filter(|&end| end <= bytes.len())
+            .ok_or("PSF2 glyphs are truncated")?;
+
+        Ok(Self {
+            glyphs: &bytes[header_size..glyphs_end],
+            count,
+            width,
+            height,
+            unicode_table: (flags & PSF2_HAS_UNICODE_TABLE != 0).then(|| &bytes[glyphs_end..]),
+            first: 0,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns `None` if the font cannot draw `c`.
+    pub fn glyph(&self, c: char) -> Option<Glyph<'a>> {
+        let index = match self.unicode_table {
+            Some(table) => table
+                .split(|&byte| byte == PSF2_SEPARATOR)
+                .take(self.count)
+                .position(|entry| {
+                    // Only single characters are drawn, so the combining sequences after the first 0xFE are skipped.
+                    let singles = entry.split(|&byte| byte == PSF2_START_SEQUENCE).next();
+                    singles
+                        .and_then(|singles| core::str::from_utf8(singles).ok())
+                        .is_some_and(|singles| singles.contains(c))
+                })?,
+            None => (c as u32).checked_sub(self.first)? as usize,
+        };
+        if index >= self.count {
+            return None;
+        }
+
+        let size = self.height * self.width.div_ceil(8);
+        Some(Glyph {
+            bitmap: &self.glyphs[index * size..(index + 1) * size],
+            width: self.width,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph<'a> {
+    bitmap: &'a [u8],
+    width: usize,
+}
+
+impl Glyph<'_> {
+    /// Whether the pixel at (`x`, `y`) is part of the character rather than the background.
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        let row = &self.bitmap[y * self.width.div_ceil(8)..];
+        row[x / 8] & (0x80 >> (x % 8)) != 0
+    }
+}
+
+/// 16 rows per character from 0x20 to 0x7E, most significant bit on the left.
+#[rustfmt::skip]
+static BUILTIN_GLYPHS: [u8; 95 * 16] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // ' '
+    0x00, 0x00, 0x00, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, // '!'
+    0x00, 0x00, 0x00, 0x24, 0x24, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '"'
+    0x00, 0x00, 0x00, 0x00, 0x24, 0x24, 0x7E, 0x24, 0x24, 0x7E, 0x24, 0x24, 0x00, 0x00, 0x00, 0x00, // '#'
+    0x00, 0x00, 0x00, 0x10, 0x3C, 0x50, 0x50, 0x38, 0x14, 0x14, 0x78, 0x10, 0x00, 0x00, 0x00, 0x00, // '$'
+    0x00, 0x00, 0x00, 0x60, 0x62, 0x04, 0x08, 0x10, 0x20, 0x46, 0x06, 0x00, 0x00, 0x00, 0x00, 0x00, // '%'
+    0x00, 0x00, 0x00, 0x00, 0x30, 0x48, 0x48, 0x30, 0x52, 0x4C, 0x44, 0x3A, 0x00, 0x00, 0x00, 0x00, // '&'
+    0x00, 0x00, 0x00, 0x10, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '\''
+    0x00, 0x00, 0x00, 0x08, 0x10, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x10, 0x08, 0x00, 0x00, 0x00, // '('
+    0x00, 0x00, 0x00, 0x20, 0x10, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x10, 0x20, 0x00, 0x00, 0x00, // ')'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x54, 0x38, 0x54, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, // '*'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x10, 0x7C, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, // '+'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x20, 0x00, 0x00, // ','
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '-'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, // '.'
+    0x00, 0x00, 0x00, 0x02, 0x04, 0x04, 0x08, 0x08, 0x10, 0x10, 0x20, 0x20, 0x40, 0x00, 0x00, 0x00, // '/'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x46, 0x4A, 0x4A, 0x52, 0x52, 0x62, 0x42, 0x3C, 0x00, 0x00, 0x00, // '0'
+    0x00, 0x00, 0x00, 0x10, 0x30, 0x50, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x7C, 0x00, 0x00, 0x00, // '1'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x02, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x7E, 0x00, 0x00, 0x00, // '2'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x02, 0x02, 0x1C, 0x02, 0x02, 0x02, 0x42, 0x3C, 0x00, 0x00, 0x00, // '3'
+    0x00, 0x00, 0x00, 0x04, 0x0C, 0x14, 0x24, 0x44, 0x7E, 0x04, 0x04, 0x04, 0x04, 0x00, 0x00, 0x00, // '4'
+    0x00, 0x00, 0x00, 0x7E, 0x40, 0x40, 0x7C, 0x02, 0x02, 0x02, 0x02, 0x42, 0x3C, 0x00, 0x00, 0x00, // '5'
+    0x00, 0x00, 0x00, 0x1C, 0x20, 0x40, 0x40, 0x7C, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, // '6'
+    0x00, 0x00, 0x00, 0x7E, 0x02, 0x02, 0x04, 0x08, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00, 0x00, // '7'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x3C, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, // '8'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x42, 0x3E, 0x02, 0x02, 0x04, 0x38, 0x00, 0x00, 0x00, // '9'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, // ':'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x20, 0x00, 0x00, // ';'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x08, 0x10, 0x20, 0x10, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, // '<'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7C, 0x00, 0x7C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '='
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x20, 0x10, 0x08, 0x04, 0x08, 0x10, 0x20, 0x00, 0x00, 0x00, 0x00, // '>'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x02, 0x04, 0x08, 0x10, 0x10, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, // '?'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x5E, 0x52, 0x52, 0x5E, 0x40, 0x40, 0x3C, 0x00, 0x00, 0x00, // '@'
+    0x00, 0x00, 0x00, 0x18, 0x24, 0x42, 0x42, 0x42, 0x7E, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, // 'A'
+    0x00, 0x00, 0x00, 0x7C, 0x42, 0x42, 0x42, 0x7C, 0x42, 0x42, 0x42, 0x42, 0x7C, 0x00, 0x00, 0x00, // 'B'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x42, 0x3C, 0x00, 0x00, 0x00, // 'C'
+    0x00, 0x00, 0x00, 0x78, 0x44, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x44, 0x78, 0x00, 0x00, 0x00, // 'D'
+    0x00, 0x00, 0x00, 0x7E, 0x40, 0x40, 0x40, 0x7C, 0x40, 0x40, 0x40, 0x40, 0x7E, 0x00, 0x00, 0x00, // 'E'
+    0x00, 0x00, 0x00, 0x7E, 0x40, 0x40, 0x40, 0x7C, 0x40, 0x40, 0x40, 0x40, 0x40, 0x00, 0x00, 0x00, // 'F'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x40, 0x40, 0x40, 0x4E, 0x42, 0x42, 0x46, 0x3A, 0x00, 0x00, 0x00, // 'G'
+    0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x42, 0x7E, 0x42, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, // 'H'
+    0x00, 0x00, 0x00, 0x7C, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x7C, 0x00, 0x00, 0x00, // 'I'
+    0x00, 0x00, 0x00, 0x1E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x44, 0x44, 0x38, 0x00, 0x00, 0x00, // 'J'
+    0x00, 0x00, 0x00, 0x42, 0x44, 0x48, 0x50, 0x60, 0x50, 0x48, 0x44, 0x42, 0x42, 0x00, 0x00, 0x00, // 'K'
+    0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x7E, 0x00, 0x00, 0x00, // 'L'
+    0x00, 0x00, 0x00, 0x42, 0x66, 0x5A, 0x5A, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, // 'M'
+    0x00, 0x00, 0x00, 0x42, 0x62, 0x62, 0x52, 0x52, 0x4A, 0x4A, 0x46, 0x46, 0x42, 0x00, 0x00, 0x00, // 'N'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, // 'O'
+    0x00, 0x00, 0x00, 0x7C, 0x42, 0x42, 0x42, 0x7C, 0x40, 0x40, 0x40, 0x40, 0x40, 0x00, 0x00, 0x00, // 'P'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x4A, 0x44, 0x3A, 0x00, 0x00, 0x00, // 'Q'
+    0x00, 0x00, 0x00, 0x7C, 0x42, 0x42, 0x42, 0x7C, 0x50, 0x48, 0x44, 0x42, 0x42, 0x00, 0x00, 0x00, // 'R'
+    0x00, 0x00, 0x00, 0x3C, 0x42, 0x40, 0x40, 0x3C, 0x02, 0x02, 0x02, 0x42, 0x3C, 0x00, 0x00, 0x00, // 'S'
+    0x00, 0x00, 0x00, 0x7C, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00, 0x00, // 'T'
+    0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, // 'U'
+    0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x42, 0x42, 0x24, 0x24, 0x24, 0x18, 0x18, 0x00, 0x00, 0x00, // 'V'
+    0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x42, 0x42, 0x5A, 0x5A, 0x66, 0x66, 0x42, 0x00, 0x00, 0x00, // 'W'
+    0x00, 0x00, 0x00, 0x42, 0x42, 0x24, 0x24, 0x18, 0x18, 0x24, 0x24, 0x42, 0x42, 0x00, 0x00, 0x00, // 'X'
+    0x00, 0x00, 0x00, 0x44, 0x44, 0x28, 0x28, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00, 0x00, // 'Y'
+    0x00, 0x00, 0x00, 0x7E, 0x02, 0x04, 0x04, 0x08, 0x10, 0x20, 0x20, 0x40, 0x7E, 0x00, 0x00, 0x00, // 'Z'
+    0x00, 0x00, 0x00, 0x38, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x38, 0x00, 0x00, 0x00, // '['
+    0x00, 0x00, 0x00, 0x40, 0x20, 0x20, 0x10, 0x10, 0x08, 0x08, 0x04, 0x04, 0x02, 0x00, 0x00, 0x00, // '\\'
+    0x00, 0x00, 0x00, 0x38, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x38, 0x00, 0x00, 0x00, // ']'
+    0x00, 0x00, 0x00, 0x10, 0x28, 0x44, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '^'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7E, 0x00, // '_'
+    0x00, 0x00, 0x00, 0x20, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '`'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x02, 0x3E, 0x42, 0x42, 0x46, 0x3A, 0x00, 0x00, 0x00, // 'a'
+    0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x5C, 0x62, 0x42, 0x42, 0x42, 0x62, 0x5C, 0x00, 0x00, 0x00, // 'b'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x42, 0x40, 0x40, 0x40, 0x42, 0x3C, 0x00, 0x00, 0x00, // 'c'
+    0x00, 0x00, 0x00, 0x02, 0x02, 0x02, 0x3A, 0x46, 0x42, 0x42, 0x42, 0x46, 0x3A, 0x00, 0x00, 0x00, // 'd'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x7E, 0x40, 0x42, 0x3C, 0x00, 0x00, 0x00, // 'e'
+    0x00, 0x00, 0x00, 0x1C, 0x22, 0x20, 0x20, 0x78, 0x20, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00, 0x00, // 'f'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3A, 0x46, 0x42, 0x42, 0x46, 0x3A, 0x02, 0x42, 0x3C, 0x00, // 'g'
+    0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x5C, 0x62, 0x42, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, // 'h'
+    0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x30, 0x10, 0x10, 0x10, 0x10, 0x10, 0x38, 0x00, 0x00, 0x00, // 'i'
+    0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x44, 0x38, 0x00, // 'j'
+    0x00, 0x00, 0x00, 0x40, 0x40, 0x40, 0x44, 0x48, 0x50, 0x60, 0x50, 0x48, 0x44, 0x00, 0x00, 0x00, // 'k'
+    0x00, 0x00, 0x00, 0x30, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x38, 0x00, 0x00, 0x00, // 'l'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x6C, 0x54, 0x54, 0x54, 0x54, 0x54, 0x54, 0x00, 0x00, 0x00, // 'm'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5C, 0x62, 0x42, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00, 0x00, // 'n'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x42, 0x42, 0x42, 0x42, 0x42, 0x3C, 0x00, 0x00, 0x00, // 'o'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5C, 0x62, 0x42, 0x42, 0x42, 0x62, 0x5C, 0x40, 0x40, 0x00, // 'p'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3A, 0x46, 0x42, 0x42, 0x42, 0x46, 0x3A, 0x02, 0x02, 0x00, // 'q'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5C, 0x62, 0x40, 0x40, 0x40, 0x40, 0x40, 0x00, 0x00, 0x00, // 'r'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3C, 0x42, 0x40, 0x3C, 0x02, 0x42, 0x3C, 0x00, 0x00, 0x00, // 's'
+    0x00, 0x00, 0x00, 0x00, 0x20, 0x20, 0x78, 0x20, 0x20, 0x20, 0x20, 0x22, 0x1C, 0x00, 0x00, 0x00, // 't'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x42, 0x42, 0x46, 0x3A, 0x00, 0x00, 0x00, // 'u'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x42, 0x42, 0x24, 0x24, 0x24, 0x18, 0x18, 0x00, 0x00, 0x00, // 'v'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x42, 0x5A, 0x5A, 0x24, 0x00, 0x00, 0x00, // 'w'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x42, 0x24, 0x18, 0x18, 0x18, 0x24, 0x42, 0x00, 0x00, 0x00, // 'x'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x42, 0x42, 0x46, 0x3A, 0x02, 0x3C, 0x00, // 'y'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7E, 0x04, 0x08, 0x10, 0x20, 0x40, 0x7E, 0x00, 0x00, 0x00, // 'z'
+    0x00, 0x00, 0x00, 0x0C, 0x10, 0x10, 0x10, 0x20, 0x10, 0x10, 0x10, 0x10, 0x0C, 0x00, 0x00, 0x00, // '{'
+    0x00, 0x00, 0x00, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, // '|'
+    0x00, 0x00, 0x00, 0x30, 0x08, 0x08, 0x08, 0x04, 0x08, 0x08, 0x08, 0x08, 0x30, 0x00, 0x00, 0x00, // '}'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x32, 0x4C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '~'
+];
+
+#[cfg(all(test, not(target_os = "uefi")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builtin() {
+        let font = Font::builtin();
+        assert_eq!((font.width(), font.height()), (8, 16));
+        assert!(font.glyph('\n').is_none());
+        assert!(font.glyph('\u{E9}').is_none());
+
+        let space = font.glyph(' ').unwrap();
+        assert!((0..16).all(|y| (0..8).all(|x| !space.pixel(x, y))));
+
+        // The bar of 'T' is the first row of ink and its stem runs down to the baseline.
+        let t = font.glyph('T').unwrap();
+        assert!((1..6).all(|x| t.pixel(x, 3)));
+        assert!((3..13).all(|y| t.pixel(3, y)));
+        assert!(!t.pixel(3, 13));
+    }
+
+    #[test]
+    fn psf2() {
+        #[rustfmt::skip]
+        let bytes = [
+            0x72, 0xB5, 0x4A, 0x86, // magic
+            0, 0, 0, 0,             // version
+            32, 0, 0, 0,            // header size
+            1, 0, 0, 0,             // flags: unicode table
+            2, 0, 0, 0,             // glyph count
+            4, 0, 0, 0,             // bytes per glyph
+            2, 0, 0, 0,             // height
+            10, 0, 0, 0,            // width
+            0x00, 0x00, 0x00, 0x00, // glyph 0
+            0x80, 0x40, 0x00, 0x00, // glyph 1
+            b'a', 0xFF,
+            b'x', 0xC3, 0xA9, 0xFE, b'e', 0xCC, 0x81, 0xFF,
+        ];
+        let font = Font::from_psf2(&bytes).unwrap();
+        assert_eq!((font.width(), font.height()), (10, 2));
+
+        let glyph = font.glyph('\u{E9}').unwrap();
+        assert!(glyph.pixel(0, 0) && glyph.pixel(9, 0));
+        assert!(!glyph.pixel(1, 0) && !glyph.pixel(0, 1));
+        assert!(font.glyph('a').is_some());
+        assert!(font.glyph('e').is_none());
+
+        assert!(Font::from_psf2(&bytes[..39]).is_err());
+        assert!(Font::from_psf2(&bytes[4..]).is_err());
+    }
+}
@@ -0,0 +1,316 @@
+//! Linear frame buffer set up by the firmware through the graphics output protocol.
+
+use core::ptr::NonNull;
+
+use uefi::{
+    protocol::graphics::{EfiGraphicsOutputProtocol, EfiGraphicsPixelFormat},
+    EfiResult,
+};
+
+/// Color with 8 bits per channel, independent of the pixel format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Color {
+    #[allow(dead_code)]
+    pub const BLACK: Self = Self::new(0x00, 0x00, 0x00);
+    #[allow(dead_code)]
+    pub const WHITE: Self = Self::new(0xFF, 0xFF, 0xFF);
+    #[allow(dead_code)]
+    pub const RED: Self = Self::new(0xFF, 0x00, 0x00);
+    #[allow(dead_code)]
+    pub const GREEN: Self = Self::new(0x00, 0xFF, 0x00);
+    #[allow(dead_code)]
+    pub const BLUE: Self = Self::new(0x00, 0x00, 0xFF);
+
+    pub const fn new(red: u8, green: u8, blue: u8) -> Self {
+        Self { red, green, blue }
+    }
+
+    /// From `0xRRGGBB`, ignoring the highest byte.
+    pub const fn from_rgb(rgb: u32) -> Self {
+        Self::new((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+    }
+
+    /// To `0xRRGGBB`.
+    pub const fn to_rgb(self) -> u32 {
+        (self.red as u32) << 16 | (self.green as u32) << 8 | self.blue as u32
+    }
+
+    /// Draws `over` on top of `self` with `alpha` opacity, 0 being transparent.
+    #[allow(dead_code)]
+    pub fn blend(self, over: Self, alpha: u8) -> Self {
+        let mix = |below: u8, above: u8| {
+            let alpha = u32::from(alpha);
+            ((u32::from(above) * alpha + u32::from(below) * (255 - alpha) + 127) / 255) as u8
+        };
+        Self::new(
+            mix(self.red, over.red),
+            mix(self.green, over.green),
+            mix(self.blue, over.blue),
+        )
+    }
+}
+
+/// Layout of a 32-bit pixel in the frame buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// Red in the lowest byte, then green and blue.
+    Rgb,
+    /// Blue in the lowest byte, then green and red.
+    Bgr,
+    /// Each channel occupies the bits set in its mask.
+    Bitmask { red: u32, green: u32, blue: u32 },
+}
+
+impl PixelFormat {
+    pub fn encode(&self, color: Color) -> u32 {
+        let Color { red, green, blue } = color;
+        match *self {
+            Self::Rgb => u32::from_le_bytes([red, green, blue, 0]),
+            Self::Bgr => u32::from_le_bytes([blue, green, red, 0]),
+            Self::Bitmask {
+                red: red_mask,
+                green: green_mask,
+                blue: blue_mask,
+            } => {
+                encode_channel(red, red_mask)
+                    | encode_channel(green, green_mask)
+                    | encode_channel(blue, blue_mask)
+            }
+        }
+    }
+
+    pub fn decode(&self, pixel: u32) -> Color {
+        let [b0, b1, b2, _] = pixel.to_le_bytes();
+        match *self {
+            Self::Rgb => Color::new(b0, b1, b2),
+            Self::Bgr => Color::new(b2, b1, b0),
+            Self::Bitmask { red, green, blue } => Color::new(
+                decode_channel(pixel, red),
+                decode_channel(pixel, green),
+                decode_channel(pixel, blue),
+            ),
+        }
+    }
+}
+
+/// Scales `value` to the width of `mask`, rounding to the nearest.
+fn encode_channel(value: u8, mask: u32) -> u32 {
+    if mask == 0 {
+        return 0;
+    }
+    let shift = mask.trailing_zeros();
+    let max = u64::from(mask >> shift);
+    (((u64::from(value) * max + 127) / 255) as u32) << shift
+}
+
+fn decode_channel(pixel: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let shift = mask.trailing_zeros();
+    let max = u64::from(mask >> shift);
+    let value = u64::from((pixel & mask) >> shift);
+    ((value * 255 + max / 2) / max) as u8
+}
+
+/// Pixels of the screen in memory.
+///
+/// It only holds the address and geometry, not the protocol it came from,
+/// so it stays usable after ExitBootServices as long as the memory stays mapped.
+#[derive(Debug)]
+pub struct Framebuffer {
+    base: NonNull<u32>,
+    width: usize,
+    height: usize,
+    // Pixels per row in memory, at least `width`.
+    stride: usize,
+    format: PixelFormat,
+}
+
+// The frame buffer is plain memory owned by whoever holds the Framebuffer.
+unsafe impl Send for Framebuffer {}
+
+impl Framebuffer {
+    /// Takes the frame buffer of the current mode of `graphics`.
+    pub fn from_gop(graphics: &EfiGraphicsOutputProtocol) -> EfiResult<Self> {
+        let mode = graphics.mode();
+        let info = mode.info();
+        let format = match info.pixel_format() {
+            Ok(EfiGraphicsPixelFormat::PixelRedGreenBlueReserved8BitPerColor) => PixelFormat::Rgb,
+            Ok(EfiGraphicsPixelFormat::PixelBlueGreenRedReserved8BitPerColor) => PixelFormat::Bgr,
+            Ok(EfiGraphicsPixelFormat::PixelBitMask) => {
+                let masks = info.pixel_information();
+                PixelFormat::Bitmask {
+                    red: masks.red_mask(),
+                    green: masks.green_mask(),
+                    blue: masks.blue_mask(),
+                }
+            }
+            Ok(EfiGraphicsPixelFormat::PixelBltOnly) => {
+                return Err("Graphics output has no frame buffer")
+            }
+            Err(_) => return Err("Unknown pixel format of graphics output"),
+        };
+
+        let width = info.horizontal_resolution() as usize;
+        let height = info.vertical_resolution() as usize;
+        let stride = info.pixels_per_scan_line() as usize;
+        if stride < width {
+            return Err("Scan line of graphics output is shorter than its resolution");
+        }
+        let size = stride
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(core::mem::size_of::<u32>()))
+            .ok_or("Frame buffer of graphics output is too large")?;
+        if size > mode.frame_buffer_size() {
+            return Err("Frame buffer of graphics output is smaller than its mode");
+        }
+
+        let base = NonNull::new(mode.frame_buffer_base() as *mut u32)
+            .ok_or("Graphics output has no frame buffer")?;
+        Ok(unsafe { Self::new(base, width, height, stride, format) })
+    }
+
+    /// # Safety
+    /// `base` must point to `stride * height` pixels that stay mapped while the result is used
+    /// and that nothing else writes to.
+    pub unsafe fn new(
+        base: NonNull<u32>,
+        width: usize,
+        height: usize,
+        stride: usize,
+        format: PixelFormat,
+    ) -> Self {
+        assert!(width <= stride);
+        Self {
+            base,
+            width,
+            height,
+            stride,
+            format,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Pixels per row in memory, which may exceed the width.
+    #[allow(dead_code)]
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    #[allow(dead_code)]
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// Sets the pixel at (`x`, `y`). Pixels outside the screen are clipped.
+    pub fn write(&mut self, x: usize, y: usize, color: Color) {
+        if !self.contains(x, y) {
+            return;
+        }
+        let pixel = self.format.encode(color);
+        // Volatile, since the memory is scanned out by the display rather than read back.
+        unsafe { self.pixel_ptr(x, y).write_volatile(pixel) };
+    }
+
+    pub fn read(&self, x: usize, y: usize) -> Option<Color> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        let pixel = unsafe { self.pixel_ptr(x, y).read_volatile() };
+        Some(self.format.decode(pixel))
+    }
+
+    /// Sets every visible pixel, leaving the padding at the end of rows untouched.
+    #[allow(dead_code)]
+    pub fn fill(&mut self, color: Color) {
+        let pixel = self.format.encode(color);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                unsafe { self.pixel_ptr(x, y).write_volatile(pixel) };
+            }
+        }
+    }
+
+    /// Moves everything up by `rows` pixel rows and fills the rows freed at the bottom with `color`.
+    pub fn scroll_up(&mut self, rows: usize, color: Color) {
+        let rows = rows.min(self.height);
+        let moved = (self.height - rows) * self.stride;
+        unsafe { core::ptr::copy(self.pixel_ptr(0, rows), self.base.as_ptr(), moved) };
+
+        let pixel = self.format.encode(color);
+        for y in self.height - rows..self.height {
+            for x in 0..self.width {
+                unsafe { self.pixel_ptr(x, y).write_volatile(pixel) };
+            }
+        }
+    }
+
+    /// # Safety
+    /// (`x`, `y`) must be inside the screen, or (0, `height`) for the end of the frame buffer.
+    unsafe fn pixel_ptr(&self, x: usize, y: usize) -> *mut u32 {
+        self.base.as_ptr().add(y * self.stride + x)
+    }
+}
+
+#[cfg(all(test, not(target_os = "uefi")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pixel_formats() {
+        let color = Color::new(0x12, 0x34, 0x56);
+        assert_eq!(PixelFormat::Rgb.encode(color), 0x00563412);
+        assert_eq!(PixelFormat::Bgr.encode(color), 0x00123456);
+        assert_eq!(PixelFormat::Bgr.decode(0x00123456), color);
+
+        // 5:6:5, as some GPUs report through PixelBitMask.
+        let rgb565 = PixelFormat::Bitmask {
+            red: 0xF800,
+            green: 0x07E0,
+            blue: 0x001F,
+        };
+        assert_eq!(rgb565.encode(Color::WHITE), 0xFFFF);
+        assert_eq!(rgb565.encode(Color::RED), 0xF800);
+        assert_eq!(rgb565.encode(Color::new(0x80, 0x80, 0x80)), 0x8410);
+        assert_eq!(rgb565.decode(0x07E0), Color::GREEN);
+    }
+
+    #[test]
+    fn stride_and_clipping() {
+        const WIDTH: usize = 3;
+        const HEIGHT: usize = 2;
+        const STRIDE: usize = 4;
+        const PADDING: u32 = 0xDEADBEEF;
+        let mut memory = [PADDING; STRIDE * HEIGHT];
+        let base = NonNull::new(memory.as_mut_ptr()).unwrap();
+        let mut framebuffer =
+            unsafe { Framebuffer::new(base, WIDTH, HEIGHT, STRIDE, PixelFormat::Bgr) };
+
+        framebuffer.fill(Color::BLACK);
+        framebuffer.write(2, 1, Color::WHITE);
+        framebuffer.write(3, 0, Color::WHITE);
+        framebuffer.write(0, 2, Color::WHITE);
+        assert_eq!(framebuffer.read(2, 1), Some(Color::WHITE));
+        assert_eq!(framebuffer.read(3, 0), None);
+
+        assert_eq!(memory, [0, 0, 0, PADDING, 0, 0, 0x00FFFFFF, PADDING]);
+    }
+}
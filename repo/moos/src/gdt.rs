@@ -0,0 +1,196 @@
+//! Global descriptor table of the kernel, replacing the one of the firmware, and the task state
+//! segment holding the stacks that exceptions switch to.
+//!
+//! REF: Intel SDM Vol. 3A, 3.4.5 Segment Descriptors and 8.7 Task Management in 64-bit Mode
+
+use core::{
+    mem::size_of,
+    ptr::{addr_of, addr_of_mut},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::cpu::{self, DescriptorTablePointer};
+
+// Selectors of the segments below. User data comes before user code, as SYSRET expects.
+pub const KERNEL_CODE: u16 = 0x08;
+pub const KERNEL_DATA: u16 = 0x10;
+#[allow(dead_code)]
+pub const USER_DATA: u16 = 0x18 | 3;
+#[allow(dead_code)]
+pub const USER_CODE: u16 = 0x20 | 3;
+pub const TSS: u16 = 0x28;
+
+// Interrupt stack table entries, 1-based as in IDT gates.
+pub const DOUBLE_FAULT_IST: u8 = 1;
+pub const NMI_IST: u8 = 2;
+pub const MACHINE_CHECK_IST: u8 = 3;
+
+/// Size of each IST stack, enough for the panic handler to print a backtrace.
+const IST_STACK_SIZE: usize = 32 * 1024;
+const IST_STACK_COUNT: usize = 3;
+
+// Access bytes: present, ring 0 or 3, code or data, and readable or writable.
+const KERNEL_CODE_ACCESS: u8 = 0x9A;
+const KERNEL_DATA_ACCESS: u8 = 0x92;
+const USER_CODE_ACCESS: u8 = 0xFA;
+const USER_DATA_ACCESS: u8 = 0xF2;
+/// Present, available 64-bit TSS.
+const TSS_ACCESS: u8 = 0x89;
+// Flags: 4 KiB granularity, with the long mode bit for code and the 32-bit bit for data.
+const CODE_FLAGS: u8 = 0xA;
+const DATA_FLAGS: u8 = 0xC;
+
+/// Null, four segments, and the TSS, whose descriptor takes two entries.
+const GDT_ENTRIES: usize = 7;
+
+/// Task state segment of 64-bit mode, which only holds stack pointers.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+struct TaskStateSegment {
+    _reserved0: u32,
+    /// Stacks for interrupts raising the privilege to rings 0, 1 and 2.
+    privilege_stacks: [u64; 3],
+    _reserved1: u64,
+    interrupt_stacks: [u64; 7],
+    _reserved2: u64,
+    _reserved3: u16,
+    io_map_base: u16,
+}
+
+const _: () = assert!(size_of::<TaskStateSegment>() == 104);
+
+impl TaskStateSegment {
+    /// No I/O permission bitmap, so ring 3 has no access to ports.
+    const fn new(interrupt_stacks: [u64; 7]) -> Self {
+        Self {
+            _reserved0: 0,
+            privilege_stacks: [0; 3],
+            _reserved1: 0,
+            interrupt_stacks,
+            _reserved2: 0,
+            _reserved3: 0,
+            io_map_base: size_of::<Self>() as u16,
+        }
+    }
+}
+
+#[repr(C, align(16))]
+struct Stack([u8; IST_STACK_SIZE]);
+
+/// Flat segment covering the whole address space, as base and limit are ignored in 64-bit mode.
+const fn segment_descriptor(access: u8, flags: u8) -> u64 {
+    0xFFFF | (access as u64) << 40 | 0xF << 48 | (flags as u64) << 52
+}
+
+/// The two entries describing a TSS at `base`.
+fn tss_descriptor(base: u64) -> [u64; 2] {
+    let limit = size_of::<TaskStateSegment>() as u64 - 1;
+    let low = limit & 0xFFFF
+        | (base & 0xFF_FFFF) << 16
+        | (TSS_ACCESS as u64) << 40
+        | (limit >> 16 & 0xF) << 48
+        | (base >> 24 & 0xFF) << 56;
+    [low, base >> 32]
+}
+
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+// The processor refers to these as long as they are loaded, so they never move or go away.
+// Only `init` writes them, once.
+static mut IST_STACKS: [Stack; IST_STACK_COUNT] = [
+    Stack([0; IST_STACK_SIZE]),
+    Stack([0; IST_STACK_SIZE]),
+    Stack([0; IST_STACK_SIZE]),
+];
+static mut TASK_STATE_SEGMENT: TaskStateSegment = TaskStateSegment::new([0; 7]);
+static mut GDT: [u64; GDT_ENTRIES] = [0; GDT_ENTRIES];
+
+/// Installs the GDT and TSS of the kernel and reloads every segment register.
+///
+/// This disables interrupts for good: the gates of the firmware refer to its own code segment,
+/// which the new GDT lacks, so interrupts may only come back with the IDT of the kernel.
+/// Boot services that need timer events must not be used afterwards.
+pub fn init() {
+    assert!(
+        !INITIALIZED.swap(true, Ordering::Relaxed),
+        "GDT is already initialized"
+    );
+    cpu::disable_interrupts();
+
+    unsafe {
+        let mut interrupt_stacks = [0; 7];
+        for (i, stack) in (*addr_of!(IST_STACKS)).iter().enumerate() {
+            // Stacks grow down from their end.
+            interrupt_stacks[i] = stack.0.as_ptr_range().end as u64;
+        }
+        TASK_STATE_SEGMENT = TaskStateSegment::new(interrupt_stacks);
+
+        let [tss_low, tss_high] = tss_descriptor(addr_of!(TASK_STATE_SEGMENT) as u64);
+        GDT = [
+            0,
+            segment_descriptor(KERNEL_CODE_ACCESS, CODE_FLAGS),
+            segment_descriptor(KERNEL_DATA_ACCESS, DATA_FLAGS),
+            segment_descriptor(USER_DATA_ACCESS, DATA_FLAGS),
+            segment_descriptor(USER_CODE_ACCESS, CODE_FLAGS),
+            tss_low,
+            tss_high,
+        ];
+
+        cpu::load_gdt(&DescriptorTablePointer {
+            limit: (size_of::<[u64; GDT_ENTRIES]>() - 1) as u16,
+            base: addr_of_mut!(GDT) as u64,
+        });
+        cpu::set_code_segment(KERNEL_CODE);
+        cpu::set_data_segments(KERNEL_DATA);
+        cpu::load_task_register(TSS);
+    }
+}
+
+#[cfg(all(test, not(target_os = "uefi")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn descriptors() {
+        // The values every 64-bit kernel ends up with.
+        assert_eq!(
+            segment_descriptor(KERNEL_CODE_ACCESS, CODE_FLAGS),
+            0x00AF_9A00_0000_FFFF
+        );
+        assert_eq!(
+            segment_descriptor(KERNEL_DATA_ACCESS, DATA_FLAGS),
+            0x00CF_9200_0000_FFFF
+        );
+        assert_eq!(
+            segment_descriptor(USER_CODE_ACCESS, CODE_FLAGS),
+            0x00AF_FA00_0000_FFFF
+        );
+        assert_eq!(
+            segment_descriptor(USER_DATA_ACCESS, DATA_FLAGS),
+            0x00CF_F200_0000_FFFF
+        );
+
+        assert_eq!(
+            tss_descriptor(0xFFFF_8000_1234_5678),
+            [0x1200_8934_5678_0067, 0xFFFF_8000]
+        );
+    }
+}
+
+#[cfg(all(test, target_os = "uefi"))]
+mod qemu_test {
+    use super::*;
+
+    /// efi_main installs the GDT before running the tests.
+    #[test_case]
+    fn segments() {
+        assert_eq!(cpu::code_segment(), KERNEL_CODE);
+        assert_eq!(cpu::task_register(), TSS);
+
+        let stacks = unsafe { addr_of!(TASK_STATE_SEGMENT).read().interrupt_stacks };
+        for ist in [DOUBLE_FAULT_IST, NMI_IST, MACHINE_CHECK_IST] {
+            let top = stacks[ist as usize - 1];
+            assert!(top != 0 && top % 16 == 0);
+        }
+    }
+}
@@ -0,0 +1,477 @@
+//! 2D drawing on anything that stores pixels, on screen or off screen.
+//!
+//! Coordinates are signed so that shapes may extend past the edges, where they are clipped.
+
+use crate::framebuffer::{Color, Framebuffer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Point {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Point {
+    #[allow(dead_code)]
+    pub const fn new(x: isize, y: isize) -> Self {
+        Self { x, y }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: isize,
+    pub y: isize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub const fn new(x: isize, y: isize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// Read-only image in memory, `0xAARRGGBB` per pixel, row by row.
+#[derive(Debug, Clone, Copy)]
+pub struct Image<'a> {
+    pixels: &'a [u32],
+    width: usize,
+    height: usize,
+}
+
+impl<'a> Image<'a> {
+    #[allow(dead_code)]
+    pub fn new(pixels: &'a [u32], width: usize, height: usize) -> Self {
+        assert_eq!(pixels.len(), width * height);
+        Self {
+            pixels,
+            width,
+            height,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[allow(dead_code)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Color and alpha of the pixel at (`x`, `y`).
+    #[allow(dead_code)]
+    pub fn pixel(&self, x: usize, y: usize) -> (Color, u8) {
+        let argb = self.pixels[y * self.width + x];
+        (Color::from_rgb(argb), (argb >> 24) as u8)
+    }
+}
+
+/// Off-screen pixels in memory, `0x00RRGGBB` each, row by row.
+#[derive(Debug)]
+pub struct Canvas<'a> {
+    pixels: &'a mut [u32],
+    width: usize,
+    height: usize,
+}
+
+impl<'a> Canvas<'a> {
+    #[allow(dead_code)]
+    pub fn new(pixels: &'a mut [u32], width: usize, height: usize) -> Self {
+        assert_eq!(pixels.len(), width * height);
+        Self {
+            pixels,
+            width,
+            height,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn pixels(&self) -> &[u32] {
+        self.pixels
+    }
+}
+
+/// Destination of drawing. Only pixel access is required; the shapes are built on top of it.
+pub trait PixelWriter {
+    fn width(&self) -> usize;
+
+    fn height(&self) -> usize;
+
+    /// Sets the pixel at (`x`, `y`). Pixels outside are ignored.
+    fn write(&mut self, x: usize, y: usize, color: Color);
+
+    /// Returns `None` outside.
+    fn read(&self, x: usize, y: usize) -> Option<Color>;
+
+    fn draw_pixel(&mut self, point: Point, color: Color) {
+        if let Some((x, y)) = clip(self, point) {
+            self.write(x, y, color);
+        }
+    }
+
+    /// Draws `color` over the pixel with `alpha` opacity.
+    fn blend_pixel(&mut self, point: Point, color: Color, alpha: u8) {
+        if let Some((x, y)) = clip(self, point) {
+            if let Some(below) = self.read(x, y) {
+                self.write(x, y, below.blend(color, alpha));
+            }
+        }
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let (xs, ys) = clip_rect(self, rect);
+        for y in ys {
+            for x in xs.clone() {
+                self.write(x, y, color);
+            }
+        }
+    }
+
+    /// Fills with `color` at `alpha` opacity, letting what is below show through.
+    fn blend_rect(&mut self, rect: Rect, color: Color, alpha: u8) {
+        let (xs, ys) = clip_rect(self, rect);
+        for y in ys {
+            for x in xs.clone() {
+                if let Some(below) = self.read(x, y) {
+                    self.write(x, y, below.blend(color, alpha));
+                }
+            }
+        }
+    }
+
+    /// Draws the 1-pixel border just inside `rect`.
+    fn draw_rect(&mut self, rect: Rect, color: Color) {
+        if rect.width == 0 || rect.height == 0 {
+            return;
+        }
+        let right = rect.x + rect.width as isize - 1;
+        let bottom = rect.y + rect.height as isize - 1;
+        self.fill_rect(Rect::new(rect.x, rect.y, rect.width, 1), color);
+        self.fill_rect(Rect::new(rect.x, bottom, rect.width, 1), color);
+        self.fill_rect(Rect::new(rect.x, rect.y, 1, rect.height), color);
+        self.fill_rect(Rect::new(right, rect.y, 1, rect.height), color);
+    }
+
+    /// Bresenham's line, including both ends.
+    fn draw_line(&mut self, from: Point, to: Point, color: Color) {
+        let dx = (to.x - from.x).abs();
+        let dy = -(to.y - from.y).abs();
+        let step_x = if from.x < to.x { 1 } else { -1 };
+        let step_y = if from.y < to.y { 1 } else { -1 };
+        let mut error = dx + dy;
+        let mut point = from;
+        loop {
+            self.draw_pixel(point, color);
+            if point == to {
+                break;
+            }
+            let error2 = 2 * error;
+            if error2 >= dy {
+                error += dy;
+                point.x += step_x;
+            }
+            if error2 <= dx {
+                error += dx;
+                point.y += step_y;
+            }
+        }
+    }
+
+    /// Midpoint circle through the points `radius` away from `center`.
+    fn draw_circle(&mut self, center: Point, radius: usize, color: Color) {
+        for_each_octant(radius, |x, y| {
+            for (dx, dy) in [(x, y), (y, x), (-y, x), (-x, y)] {
+                self.draw_pixel(Point::new(center.x + dx, center.y + dy), color);
+                self.draw_pixel(Point::new(center.x - dx, center.y - dy), color);
+            }
+        });
+    }
+
+    fn fill_circle(&mut self, center: Point, radius: usize, color: Color) {
+        for_each_octant(radius, |x, y| {
+            for (half, dy) in [(x, y), (x, -y), (y, x), (y, -x)] {
+                let span = Rect::new(center.x - half, center.y + dy, 2 * half as usize + 1, 1);
+                self.fill_rect(span, color);
+            }
+        });
+    }
+
+    /// Copies `image` with its top-left corner at `at`, blending by the alpha of each pixel.
+    fn blit(&mut self, image: &Image, at: Point) {
+        let (xs, ys) = clip_rect(self, Rect::new(at.x, at.y, image.width, image.height));
+        for y in ys {
+            for x in xs.clone() {
+                let (color, alpha) =
+                    image.pixel((x as isize - at.x) as usize, (y as isize - at.y) as usize);
+                match alpha {
+                    0 => {}
+                    u8::MAX => self.write(x, y, color),
+                    _ => {
+                        if let Some(below) = self.read(x, y) {
+                            self.write(x, y, below.blend(color, alpha));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Moves everything up by `rows` pixel rows and fills the rows freed at the bottom with `color`.
+    ///
+    /// Copies pixel by pixel; writers backed by plain memory should move whole rows instead.
+    fn scroll_up(&mut self, rows: usize, color: Color) {
+        let rows = rows.min(self.height());
+        for y in 0..self.height() - rows {
+            for x in 0..self.width() {
+                if let Some(below) = self.read(x, y + rows) {
+                    self.write(x, y, below);
+                }
+            }
+        }
+        let freed = self.height() - rows;
+        self.fill_rect(Rect::new(0, freed as isize, self.width(), rows), color);
+    }
+}
+
+#[allow(dead_code)]
+fn clip<W: PixelWriter + ?Sized>(writer: &W, point: Point) -> Option<(usize, usize)> {
+    let x = usize::try_from(point.x)
+        .ok()
+        .filter(|&x| x < writer.width())?;
+    let y = usize::try_from(point.y)
+        .ok()
+        .filter(|&y| y < writer.height())?;
+    Some((x, y))
+}
+
+/// Columns and rows of `rect` that are inside `writer`.
+fn clip_rect<W: PixelWriter + ?Sized>(
+    writer: &W,
+    rect: Rect,
+) -> (core::ops::Range<usize>, core::ops::Range<usize>) {
+    let span = |start: isize, length: usize, limit: usize| {
+        let end = start.saturating_add_unsigned(length);
+        let clamp = |value: isize| value.clamp(0, limit as isize) as usize;
+        clamp(start)..clamp(end)
+    };
+    (
+        span(rect.x, rect.width, writer.width()),
+        span(rect.y, rect.height, writer.height()),
+    )
+}
+
+/// Calls `plot` with the points of the first octant of a circle, from (`radius`, 0) to the diagonal.
+#[allow(dead_code)]
+fn for_each_octant(radius: usize, mut plot: impl FnMut(isize, isize)) {
+    let mut x = radius as isize;
+    let mut y = 0;
+    let mut error = 1 - x;
+    while x >= y {
+        plot(x, y);
+        y += 1;
+        if error < 0 {
+            error += 2 * y + 1;
+        } else {
+            x -= 1;
+            error += 2 * (y - x) + 1;
+        }
+    }
+}
+
+impl PixelWriter for Framebuffer {
+    fn width(&self) -> usize {
+        Framebuffer::width(self)
+    }
+
+    fn height(&self) -> usize {
+        Framebuffer::height(self)
+    }
+
+    fn write(&mut self, x: usize, y: usize, color: Color) {
+        Framebuffer::write(self, x, y, color)
+    }
+
+    fn read(&self, x: usize, y: usize) -> Option<Color> {
+        Framebuffer::read(self, x, y)
+    }
+
+    fn scroll_up(&mut self, rows: usize, color: Color) {
+        Framebuffer::scroll_up(self, rows, color)
+    }
+}
+
+impl PixelWriter for Canvas<'_> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn write(&mut self, x: usize, y: usize, color: Color) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color.to_rgb();
+        }
+    }
+
+    fn read(&self, x: usize, y: usize) -> Option<Color> {
+        if x < self.width && y < self.height {
+            Some(Color::from_rgb(self.pixels[y * self.width + x]))
+        } else {
+            None
+        }
+    }
+
+    fn scroll_up(&mut self, rows: usize, color: Color) {
+        let rows = rows.min(self.height);
+        self.pixels.copy_within(rows * self.width.., 0);
+        let freed = (self.height - rows) * self.width;
+        self.pixels[freed..].fill(color.to_rgb());
+    }
+}
+
+#[cfg(all(test, not(target_os = "uefi")))]
+mod test {
+    use super::*;
+    use std::{string::String, vec, vec::Vec};
+
+    /// Renders `draw` on a `width` x `height` black canvas and compares with `golden`,
+    /// one line per row: `.` black, `#` white, `R`/`G`/`B` primaries, `?` anything else.
+    fn assert_golden(width: usize, height: usize, draw: impl FnOnce(&mut Canvas), golden: &str) {
+        let mut pixels = vec![0; width * height];
+        draw(&mut Canvas::new(&mut pixels, width, height));
+
+        let rendered: Vec<String> = pixels
+            .chunks(width)
+            .map(|row| {
+                row.iter()
+                    .map(|&rgb| match Color::from_rgb(rgb) {
+                        Color::BLACK => '.',
+                        Color::WHITE => '#',
+                        Color::RED => 'R',
+                        Color::GREEN => 'G',
+                        Color::BLUE => 'B',
+                        _ => '?',
+                    })
+                    .collect()
+            })
+            .collect();
+        let golden: Vec<&str> = golden.split_whitespace().collect();
+        assert_eq!(rendered, golden, "\n{}", rendered.join("\n"));
+    }
+
+    #[test]
+    fn rectangles() {
+        assert_golden(
+            8,
+            6,
+            |canvas| {
+                canvas.draw_rect(Rect::new(0, 0, 5, 4), Color::WHITE);
+                canvas.fill_rect(Rect::new(2, 1, 2, 2), Color::RED);
+                canvas.fill_rect(Rect::new(6, 4, 10, 10), Color::BLUE);
+                canvas.fill_rect(Rect::new(-3, 5, 4, 1), Color::GREEN);
+            },
+            "
+            #####...
+            #.RR#...
+            #.RR#...
+            #####...
+            ......BB
+            G.....BB
+            ",
+        );
+    }
+
+    #[test]
+    fn lines() {
+        assert_golden(
+            7,
+            5,
+            |canvas| {
+                canvas.draw_line(Point::new(0, 0), Point::new(6, 2), Color::WHITE);
+                canvas.draw_line(Point::new(0, 4), Point::new(2, 0), Color::RED);
+                canvas.draw_line(Point::new(6, 4), Point::new(3, 4), Color::BLUE);
+                canvas.draw_line(Point::new(8, 6), Point::new(5, 3), Color::GREEN);
+            },
+            "
+            ##R....
+            ..R##..
+            .R...##
+            .R...G.
+            R..BBBG
+            ",
+        );
+    }
+
+    #[test]
+    fn circles() {
+        assert_golden(
+            15,
+            7,
+            |canvas| {
+                canvas.draw_circle(Point::new(3, 3), 3, Color::WHITE);
+                canvas.fill_circle(Point::new(10, 3), 3, Color::RED);
+                canvas.draw_circle(Point::new(14, 0), 1, Color::BLUE);
+            },
+            "
+            ..###....RRR.B.
+            .#...#..RRRRR.B
+            #.....#RRRRRRR.
+            #.....#RRRRRRR.
+            #.....#RRRRRRR.
+            .#...#..RRRRR..
+            ..###....RRR...
+            ",
+        );
+    }
+
+    #[test]
+    fn alpha_blending() {
+        assert_eq!(Color::BLACK.blend(Color::WHITE, 0), Color::BLACK);
+        assert_eq!(Color::BLACK.blend(Color::WHITE, 255), Color::WHITE);
+        assert_eq!(
+            Color::new(0x00, 0x80, 0xFF).blend(Color::new(0xFF, 0x00, 0xFF), 0x40),
+            Color::new(0x40, 0x60, 0xFF)
+        );
+
+        let mut pixels = vec![0; 2];
+        let mut canvas = Canvas::new(&mut pixels, 2, 1);
+        canvas.fill_rect(Rect::new(0, 0, 2, 1), Color::BLUE);
+        canvas.blend_rect(Rect::new(1, 0, 1, 1), Color::RED, 0x80);
+        canvas.blend_pixel(Point::new(2, 0), Color::RED, 0x80);
+        assert_eq!(pixels, [0x0000FF, 0x80007F]);
+    }
+
+    #[test]
+    fn bit_blit() {
+        const O: u32 = 0xFFFFFFFF;
+        const T: u32 = 0x00FFFFFF;
+        const R: u32 = 0xFFFF0000;
+        let sprite = [O, T, R, T, O, T];
+        let image = Image::new(&sprite, 3, 2);
+
+        assert_golden(
+            5,
+            3,
+            |canvas| {
+                canvas.fill_rect(Rect::new(0, 0, 5, 3), Color::GREEN);
+                canvas.blit(&image, Point::new(1, 0));
+                canvas.blit(&image, Point::new(3, 2));
+                canvas.blit(&image, Point::new(-2, 2));
+            },
+            "
+            G#GRG
+            GG#GG
+            RGG#G
+            ",
+        );
+    }
+}
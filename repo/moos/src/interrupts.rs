@@ -0,0 +1,314 @@
+//! Interrupt descriptor table of the kernel and the entry stubs behind every vector.
+//!
+//! Each stub pushes the vector, and a zero where the processor pushes no error code, so that
+//! a common stub can save the general registers and pass them as one `Context` to `dispatch`.
+//!
+//! REF: Intel SDM Vol. 3A, 6.12 Exception and Interrupt Handling
+
+use core::{
+    fmt,
+    mem::size_of,
+    ptr::{addr_of, addr_of_mut},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+    apic,
+    cpu::{self, DescriptorTablePointer},
+    exceptions, gdt,
+    sync::SpinLock,
+};
+
+pub const VECTOR_COUNT: usize = 256;
+/// Vectors below this are reserved for CPU exceptions.
+pub const EXCEPTION_COUNT: usize = 32;
+
+/// Distance between two stubs, each fitting in 16 bytes.
+const STUB_SIZE: usize = 16;
+/// Present, ring 0, 64-bit interrupt gate, which clears IF on entry.
+const INTERRUPT_GATE: u8 = 0x8E;
+
+// The stubs in AT&T syntax, where an immediate cannot be mistaken for a memory operand.
+// The list of vectors with an error code must match `Exception::has_error_code`.
+core::arch::global_asm!(
+    r#"
+    .text
+    .balign 16
+    moos_interrupt_stubs:
+    .set vector, 0
+    .rept 256
+        .balign 16
+        .if !(vector == 8 || (vector >= 10 && vector <= 14) || vector == 17 || vector == 21 || vector == 29 || vector == 30)
+            pushq $0
+        .endif
+        pushq $vector
+        jmp moos_interrupt_common
+        .set vector, vector + 1
+    .endr
+
+    moos_interrupt_common:
+        pushq %rax
+        pushq %rbx
+        pushq %rcx
+        pushq %rdx
+        pushq %rsi
+        pushq %rdi
+        pushq %rbp
+        pushq %r8
+        pushq %r9
+        pushq %r10
+        pushq %r11
+        pushq %r12
+        pushq %r13
+        pushq %r14
+        pushq %r15
+        movq %rsp, %rdi
+        // The interrupted code may have left the stack aligned to 8 only, while calls expect 16.
+        movq %rsp, %rbx
+        andq $-16, %rsp
+        cld
+        callq {dispatch}
+        movq %rbx, %rsp
+        popq %r15
+        popq %r14
+        popq %r13
+        popq %r12
+        popq %r11
+        popq %r10
+        popq %r9
+        popq %r8
+        popq %rbp
+        popq %rdi
+        popq %rsi
+        popq %rdx
+        popq %rcx
+        popq %rbx
+        popq %rax
+        // Vector and error code.
+        addq $16, %rsp
+        iretq
+
+    .globl moos_interrupt_stubs
+    "#,
+    dispatch = sym dispatch,
+    options(att_syntax),
+);
+
+extern "C" {
+    static moos_interrupt_stubs: u8;
+}
+
+/// Registers saved by the common stub, in the reverse order of the pushes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GeneralRegisters {
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub r11: u64,
+    pub r10: u64,
+    pub r9: u64,
+    pub r8: u64,
+    pub rbp: u64,
+    pub rdi: u64,
+    pub rsi: u64,
+    pub rdx: u64,
+    pub rcx: u64,
+    pub rbx: u64,
+    pub rax: u64,
+}
+
+/// Pushed by the processor on every interrupt in 64-bit mode.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct InterruptStackFrame {
+    pub rip: u64,
+    pub cs: u64,
+    pub rflags: u64,
+    pub rsp: u64,
+    pub ss: u64,
+}
+
+/// State of the interrupted code. Changes are restored when the handler returns.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Context {
+    pub registers: GeneralRegisters,
+    pub vector: u64,
+    /// 0 for vectors without an error code.
+    pub error_code: u64,
+    pub frame: InterruptStackFrame,
+}
+
+const _: () = assert!(size_of::<Context>() == 22 * size_of::<u64>());
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let GeneralRegisters {
+            r15,
+            r14,
+            r13,
+            r12,
+            r11,
+            r10,
+            r9,
+            r8,
+            rbp,
+            rdi,
+            rsi,
+            rdx,
+            rcx,
+            rbx,
+            rax,
+        } = self.registers;
+        let InterruptStackFrame {
+            rip,
+            cs,
+            rflags,
+            rsp,
+            ss,
+        } = self.frame;
+        let rows = [
+            [("RIP", rip), ("RSP", rsp), ("RFL", rflags)],
+            [("RAX", rax), ("RBX", rbx), ("RCX", rcx)],
+            [("RDX", rdx), ("RSI", rsi), ("RDI", rdi)],
+            [("RBP", rbp), ("R8", r8), ("R9", r9)],
+            [("R10", r10), ("R11", r11), ("R12", r12)],
+            [("R13", r13), ("R14", r14), ("R15", r15)],
+        ];
+        for row in rows {
+            for (i, (name, value)) in row.into_iter().enumerate() {
+                let separator = if i == 0 { "" } else { " " };
+                write!(f, "{}{:<3}={:#018x}", separator, name, value)?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "CS={:#x} SS={:#x}", cs, ss)
+    }
+}
+
+/// The two halves of a gate to `offset` in the code segment `selector`.
+/// `ist` selects a stack of the TSS to switch to, 0 keeping the current one.
+fn gate_descriptor(offset: u64, selector: u16, ist: u8, attributes: u8) -> [u64; 2] {
+    let low = offset & 0xFFFF
+        | (selector as u64) << 16
+        | (ist as u64 & 0x7) << 32
+        | (attributes as u64) << 40
+        | (offset >> 16 & 0xFFFF) << 48;
+    [low, offset >> 32]
+}
+
+/// Stack of the TSS for vectors that may arrive while the current stack is unusable.
+fn interrupt_stack(vector: usize) -> u8 {
+    match vector {
+        exceptions::DOUBLE_FAULT => gdt::DOUBLE_FAULT_IST,
+        exceptions::NMI => gdt::NMI_IST,
+        exceptions::MACHINE_CHECK => gdt::MACHINE_CHECK_IST,
+        _ => 0,
+    }
+}
+
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+// The processor refers to it as long as it is loaded. Only `init` writes it, once.
+static mut IDT: [[u64; 2]; VECTOR_COUNT] = [[0; 2]; VECTOR_COUNT];
+
+/// Installs the IDT, sending every vector to `dispatch`.
+///
+/// The GDT of the kernel must be installed, as the gates refer to its code segment and its TSS.
+/// Interrupts stay disabled.
+pub fn init() {
+    assert!(
+        !INITIALIZED.swap(true, Ordering::Relaxed),
+        "IDT is already initialized"
+    );
+
+    unsafe {
+        let stubs = addr_of!(moos_interrupt_stubs) as u64;
+        for (vector, gate) in (*addr_of_mut!(IDT)).iter_mut().enumerate() {
+            *gate = gate_descriptor(
+                stubs + (vector * STUB_SIZE) as u64,
+                gdt::KERNEL_CODE,
+                interrupt_stack(vector),
+                INTERRUPT_GATE,
+            );
+        }
+        cpu::load_idt(&DescriptorTablePointer {
+            limit: (size_of::<[[u64; 2]; VECTOR_COUNT]>() - 1) as u16,
+            base: addr_of!(IDT) as u64,
+        });
+    }
+}
+
+/// Handles an interrupt above the exceptions, e.g. an IRQ, with interrupts disabled.
+/// The local APIC gets its EOI afterwards.
+pub type Handler = fn(&mut Context);
+
+static HANDLERS: SpinLock<[Option<Handler>; VECTOR_COUNT - EXCEPTION_COUNT]> =
+    SpinLock::new([None; VECTOR_COUNT - EXCEPTION_COUNT]);
+
+/// # Panics
+/// If `vector` is one of the CPU exceptions, whose handlers are set with `exceptions::set_handler`.
+#[allow(dead_code)]
+pub fn set_handler(vector: u8, handler: Option<Handler>) {
+    let index = (vector as usize)
+        .checked_sub(EXCEPTION_COUNT)
+        .expect("Vector of a CPU exception");
+    // An interrupt arriving meanwhile would find the lock held.
+    cpu::without_interrupts(|| HANDLERS.lock()[index] = handler);
+}
+
+/// Called by the common stub with interrupts disabled.
+extern "sysv64" fn dispatch(context: &mut Context) {
+    let vector = context.vector as usize;
+    if vector < EXCEPTION_COUNT {
+        exceptions::handle(context);
+        return;
+    }
+    if vector == apic::SPURIOUS_VECTOR as usize {
+        return;
+    }
+
+    // Handlers run with interrupts disabled, so only `set_handler` on another processor can hold the lock.
+    let handler = HANDLERS
+        .try_lock()
+        .and_then(|handlers| handlers[vector - EXCEPTION_COUNT]);
+    match handler {
+        Some(handler) => handler(context),
+        None => crate::try_log!(crate::log::Level::Warn, "Unexpected interrupt {}", vector),
+    }
+    // With the 8259s disabled, every other vector comes from the local APIC.
+    apic::end_of_interrupt();
+}
+
+#[cfg(all(test, not(target_os = "uefi")))]
+mod test {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn gates() {
+        assert_eq!(
+            gate_descriptor(0xFFFF_8000_1234_5678, gdt::KERNEL_CODE, 1, INTERRUPT_GATE),
+            [0x1234_8E01_0008_5678, 0xFFFF_8000]
+        );
+        assert_eq!(
+            interrupt_stack(exceptions::DOUBLE_FAULT),
+            gdt::DOUBLE_FAULT_IST
+        );
+        assert_eq!(interrupt_stack(exceptions::PAGE_FAULT), 0);
+    }
+
+    #[test]
+    fn context() {
+        let mut context: Context = unsafe { core::mem::zeroed() };
+        context.frame.rip = 0x1234;
+        context.registers.r8 = u64::MAX;
+        context.frame.cs = gdt::KERNEL_CODE as u64;
+        let text = format!("{}", context);
+        assert!(text.starts_with("RIP=0x0000000000001234 RSP=0x0000000000000000"));
+        assert!(text.contains(" R8 =0xffffffffffffffff R9 =0x0000000000000000\n"));
+        assert!(text.ends_with("\nCS=0x8 SS=0x0"));
+    }
+}
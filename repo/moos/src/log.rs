@@ -0,0 +1,285 @@
+//! Leveled logging, mirrored to the serial port and the framebuffer console.
+//!
+//! `error!`, `warn!`, `info!`, `debug!` and `trace!` take `format!` arguments and tag the message
+//! with the level and the module it comes from. Messages above `max_level()` are dropped.
+//! Once [`set_clock`] is called, messages are also stamped with the UTC time of day.
+//!
+//! Interrupt and exception handlers log with `try_log!`, which drops the message rather than
+//! wait for the serial port or the console that the interrupted code may hold.
+
+use core::{
+    arch::x86_64::_rdtsc,
+    fmt::{self, Write},
+    sync::atomic::{AtomicI64, AtomicU64, AtomicU8, Ordering},
+};
+
+use uefi::efi_runtime_services::EfiTime;
+
+use crate::{console, serial};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    const ALL: [Self; 5] = [
+        Self::Error,
+        Self::Warn,
+        Self::Info,
+        Self::Debug,
+        Self::Trace,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        }
+    }
+
+    /// SGR sequence coloring the level on the console.
+    fn color(&self) -> &'static str {
+        match self {
+            Self::Error => "\x1B[91m",
+            Self::Warn => "\x1B[93m",
+            Self::Info => "\x1B[92m",
+            Self::Debug => "\x1B[96m",
+            Self::Trace => "\x1B[90m",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+
+pub fn max_level() -> Level {
+    let value = MAX_LEVEL.load(Ordering::Relaxed);
+    Level::ALL[value as usize - 1]
+}
+
+pub fn set_max_level(level: Level) {
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+// Nanoseconds since the Unix epoch at TSC value CLOCK_ORIGIN. CLOCK_FREQUENCY is 0 until set.
+static CLOCK_BASE: AtomicI64 = AtomicI64::new(0);
+static CLOCK_ORIGIN: AtomicU64 = AtomicU64::new(0);
+static CLOCK_FREQUENCY: AtomicU64 = AtomicU64::new(0);
+
+/// Stamps messages with `time`, read from the wall clock once at boot, advanced by the TSC
+/// running at `frequency` ticks per second.
+/// Fails for times after 2262, which do not fit in nanoseconds since the Unix epoch.
+pub fn set_clock(time: &EfiTime, frequency: u64) -> Result<(), &'static str> {
+    let base = time
+        .to_unix_timestamp()
+        .checked_mul(1_000_000_000)
+        .and_then(|nanoseconds| nanoseconds.checked_add(i64::from(time.nanosecond())))
+        .ok_or("Wall clock is out of range")?;
+    CLOCK_BASE.store(base, Ordering::Relaxed);
+    CLOCK_ORIGIN.store(unsafe { _rdtsc() }, Ordering::Relaxed);
+    CLOCK_FREQUENCY.store(frequency, Ordering::Release);
+    Ok(())
+}
+
+/// UTC time, or `None` before [`set_clock`].
+pub fn now() -> Option<EfiTime> {
+    let frequency = CLOCK_FREQUENCY.load(Ordering::Acquire);
+    if frequency == 0 {
+        return None;
+    }
+    let ticks = unsafe { _rdtsc() }.wrapping_sub(CLOCK_ORIGIN.load(Ordering::Relaxed));
+    let elapsed = u128::from(ticks) * 1_000_000_000 / u128::from(frequency);
+    let nanoseconds = CLOCK_BASE
+        .load(Ordering::Relaxed)
+        .saturating_add(i64::try_from(elapsed).ok()?);
+    EfiTime::from_unix_timestamp(
+        nanoseconds.div_euclid(1_000_000_000),
+        nanoseconds.rem_euclid(1_000_000_000) as u32,
+    )
+    .ok()
+}
+
+/// `09:30:00.123 INFO  moos::machine: message`, with the level colored if `color` is set.
+/// The time is left out if there is none.
+fn write_record(
+    writer: &mut (impl Write + ?Sized),
+    time: Option<&EfiTime>,
+    level: Level,
+    target: &str,
+    args: fmt::Arguments,
+    color: bool,
+) -> fmt::Result {
+    if let Some(time) = time {
+        write!(
+            writer,
+            "{:02}:{:02}:{:02}.{:03} ",
+            time.hour(),
+            time.minute(),
+            time.second(),
+            time.nanosecond() / 1_000_000
+        )?;
+    }
+    if color {
+        write!(writer, "{}{:<5}\x1B[0m", level.color(), level)?;
+    } else {
+        write!(writer, "{:<5}", level)?;
+    }
+    writeln!(writer, " {}: {}", target, args)
+}
+
+/// Writes the record to the serial port and the console, each held for the whole record.
+/// Without `wait`, an output that someone else holds is skipped.
+fn emit(level: Level, target: &str, args: fmt::Arguments, wait: bool) {
+    if level > max_level() {
+        return;
+    }
+    let time = now();
+    let time = time.as_ref();
+    let plain = |writer: &mut dyn Write| write_record(writer, time, level, target, args, false);
+    let colored = |writer: &mut dyn Write| write_record(writer, time, level, target, args, true);
+    if wait {
+        let _ = serial::write_all(plain);
+        let _ = console::write_all(colored);
+    } else {
+        let _ = serial::try_write_all(plain);
+        let _ = console::try_write_all(colored);
+    }
+}
+
+#[doc(hidden)]
+pub fn _log(level: Level, target: &str, args: fmt::Arguments) {
+    emit(level, target, args, true);
+}
+
+#[doc(hidden)]
+pub fn _try_log(level: Level, target: &str, args: fmt::Arguments) {
+    emit(level, target, args, false);
+}
+
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {
+        $crate::log::_log($level, module_path!(), format_args!($($arg)+))
+    };
+}
+
+/// `log!` for interrupt and exception handlers, dropping the message instead of waiting for output.
+#[macro_export]
+macro_rules! try_log {
+    ($level:expr, $($arg:tt)+) => {
+        $crate::log::_try_log($level, module_path!(), format_args!($($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Error, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Trace, $($arg)+) };
+}
+
+#[cfg(all(test, not(target_os = "uefi")))]
+mod test {
+    use super::*;
+    use std::string::String;
+
+    #[test]
+    fn record() {
+        let mut plain = String::new();
+        write_record(
+            &mut plain,
+            None,
+            Level::Warn,
+            "moos::machine",
+            format_args!("{} MiB", 4),
+            false,
+        )
+        .unwrap();
+        assert_eq!(plain, "WARN  moos::machine: 4 MiB\n");
+
+        let mut colored = String::new();
+        write_record(
+            &mut colored,
+            None,
+            Level::Error,
+            "moos",
+            format_args!("oops"),
+            true,
+        )
+        .unwrap();
+        assert_eq!(colored, "\x1B[91mERROR\x1B[0m moos: oops\n");
+
+        let time = EfiTime::new(2024, 1, 1, 9, 5, 0)
+            .and_then(|time| time.with_nanosecond(12_345_678))
+            .unwrap();
+        let mut stamped = String::new();
+        write_record(
+            &mut stamped,
+            Some(&time),
+            Level::Info,
+            "moos",
+            format_args!("up"),
+            false,
+        )
+        .unwrap();
+        assert_eq!(stamped, "09:05:00.012 INFO  moos: up\n");
+    }
+
+    #[test]
+    fn clock() {
+        assert!(now().is_none());
+        let far = EfiTime::new(2263, 1, 1, 0, 0, 0).unwrap();
+        assert!(set_clock(&far, u64::MAX).is_err());
+        assert!(now().is_none());
+
+        let time = EfiTime::new(2024, 2, 29, 23, 59, 59)
+            .and_then(|time| time.with_time_zone(Some(9 * 60)))
+            .unwrap();
+        // The TSC barely advances at this frequency.
+        set_clock(&time, u64::MAX).unwrap();
+        let now = now().unwrap();
+        assert_eq!(now.to_unix_timestamp(), time.to_unix_timestamp());
+        assert_eq!((now.hour(), now.minute()), (14, 59));
+    }
+
+    #[test]
+    fn levels() {
+        assert!(Level::Error < Level::Trace);
+        assert_eq!(max_level(), Level::Info);
+        set_max_level(Level::Trace);
+        assert_eq!(max_level(), Level::Trace);
+        set_max_level(Level::Info);
+    }
+}
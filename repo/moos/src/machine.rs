@@ -0,0 +1,81 @@
+//! Description of the machine from the SMBIOS tables published by the firmware.
+
+use core::fmt::{self, Write};
+
+use smbios::{Smbios2EntryPoint, Smbios3EntryPoint, StructureTable};
+use uefi::{efi_configuration_table::guid, EfiSystemTable};
+
+const UNKNOWN: &str = "unknown";
+
+/// Finds the structure table, preferring the 64-bit entry point.
+pub fn structure_table(system_table: &EfiSystemTable) -> Option<StructureTable<'static>> {
+    let smbios3 = system_table
+        .find_configuration_table(&guid::SMBIOS3_TABLE_GUID)
+        .and_then(|address| {
+            unsafe { Smbios3EntryPoint::from_address(address.as_ptr() as usize) }.ok()
+        })
+        .map(|entry_point| unsafe { entry_point.structure_table() });
+
+    smbios3.or_else(|| {
+        let address = system_table.find_configuration_table(&guid::SMBIOS_TABLE_GUID)?;
+        let entry_point =
+            unsafe { Smbios2EntryPoint::from_address(address.as_ptr() as usize) }.ok()?;
+        Some(unsafe { entry_point.structure_table() })
+    })
+}
+
+/// Writes the machine, firmware, processors and DIMMs.
+pub fn print(system_table: &EfiSystemTable, writer: &mut impl Write) -> fmt::Result {
+    let Some(table) = structure_table(system_table) else {
+        return writeln!(writer, "no SMBIOS table");
+    };
+
+    if let Some(system) = table.system_information() {
+        writeln!(
+            writer,
+            "machine: {} {}",
+            system.manufacturer().unwrap_or(UNKNOWN),
+            system.product_name().unwrap_or(UNKNOWN)
+        )?;
+    }
+
+    if let Some(bios) = table.bios_information() {
+        writeln!(
+            writer,
+            "firmware: {} {} ({})",
+            bios.vendor().unwrap_or(UNKNOWN),
+            bios.version().unwrap_or(UNKNOWN),
+            bios.release_date().unwrap_or(UNKNOWN)
+        )?;
+    }
+
+    for processor in table.processors().filter(|p| p.is_populated()) {
+        writeln!(
+            writer,
+            "cpu: {} {}, {} cores, {} threads, {} MHz",
+            processor.socket_designation().unwrap_or(UNKNOWN),
+            processor.version().unwrap_or(UNKNOWN),
+            processor.core_count().unwrap_or(0),
+            processor.thread_count().unwrap_or(0),
+            processor.max_speed().unwrap_or(0)
+        )?;
+    }
+
+    for dimm in table.memory_devices() {
+        // Empty slots report a size of 0.
+        let Some(size) = dimm.size().filter(|&size| size != 0) else {
+            continue;
+        };
+        writeln!(
+            writer,
+            "dimm: {} {} MiB, {} MT/s, {} {}",
+            dimm.device_locator().unwrap_or(UNKNOWN),
+            size / (1024 * 1024),
+            dimm.configured_speed().or(dimm.speed()).unwrap_or(0),
+            dimm.manufacturer().unwrap_or(UNKNOWN),
+            dimm.part_number().unwrap_or(UNKNOWN)
+        )?;
+    }
+
+    Ok(())
+}
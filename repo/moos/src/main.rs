@@ -1,136 +1,243 @@
 #![no_std]
-#![no_main]
-#![feature(offset_of)]
-#![feature(extern_types)]
+// Host unit tests get the main of the standard test harness; in QEMU, tests start from efi_main.
+#![cfg_attr(any(not(test), target_os = "uefi"), no_main)]
+#![feature(custom_test_frameworks)]
+#![feature(panic_info_message)]
+#![cfg_attr(target_os = "uefi", test_runner(testing::runner))]
+#![cfg_attr(target_os = "uefi", reexport_test_harness_main = "test_main")]
+
+#[cfg(all(test, not(target_os = "uefi")))]
+extern crate std;
+
+mod apic;
+mod backtrace;
+mod console;
+mod cpu;
+mod exceptions;
+mod font;
+mod framebuffer;
+mod gdt;
+mod graphics;
+mod interrupts;
+mod log;
+mod machine;
+mod panic;
+mod pic;
+mod port;
+#[cfg(all(test, target_os = "uefi"))]
+mod qemu;
+mod serial;
+mod sync;
+#[cfg(all(test, target_os = "uefi"))]
+mod testing;
 
 use core::{
-    marker::{PhantomData, PhantomPinned},
-    mem::{offset_of, size_of},
-    ptr::null_mut,
+    fmt::Write,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
 };
 
-#[no_mangle]
-extern "efiapi" fn efi_main(_image_handle: EfiHandle, efi_system_table: &EfiSystemTable) {
-    let efi_graphics_output_protocol = locate_graphics_protocol(efi_system_table).unwrap();
-    let vram_addr = efi_graphics_output_protocol.mode.frame_buffer_base;
-    let vram_byte_size = efi_graphics_output_protocol.mode.frame_buffer_size;
-    let vram = unsafe {
-        core::slice::from_raw_parts_mut(vram_addr as *mut u32, vram_byte_size / size_of::<u32>())
-    };
+use console::KernelConsole;
+use framebuffer::Framebuffer;
 
-    for e in vram {
-        *e = 0xFFFFFF;
-    }
+use uefi::{
+    efi_boot_services::{EfiMemoryMap, EfiMemoryType, PoolBox},
+    efi_runtime_services::EfiTime,
+    profiler::{calibrate_tsc, BootProfiler, ClockSource},
+    protocol::{graphics::EfiGraphicsOutputProtocol, mp_services::EfiMpServicesProtocol},
+    EfiBootServices, EfiHandle, EfiResult, EfiStatus, EfiSystemTable,
+};
 
-    loop {
-        hlt();
+uefi::entry!(efi_main);
+
+fn efi_main(image_handle: EfiHandle, system_table: &'static EfiSystemTable) -> EfiStatus {
+    #[cfg(all(test, target_os = "uefi"))]
+    {
+        let _ = serial::init();
+        // Tests run on the machine as the kernel has it after boot: without boot services,
+        // on its own descriptor tables and interrupt controllers.
+        unsafe { exit_boot_services(image_handle, system_table.boot_services(), None) }.unwrap();
+        gdt::init();
+        interrupts::init();
+        apic::find_madt(system_table)
+            .and_then(|madt| apic::init(&madt))
+            .unwrap();
+        test_main();
     }
-}
-
-fn locate_graphics_protocol<'a>(
-    efi_system_table: &EfiSystemTable,
-) -> EfiResult<&'a EfiGraphicsOutputProtocol<'a>> {
-    let mut graphics_output_protocol = null_mut();
-    let status = (efi_system_table.boot_services.locate_protocol)(
-        &EFI_GRAPHICS_OUTPUT_PROTOCOL_GUID,
-        null_mut(),
-        &mut graphics_output_protocol as *mut *mut EfiGraphicsOutputProtocol as *mut *mut EfiVoid,
-    );
 
-    if status != EfiStatus::Success {
-        return Err("Failed to locate graphics output protocol");
+    if let Err(message) = boot(image_handle, system_table) {
+        let _ = writeln!(system_table.console_out(), "moos: {}", message);
+        return EfiStatus::ABORTED;
     }
 
-    Ok(unsafe { &*graphics_output_protocol })
-}
-
-#[panic_handler]
-fn panic(_info: &core::panic::PanicInfo) -> ! {
     loop {
         hlt();
     }
 }
 
-#[repr(C)]
-#[derive(Debug, Clone, Copy)]
-struct EfiVoid {
-    _data: (),
-    _marker: PhantomData<(*mut u8, PhantomPinned)>,
-}
-
-#[repr(C)]
-#[derive(Debug, Clone, Copy)]
-struct EfiHandle(*mut EfiVoid);
-
-#[repr(u64)]
-#[must_use]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum EfiStatus {
-    Success = 0,
-}
+/// Errors are returned only while boot services are still available to report them.
+fn boot(image_handle: EfiHandle, system_table: &'static EfiSystemTable) -> EfiResult<()> {
+    let serial = serial::init();
+    if cfg!(debug_assertions) {
+        log::set_max_level(log::Level::Debug);
+    }
+    let boot_services = system_table.boot_services();
+    let mut profiler = BootProfiler::new(boot_services)?;
+    let boot_time = start_log_clock(system_table, &profiler);
+
+    let graphics = profiler.measure("locate graphics output", || {
+        boot_services.locate_protocol::<EfiGraphicsOutputProtocol>()
+    })?;
+    let framebuffer = Framebuffer::from_gop(graphics)?;
+    let (width, height) = (framebuffer.width(), framebuffer.height());
+    profiler.measure("console", || console::init(framebuffer));
+    info!("moos on a {}x{} framebuffer", width, height);
+    if let Err(message) = serial {
+        warn!("No serial output: {}", message);
+    }
+    match boot_time {
+        Ok(time) => info!("Booted at {}", time),
+        Err(message) => warn!("No timestamps in the log: {}", message),
+    }
+    if cfg!(debug_assertions) {
+        if let Err(message) = uefi::crc32::self_check(boot_services) {
+            error!("CRC32 self-check: {}", message);
+        }
+    }
 
-#[repr(C)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct EfiGuid(pub u32, pub u16, pub u16, pub [u8; 8]);
+    profiler
+        .measure("machine information", || {
+            machine::print(system_table, &mut KernelConsole)
+        })
+        .map_err(|_| "Failed to print machine information")?;
+    if let Err(message) = profiler.measure("processors", || check_processors(boot_services)) {
+        warn!("Processors: {}", message);
+    }
 
-type LocateProtocol = extern "win64" fn(
-    protocol: *const EfiGuid,
-    registration: *const EfiVoid,
-    interface: *mut *mut EfiVoid,
-) -> EfiStatus;
+    let madt = apic::find_madt(system_table);
 
-type EfiResult<T> = core::result::Result<T, &'static str>;
+    // The timestamp protocol goes away with boot services, so measuring their exit needs the TSC.
+    let mut profiler = match profiler.use_tsc(boot_services) {
+        Ok(()) => Some(profiler),
+        Err(message) => {
+            warn!("Boot timings end before exiting boot services: {}", message);
+            kprint!("{}", profiler.timings());
+            None
+        }
+    };
 
-#[repr(C)]
-struct EfiBootServicesTable {
-    _reserved0: [u64; 40],
-    locate_protocol: LocateProtocol,
+    // The kernel takes over the processor from here, which ends the use of boot services.
+    let memory_map = unsafe { exit_boot_services(image_handle, boot_services, profiler.as_mut())? };
+    if let Some(profiler) = &profiler {
+        kprint!("{}", profiler.timings());
+    }
+    info!(
+        "Exited boot services with {} memory regions",
+        memory_map.len()
+    );
+    gdt::init();
+    interrupts::init();
+    match madt.and_then(|madt| apic::init(&madt)) {
+        Ok(()) => {
+            let mode = if apic::is_x2apic() { "x2APIC" } else { "xAPIC" };
+            info!("Interrupts through the local APIC in {} mode", mode);
+            cpu::enable_interrupts();
+        }
+        Err(message) => error!("No interrupts: {}", message),
+    }
+    Ok(())
 }
 
-const _: () = assert!(offset_of!(EfiBootServicesTable, locate_protocol) == 320);
-
-#[repr(C)]
-struct EfiSystemTable {
-    _reserved0: [u64; 12],
-    pub boot_services: &'static EfiBootServicesTable,
+/// Checks that every AP has the features of the BSP, which the kernel assumes of all processors.
+fn check_processors(boot_services: &EfiBootServices) -> EfiResult<()> {
+    let mp_services = boot_services.locate_protocol::<EfiMpServicesProtocol>()?;
+    let (processors, enabled) = mp_services.get_number_of_processors()?;
+    info!("{} processors, {} enabled", processors, enabled);
+
+    let features = cpu::features();
+    // Only CPUID and atomics on the APs, which must not call boot services.
+    let differ = AtomicBool::new(false);
+    mp_services.startup_all_aps(false, Some(Duration::from_secs(1)), &|_| {
+        if cpu::features() != features {
+            differ.store(true, Ordering::Relaxed);
+        }
+    })?;
+    if differ.load(Ordering::Relaxed) {
+        return Err("APs differ in CPUID features from the BSP");
+    }
+    Ok(())
 }
 
-const EFI_GRAPHICS_OUTPUT_PROTOCOL_GUID: EfiGuid = EfiGuid(
-    0x9042a9de,
-    0x23dc,
-    0x4a38,
-    [0x96, 0xfb, 0x7a, 0xde, 0xd0, 0x80, 0x51, 0x6a],
-);
-
-#[repr(C)]
-#[derive(Debug)]
-struct EfiGraphicsOutputProtocol<'a> {
-    reserved: [u64; 3],
-    pub mode: &'a EfiGraphicsOutputProtocolMode<'a>,
+/// Reads the wall clock for the log, which then advances it by the TSC.
+/// The TSC frequency comes from `profiler` if it counts on the TSC, otherwise from a calibration.
+fn start_log_clock(system_table: &EfiSystemTable, profiler: &BootProfiler) -> EfiResult<EfiTime> {
+    let time = system_table.runtime_services().get_time()?;
+    let frequency = match profiler.clock_source() {
+        ClockSource::Tsc => profiler.frequency(),
+        ClockSource::Timestamp => calibrate_tsc(system_table.boot_services())?,
+    };
+    log::set_clock(&time, frequency)?;
+    Ok(time)
 }
 
-#[repr(C)]
-#[derive(Debug)]
-struct EfiGraphicsOutputProtocolMode<'a> {
-    pub max_mod: u32,
-    pub mode: u32,
-    pub info: &'a EfiGraphicsOutputProtocolPixelInfo,
-    pub size_of_info: u64,
-    pub frame_buffer_base: usize,
-    pub frame_buffer_size: usize,
+/// Gets the memory map into a buffer that outlives boot services, then exits them.
+/// The memory map changes when the buffer is allocated, so its size is checked again on retry.
+///
+/// # Safety
+/// Boot services and boot services memory must not be used once this succeeds.
+unsafe fn exit_boot_services(
+    image_handle: EfiHandle,
+    boot_services: &EfiBootServices,
+    mut profiler: Option<&mut BootProfiler>,
+) -> EfiResult<EfiMemoryMap<'static>> {
+    const ATTEMPTS: usize = 3;
+
+    for _ in 0..ATTEMPTS {
+        let size = boot_services.memory_map_size()?;
+        let pool = PoolBox::new_slice(
+            boot_services,
+            EfiMemoryType::EfiLoaderData,
+            size.div_ceil(8),
+            0u64,
+        )?;
+        let buffer =
+            unsafe { core::slice::from_raw_parts_mut(pool.as_ptr().as_ptr().cast::<u8>(), size) };
+        let memory_map = match measure(&mut profiler, "get memory map", || {
+            boot_services.get_memory_map(buffer)
+        }) {
+            Ok(memory_map) => memory_map,
+            // Another allocation may have grown the map since its size was taken.
+            Err(_) => continue,
+        };
+        let exited = measure(&mut profiler, "exit boot services", || unsafe {
+            boot_services.exit_boot_services(image_handle, memory_map.map_key())
+        });
+        if exited.is_ok() {
+            // EfiLoaderData stays with the kernel, and FreePool is gone.
+            pool.into_raw();
+            return Ok(memory_map);
+        }
+    }
+    Err("Failed to exit boot services")
 }
 
-#[repr(C)]
-#[derive(Debug)]
-struct EfiGraphicsOutputProtocolPixelInfo {
-    version: u32,
-    pub horizontal_resolution: u32,
-    pub vertical_resolution: u32,
-    _padding0: [u32; 5],
-    pub pixels_per_scal_line: u32,
+/// [`BootProfiler::measure`] if there is a profiler.
+fn measure<R>(
+    profiler: &mut Option<&mut BootProfiler>,
+    name: &'static str,
+    f: impl FnOnce() -> R,
+) -> R {
+    match profiler {
+        Some(profiler) => profiler.measure(name, f),
+        None => f(),
+    }
 }
 
-const _: () = assert!(size_of::<EfiGraphicsOutputProtocolPixelInfo>() == 36);
+#[cfg(any(not(test), target_os = "uefi"))]
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    panic::handle(info)
+}
 
 pub fn hlt() {
     unsafe { core::arch::asm!("hlt") }
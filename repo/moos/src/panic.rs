@@ -0,0 +1,123 @@
+//! What the kernel shows before halting on a panic or a fatal CPU exception.
+
+use core::{
+    arch::asm,
+    fmt::{self, Write},
+    panic::PanicInfo,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+    backtrace::{self, Frames, Image},
+    console, cpu, serial,
+};
+
+/// White on red, then clear the screen and go to the top left.
+const PANIC_SCREEN: &str = "\x1B[97;41m\x1B[2J\x1B[H";
+
+static PANICKING: AtomicBool = AtomicBool::new(false);
+
+/// Registers at the time of the panic. RIP is in the panic handler rather than at the panic site.
+#[derive(Debug, Clone, Copy)]
+struct Registers {
+    rip: u64,
+    rsp: u64,
+    cr2: u64,
+    cr3: u64,
+}
+
+impl Registers {
+    #[inline(always)]
+    fn capture() -> Self {
+        let (rip, rsp): (u64, u64);
+        unsafe {
+            asm!("lea {}, [rip]", out(reg) rip, options(nomem, nostack, preserves_flags));
+            asm!("mov {}, rsp", out(reg) rsp, options(nomem, nostack, preserves_flags));
+        }
+        Self {
+            rip,
+            rsp,
+            cr2: cpu::cr2(),
+            cr3: cpu::cr3(),
+        }
+    }
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RIP={:#018x} RSP={:#018x}\nCR2={:#018x} CR3={:#018x}",
+            self.rip, self.rsp, self.cr2, self.cr3
+        )
+    }
+}
+
+/// Header of the report of a panic.
+struct PanicReport<'a> {
+    info: &'a PanicInfo<'a>,
+    registers: Registers,
+}
+
+impl fmt::Display for PanicReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KERNEL PANIC")?;
+        if let Some(location) = self.info.location() {
+            write!(f, " at {}", location)?;
+        }
+        if let Some(message) = self.info.message() {
+            write!(f, "\n{}", message)?;
+        }
+        write!(f, "\n\n{}", self.registers)
+    }
+}
+
+fn write_report(writer: &mut impl Write, report: &dyn fmt::Display, frames: Frames) -> fmt::Result {
+    writeln!(writer, "{}\n", report)?;
+    match Image::current() {
+        Ok(image) => backtrace::write(writer, &image, frames),
+        Err(message) => writeln!(writer, "No backtrace: {}", message),
+    }
+}
+
+/// Reports `info` and a backtrace, then halts.
+#[cfg_attr(not(target_os = "uefi"), allow(dead_code))]
+pub fn handle(info: &PanicInfo) -> ! {
+    cpu::disable_interrupts();
+    let report = PanicReport {
+        info,
+        registers: Registers::capture(),
+    };
+    stop(&report, Frames::current())
+}
+
+/// Shows `report` and a backtrace of `frames` on the serial port and on a red console, then halts.
+/// Under the QEMU test harness, fails the running test instead.
+pub fn stop(report: &dyn fmt::Display, frames: Frames) -> ! {
+    cpu::disable_interrupts();
+
+    // A panic while reporting a panic would recurse, so the second one only stops the machine.
+    if !PANICKING.swap(true, Ordering::Relaxed) {
+        // The panicking code may have been writing, and will never release its locks.
+        unsafe {
+            serial::force_unlock();
+            console::force_unlock();
+        }
+
+        #[cfg(all(test, target_os = "uefi"))]
+        crate::testing::report_failure();
+
+        let _ = writeln!(serial::KernelSerial);
+        let _ = write_report(&mut serial::KernelSerial, report, frames.clone());
+        let _ = console::KernelConsole.write_str(PANIC_SCREEN);
+        let _ = write_report(&mut console::KernelConsole, report, frames);
+    }
+
+    #[cfg(all(test, target_os = "uefi"))]
+    crate::testing::exit_failed();
+
+    #[cfg(not(all(test, target_os = "uefi")))]
+    loop {
+        crate::hlt();
+    }
+}
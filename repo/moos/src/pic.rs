@@ -0,0 +1,39 @@
+//! Legacy 8259 interrupt controllers, which the IO APIC replaces.
+//!
+//! REF: https://wiki.osdev.org/8259_PIC
+
+use crate::port::outb;
+
+const PRIMARY_COMMAND: u16 = 0x20;
+const PRIMARY_DATA: u16 = 0x21;
+const SECONDARY_COMMAND: u16 = 0xA0;
+const SECONDARY_DATA: u16 = 0xA1;
+
+/// ICW1: start initialization, with an ICW4 to follow.
+const INIT: u8 = 0x11;
+/// ICW4: 8086 mode.
+const MODE_8086: u8 = 0x01;
+
+/// First vectors of the two controllers, clear of the CPU exceptions.
+pub const PRIMARY_VECTOR: u8 = 0x20;
+pub const SECONDARY_VECTOR: u8 = 0x28;
+
+/// Remaps both controllers above the CPU exceptions, then masks all their IRQs.
+///
+/// Remapping still matters while masked, since a spurious IRQ 7 or 15 can arrive anyway.
+pub fn disable() {
+    unsafe {
+        outb(PRIMARY_COMMAND, INIT);
+        outb(SECONDARY_COMMAND, INIT);
+        outb(PRIMARY_DATA, PRIMARY_VECTOR);
+        outb(SECONDARY_DATA, SECONDARY_VECTOR);
+        // The secondary controller is cascaded on IRQ 2 of the primary one.
+        outb(PRIMARY_DATA, 1 << 2);
+        outb(SECONDARY_DATA, 2);
+        outb(PRIMARY_DATA, MODE_8086);
+        outb(SECONDARY_DATA, MODE_8086);
+
+        outb(PRIMARY_DATA, 0xFF);
+        outb(SECONDARY_DATA, 0xFF);
+    }
+}
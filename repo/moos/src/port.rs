@@ -0,0 +1,17 @@
+//! x86 I/O port access.
+
+use core::arch::asm;
+
+/// # Safety
+/// Reading some ports has side effects on the device behind them.
+pub unsafe fn inb(port: u16) -> u8 {
+    let value;
+    asm!("in al, dx", out("al") value, in("dx") port, options(nomem, nostack, preserves_flags));
+    value
+}
+
+/// # Safety
+/// Writing to a port can reconfigure hardware the rest of the kernel relies on.
+pub unsafe fn outb(port: u16, value: u8) {
+    asm!("out dx, al", in("dx") port, in("al") value, options(nomem, nostack, preserves_flags));
+}
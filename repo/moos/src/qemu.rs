@@ -0,0 +1,23 @@
+//! Exiting QEMU through the isa-debug-exit device that `scripts/launch_qemu.sh` attaches.
+
+use crate::{hlt, port::outb};
+
+const ISA_DEBUG_EXIT: u16 = 0xF4;
+
+/// QEMU exits with status `(code << 1) | 1`, so that it cannot be mistaken for QEMU's own 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ExitCode {
+    /// Status 33.
+    Success = 0x10,
+    /// Status 35.
+    Failure = 0x11,
+}
+
+/// Halts forever when not running in QEMU.
+pub fn exit(code: ExitCode) -> ! {
+    unsafe { outb(ISA_DEBUG_EXIT, code as u8) };
+    loop {
+        hlt();
+    }
+}
@@ -0,0 +1,203 @@
+//! 16550 UART, as emulated by QEMU on COM1 and found on most PC chipsets.
+//!
+//! REF: https://wiki.osdev.org/Serial_Ports
+
+use core::fmt::{self, Write};
+
+use crate::{
+    port::{inb, outb},
+    sync::SpinLock,
+};
+
+pub const COM1: u16 = 0x3F8;
+
+/// Frequency of the UART clock divided by 16, the baud rate of divisor 1.
+const MAX_BAUD_RATE: u32 = 115_200;
+/// Bytes accepted at once when the transmit FIFO is empty.
+const FIFO_SIZE: usize = 16;
+
+// Register offsets from the base port. With DLAB set in LCR, 0 and 1 hold the divisor instead.
+const DATA: u16 = 0;
+const INTERRUPT_ENABLE: u16 = 1;
+const DIVISOR_LOW: u16 = 0;
+const DIVISOR_HIGH: u16 = 1;
+const FIFO_CONTROL: u16 = 2;
+const LINE_CONTROL: u16 = 3;
+const MODEM_CONTROL: u16 = 4;
+const LINE_STATUS: u16 = 5;
+
+const LINE_CONTROL_8N1: u8 = 0x03;
+const LINE_CONTROL_DLAB: u8 = 0x80;
+/// Enable and clear both FIFOs, interrupting at 14 received bytes.
+const FIFO_CONTROL_ENABLE: u8 = 0xC7;
+/// DTR, RTS and OUT2.
+const MODEM_CONTROL_NORMAL: u8 = 0x0B;
+/// RTS, OUT1, OUT2 and loopback.
+const MODEM_CONTROL_LOOPBACK: u8 = 0x1E;
+const LINE_STATUS_DATA_READY: u8 = 0x01;
+const LINE_STATUS_TRANSMIT_EMPTY: u8 = 0x20;
+
+/// Serial port of the kernel, set up by `init`.
+static SERIAL: SpinLock<Option<SerialPort>> = SpinLock::new(None);
+
+#[derive(Debug)]
+pub struct SerialPort {
+    base: u16,
+}
+
+impl SerialPort {
+    /// # Safety
+    /// A 16550 compatible UART, or nothing, must be at `base`, and nothing else may use it.
+    pub const unsafe fn new(base: u16) -> Self {
+        Self { base }
+    }
+
+    /// Configures 8N1 at `baud_rate` with FIFOs and no interrupts,
+    /// and checks in loopback mode that the UART echoes a byte.
+    pub fn init(&mut self, baud_rate: u32) -> Result<(), &'static str> {
+        self.write_register(INTERRUPT_ENABLE, 0x00);
+        self.set_baud_rate(baud_rate)?;
+        self.write_register(FIFO_CONTROL, FIFO_CONTROL_ENABLE);
+
+        const PROBE: u8 = 0xAE;
+        self.write_register(MODEM_CONTROL, MODEM_CONTROL_LOOPBACK);
+        self.write_register(DATA, PROBE);
+        if self.read_register(DATA) != PROBE {
+            return Err("Failed to find a UART behind the serial port");
+        }
+        self.write_register(MODEM_CONTROL, MODEM_CONTROL_NORMAL);
+        Ok(())
+    }
+
+    /// Sets the speed and the 8N1 frame format. `baud_rate` must divide 115200.
+    pub fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), &'static str> {
+        if baud_rate == 0 || MAX_BAUD_RATE % baud_rate != 0 {
+            return Err("Unsupported baud rate");
+        }
+        let [low, high] = ((MAX_BAUD_RATE / baud_rate) as u16).to_le_bytes();
+
+        self.write_register(LINE_CONTROL, LINE_CONTROL_DLAB);
+        self.write_register(DIVISOR_LOW, low);
+        self.write_register(DIVISOR_HIGH, high);
+        self.write_register(LINE_CONTROL, LINE_CONTROL_8N1);
+        Ok(())
+    }
+
+    /// Sends `bytes`, waiting for the transmit FIFO to drain before each batch of 16.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        for batch in bytes.chunks(FIFO_SIZE) {
+            while self.read_register(LINE_STATUS) & LINE_STATUS_TRANSMIT_EMPTY == 0 {
+                core::hint::spin_loop();
+            }
+            for &byte in batch {
+                self.write_register(DATA, byte);
+            }
+        }
+    }
+
+    /// Returns `None` if no byte has been received.
+    #[allow(dead_code)]
+    pub fn read_byte(&mut self) -> Option<u8> {
+        (self.read_register(LINE_STATUS) & LINE_STATUS_DATA_READY != 0)
+            .then(|| self.read_register(DATA))
+    }
+
+    fn read_register(&self, offset: u16) -> u8 {
+        unsafe { inb(self.base + offset) }
+    }
+
+    fn write_register(&mut self, offset: u16, value: u8) {
+        unsafe { outb(self.base + offset, value) }
+    }
+}
+
+impl Write for SerialPort {
+    /// Terminals expect CRLF, so "\n" is sent as "\r\n".
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for (i, line) in s.split('\n').enumerate() {
+            if i > 0 {
+                self.write_bytes(b"\r\n");
+            }
+            self.write_bytes(line.as_bytes());
+        }
+        Ok(())
+    }
+}
+
+/// Sets up COM1 at 115200 baud as the serial port of the kernel.
+pub fn init() -> Result<(), &'static str> {
+    let mut port = unsafe { SerialPort::new(COM1) };
+    port.init(MAX_BAUD_RATE)?;
+    *SERIAL.lock() = Some(port);
+    Ok(())
+}
+
+/// Releases the serial port of the kernel, even if someone is writing to it.
+///
+/// # Safety
+/// Whoever holds it must never run again, as after a panic.
+pub unsafe fn force_unlock() {
+    SERIAL.force_unlock();
+}
+
+/// Runs `f` on the serial port of the kernel, if there is one, holding it so that the output stays together.
+pub fn write_all(f: impl FnOnce(&mut dyn Write) -> fmt::Result) -> fmt::Result {
+    SERIAL.lock().as_mut().map_or(Ok(()), |port| f(port))
+}
+
+/// Like [`write_all`], but fails instead of waiting while the port is held.
+/// Interrupt handlers must use this, since the code they interrupted may hold it.
+pub fn try_write_all(f: impl FnOnce(&mut dyn Write) -> fmt::Result) -> fmt::Result {
+    SERIAL
+        .try_lock()
+        .ok_or(fmt::Error)?
+        .as_mut()
+        .map_or(Ok(()), |port| f(port))
+}
+
+/// Writes to the serial port of the kernel, if there is one.
+pub struct KernelSerial;
+
+impl Write for KernelSerial {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match SERIAL.lock().as_mut() {
+            Some(port) => port.write_str(s),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(test, not(target_os = "uefi")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn try_write_all_while_held() {
+        let held = SERIAL.lock();
+        assert!(try_write_all(|_| Ok(())).is_err());
+        drop(held);
+        // Without a port, the output goes nowhere.
+        assert!(try_write_all(|_| Err(fmt::Error)).is_ok());
+    }
+}
+
+#[cfg(all(test, target_os = "uefi"))]
+mod qemu_test {
+    use super::*;
+
+    /// QEMU only emulates the UARTs given with -serial, so COM2 is empty.
+    #[test_case]
+    fn missing_uart() {
+        let mut com2 = unsafe { SerialPort::new(0x2F8) };
+        assert!(com2.init(MAX_BAUD_RATE).is_err());
+    }
+
+    #[test_case]
+    fn baud_rates() {
+        let mut com2 = unsafe { SerialPort::new(0x2F8) };
+        assert!(com2.set_baud_rate(9600).is_ok());
+        assert!(com2.set_baud_rate(0).is_err());
+        assert!(com2.set_baud_rate(7).is_err());
+        assert!(com2.set_baud_rate(230_400).is_err());
+    }
+}
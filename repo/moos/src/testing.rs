@@ -0,0 +1,43 @@
+//! Runner of the `#[test_case]` functions, which run inside QEMU with `cargo test -p moos`.
+//!
+//! Results go to the serial port and the status of QEMU tells whether all tests passed.
+//! Unit tests that do not need the machine stay `#[test]` and run on the host with `cargo test-host -p moos`.
+
+use core::{any::type_name, fmt::Write};
+
+use crate::{
+    qemu::{self, ExitCode},
+    serial::KernelSerial,
+};
+
+pub trait Testable {
+    fn run(&self);
+}
+
+impl<T: Fn()> Testable for T {
+    fn run(&self) {
+        let _ = write!(KernelSerial, "test {} ... ", type_name::<T>());
+        self();
+        let _ = writeln!(KernelSerial, "ok");
+    }
+}
+
+pub fn runner(tests: &[&dyn Testable]) {
+    let _ = writeln!(KernelSerial, "\nrunning {} tests", tests.len());
+    for test in tests {
+        test.run();
+    }
+    let _ = writeln!(KernelSerial, "\ntest result: ok. {} passed", tests.len());
+    qemu::exit(ExitCode::Success);
+}
+
+/// Marks the running test as failed, before the panic handler prints why.
+pub fn report_failure() {
+    let _ = writeln!(KernelSerial, "FAILED");
+}
+
+/// Stops after a failure. Later tests do not run, since a panic may leave the machine in any state.
+pub fn exit_failed() -> ! {
+    let _ = writeln!(KernelSerial, "\ntest result: FAILED");
+    qemu::exit(ExitCode::Failure);
+}
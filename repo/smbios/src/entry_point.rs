@@ -0,0 +1,269 @@
+//! REF: DMTF DSP0134 3.7.0, 5.2 Table convention
+
+use crate::{structure::StructureTable, SmbiosResult};
+
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |sum, b| sum.wrapping_add(*b))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+/// 32-bit entry point structure, published with SMBIOS_TABLE_GUID.
+#[derive(Debug, Clone, Copy)]
+pub struct Smbios2EntryPoint<'a> {
+    bytes: &'a [u8],
+}
+
+impl Smbios2EntryPoint<'static> {
+    /// # Safety
+    /// `address` must point to the entry point structure.
+    pub unsafe fn from_address(address: usize) -> SmbiosResult<Self> {
+        let length = *(address as *const u8).add(5) as usize;
+        Self::new(core::slice::from_raw_parts(address as *const u8, length))
+    }
+
+    /// # Safety
+    /// The structure table must be mapped at its physical address.
+    pub unsafe fn structure_table(&self) -> StructureTable<'static> {
+        let bytes = core::slice::from_raw_parts(
+            self.table_address() as usize as *const u8,
+            self.table_length() as usize,
+        );
+        StructureTable::new(bytes)
+    }
+}
+
+impl<'a> Smbios2EntryPoint<'a> {
+    pub const ANCHOR: &'static [u8; 4] = b"_SM_";
+    pub const INTERMEDIATE_ANCHOR: &'static [u8; 5] = b"_DMI_";
+
+    pub fn new(bytes: &'a [u8]) -> SmbiosResult<Self> {
+        if bytes.len() < 0x1F || &bytes[0..4] != Self::ANCHOR {
+            return Err("Invalid SMBIOS entry point anchor");
+        }
+        let length = bytes[5] as usize;
+        if length < 0x1F || bytes.len() < length {
+            return Err("Invalid SMBIOS entry point length");
+        }
+        let bytes = &bytes[..length];
+        if checksum(bytes) != 0 {
+            return Err("Invalid SMBIOS entry point checksum");
+        }
+        if &bytes[0x10..0x15] != Self::INTERMEDIATE_ANCHOR || checksum(&bytes[0x10..0x1F]) != 0 {
+            return Err("Invalid SMBIOS intermediate entry point");
+        }
+        Ok(Self { bytes })
+    }
+
+    pub fn version(&self) -> (u8, u8) {
+        (self.bytes[6], self.bytes[7])
+    }
+
+    pub fn table_length(&self) -> u16 {
+        read_u16(self.bytes, 0x16)
+    }
+
+    pub fn table_address(&self) -> u32 {
+        read_u32(self.bytes, 0x18)
+    }
+
+    pub fn number_of_structures(&self) -> u16 {
+        read_u16(self.bytes, 0x1C)
+    }
+}
+
+/// 64-bit entry point structure, published with SMBIOS3_TABLE_GUID.
+#[derive(Debug, Clone, Copy)]
+pub struct Smbios3EntryPoint<'a> {
+    bytes: &'a [u8],
+}
+
+impl Smbios3EntryPoint<'static> {
+    /// # Safety
+    /// `address` must point to the entry point structure.
+    pub unsafe fn from_address(address: usize) -> SmbiosResult<Self> {
+        let length = *(address as *const u8).add(6) as usize;
+        Self::new(core::slice::from_raw_parts(address as *const u8, length))
+    }
+
+    /// # Safety
+    /// The structure table must be mapped at its physical address.
+    pub unsafe fn structure_table(&self) -> StructureTable<'static> {
+        let bytes = core::slice::from_raw_parts(
+            self.table_address() as usize as *const u8,
+            self.table_maximum_size() as usize,
+        );
+        StructureTable::new(bytes)
+    }
+}
+
+impl<'a> Smbios3EntryPoint<'a> {
+    pub const ANCHOR: &'static [u8; 5] = b"_SM3_";
+
+    pub fn new(bytes: &'a [u8]) -> SmbiosResult<Self> {
+        if bytes.len() < 0x18 || &bytes[0..5] != Self::ANCHOR {
+            return Err("Invalid SMBIOS3 entry point anchor");
+        }
+        let length = bytes[6] as usize;
+        if length < 0x18 || bytes.len() < length {
+            return Err("Invalid SMBIOS3 entry point length");
+        }
+        let bytes = &bytes[..length];
+        if checksum(bytes) != 0 {
+            return Err("Invalid SMBIOS3 entry point checksum");
+        }
+        Ok(Self { bytes })
+    }
+
+    pub fn version(&self) -> (u8, u8, u8) {
+        (self.bytes[7], self.bytes[8], self.bytes[9])
+    }
+
+    /// Upper bound of the structure table size. The table ends with the End-of-Table structure.
+    pub fn table_maximum_size(&self) -> u32 {
+        read_u32(self.bytes, 0x0C)
+    }
+
+    pub fn table_address(&self) -> u64 {
+        read_u64(self.bytes, 0x10)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SAMPLE_TABLE: &[u8] = include_bytes!("../testdata/sample_table.bin");
+
+    /// 64-bit entry point for SMBIOS 3.0.0, as QEMU publishes it.
+    fn smbios3(table_address: u64, table_maximum_size: u32) -> [u8; 0x18] {
+        let mut bytes = [0; 0x18];
+        bytes[0..5].copy_from_slice(Smbios3EntryPoint::ANCHOR);
+        bytes[6] = 0x18;
+        bytes[7..10].copy_from_slice(&[3, 0, 0]);
+        // Entry point revision 3.0.
+        bytes[10] = 1;
+        bytes[0x0C..0x10].copy_from_slice(&table_maximum_size.to_le_bytes());
+        bytes[0x10..0x18].copy_from_slice(&table_address.to_le_bytes());
+        bytes[5] = 0u8.wrapping_sub(checksum(&bytes));
+        bytes
+    }
+
+    /// 32-bit entry point for SMBIOS 2.8.
+    fn smbios2() -> [u8; 0x1F] {
+        let mut bytes = [0; 0x1F];
+        bytes[0..4].copy_from_slice(Smbios2EntryPoint::ANCHOR);
+        bytes[5] = 0x1F;
+        bytes[6..8].copy_from_slice(&[2, 8]);
+        bytes[8..10].copy_from_slice(&0x4Au16.to_le_bytes());
+        bytes[0x10..0x15].copy_from_slice(Smbios2EntryPoint::INTERMEDIATE_ANCHOR);
+        bytes[0x16..0x18].copy_from_slice(&0x100u16.to_le_bytes());
+        bytes[0x18..0x1C].copy_from_slice(&0x7FF0_0000u32.to_le_bytes());
+        bytes[0x1C..0x1E].copy_from_slice(&5u16.to_le_bytes());
+        bytes[0x1E] = 0x28;
+        bytes[0x15] = 0u8.wrapping_sub(checksum(&bytes[0x10..0x1F]));
+        bytes[4] = 0u8.wrapping_sub(checksum(&bytes));
+        bytes
+    }
+
+    #[test]
+    fn smbios3_entry_point() {
+        let bytes = smbios3(0x7FF0_0000, 0x100);
+        let entry_point = Smbios3EntryPoint::new(&bytes).unwrap();
+        assert_eq!(entry_point.version(), (3, 0, 0));
+        assert_eq!(entry_point.table_maximum_size(), 0x100);
+        assert_eq!(entry_point.table_address(), 0x7FF0_0000);
+
+        let mut bad = bytes;
+        bad[4] = b'X';
+        assert_eq!(
+            Smbios3EntryPoint::new(&bad).err(),
+            Some("Invalid SMBIOS3 entry point anchor")
+        );
+        assert_eq!(
+            Smbios3EntryPoint::new(&bytes[..0x10]).err(),
+            Some("Invalid SMBIOS3 entry point anchor")
+        );
+        let mut bad = bytes;
+        bad[6] = 0x10;
+        assert_eq!(
+            Smbios3EntryPoint::new(&bad).err(),
+            Some("Invalid SMBIOS3 entry point length")
+        );
+        let mut bad = bytes;
+        bad[0x10] ^= 1;
+        assert_eq!(
+            Smbios3EntryPoint::new(&bad).err(),
+            Some("Invalid SMBIOS3 entry point checksum")
+        );
+    }
+
+    #[test]
+    fn smbios2_entry_point() {
+        let bytes = smbios2();
+        let entry_point = Smbios2EntryPoint::new(&bytes).unwrap();
+        assert_eq!(entry_point.version(), (2, 8));
+        assert_eq!(entry_point.table_length(), 0x100);
+        assert_eq!(entry_point.table_address(), 0x7FF0_0000);
+        assert_eq!(entry_point.number_of_structures(), 5);
+
+        let mut bad = bytes;
+        bad[1] = b'X';
+        assert_eq!(
+            Smbios2EntryPoint::new(&bad).err(),
+            Some("Invalid SMBIOS entry point anchor")
+        );
+        let mut bad = bytes;
+        bad[5] = 0x1E;
+        assert_eq!(
+            Smbios2EntryPoint::new(&bad).err(),
+            Some("Invalid SMBIOS entry point length")
+        );
+        let mut bad = bytes;
+        bad[0x1C] ^= 1;
+        assert_eq!(
+            Smbios2EntryPoint::new(&bad).err(),
+            Some("Invalid SMBIOS entry point checksum")
+        );
+
+        // Both checksums are valid, but the intermediate anchor is not.
+        let mut bad = bytes;
+        bad[0x10] = b'X';
+        bad[0x15] = bad[0x15].wrapping_add(b'_' - b'X');
+        assert_eq!(
+            Smbios2EntryPoint::new(&bad).err(),
+            Some("Invalid SMBIOS intermediate entry point")
+        );
+        // The intermediate checksum fails while the whole structure still sums to zero.
+        let mut bad = bytes;
+        bad[0x15] = bad[0x15].wrapping_add(1);
+        bad[0x0A] = bad[0x0A].wrapping_sub(1);
+        assert_eq!(
+            Smbios2EntryPoint::new(&bad).err(),
+            Some("Invalid SMBIOS intermediate entry point")
+        );
+    }
+
+    #[test]
+    fn structure_table_from_entry_point() {
+        let bytes = smbios3(SAMPLE_TABLE.as_ptr() as u64, SAMPLE_TABLE.len() as u32);
+        let entry_point =
+            unsafe { Smbios3EntryPoint::from_address(bytes.as_ptr() as usize) }.unwrap();
+        let table = unsafe { entry_point.structure_table() };
+        assert_eq!(table.structures().count(), 5);
+        assert_eq!(
+            table.system_information().unwrap().manufacturer(),
+            Some("QEMU")
+        );
+    }
+}
@@ -0,0 +1,11 @@
+#![no_std]
+
+pub mod entry_point;
+pub mod structure;
+pub mod types;
+
+pub use entry_point::{Smbios2EntryPoint, Smbios3EntryPoint};
+pub use structure::{Structure, StructureTable};
+pub use types::{BiosInformation, MemoryDevice, ProcessorInformation, SystemInformation};
+
+pub type SmbiosResult<T> = core::result::Result<T, &'static str>;
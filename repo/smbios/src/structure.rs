@@ -0,0 +1,126 @@
+//! REF: DMTF DSP0134 3.7.0, 6.1 Structure standards
+
+/// Structure records following an entry point.
+#[derive(Debug, Clone, Copy)]
+pub struct StructureTable<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> StructureTable<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub fn structures(&self) -> Structures<'a> {
+        Structures { bytes: self.bytes }
+    }
+
+    /// Structures of `structure_type`, e.g. 17 for Memory Device.
+    pub fn structures_of_type(&self, structure_type: u8) -> impl Iterator<Item = Structure<'a>> {
+        self.structures()
+            .filter(move |structure| structure.structure_type() == structure_type)
+    }
+
+    pub fn find_by_handle(&self, handle: u16) -> Option<Structure<'a>> {
+        self.structures()
+            .find(|structure| structure.handle() == handle)
+    }
+}
+
+/// One structure record: the formatted area followed by its string set.
+#[derive(Debug, Clone, Copy)]
+pub struct Structure<'a> {
+    formatted: &'a [u8],
+    strings: &'a [u8],
+}
+
+impl<'a> Structure<'a> {
+    pub const END_OF_TABLE: u8 = 127;
+
+    pub fn structure_type(&self) -> u8 {
+        self.formatted[0]
+    }
+
+    pub fn length(&self) -> u8 {
+        self.formatted[1]
+    }
+
+    pub fn handle(&self) -> u16 {
+        u16::from_le_bytes([self.formatted[2], self.formatted[3]])
+    }
+
+    /// Formatted area including the 4-byte header.
+    pub fn formatted(&self) -> &'a [u8] {
+        self.formatted
+    }
+
+    pub fn byte(&self, offset: usize) -> Option<u8> {
+        self.formatted.get(offset).copied()
+    }
+
+    pub fn word(&self, offset: usize) -> Option<u16> {
+        Some(u16::from_le_bytes(
+            self.formatted.get(offset..offset + 2)?.try_into().ok()?,
+        ))
+    }
+
+    pub fn dword(&self, offset: usize) -> Option<u32> {
+        Some(u32::from_le_bytes(
+            self.formatted.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    }
+
+    pub fn qword(&self, offset: usize) -> Option<u64> {
+        Some(u64::from_le_bytes(
+            self.formatted.get(offset..offset + 8)?.try_into().ok()?,
+        ))
+    }
+
+    /// String referenced by a 1-based string number. 0 means no string.
+    pub fn string(&self, number: u8) -> Option<&'a str> {
+        if number == 0 {
+            return None;
+        }
+        let string = self.strings.split(|b| *b == 0).nth(number as usize - 1)?;
+        core::str::from_utf8(string).ok()
+    }
+
+    /// String referenced by the string number stored at `offset` of the formatted area.
+    pub fn string_at(&self, offset: usize) -> Option<&'a str> {
+        self.string(self.byte(offset)?)
+    }
+}
+
+pub struct Structures<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for Structures<'a> {
+    type Item = Structure<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let length = *self.bytes.get(1)? as usize;
+        if length < 4 || self.bytes.len() < length {
+            self.bytes = &[];
+            return None;
+        }
+
+        let formatted = &self.bytes[..length];
+        let rest = &self.bytes[length..];
+
+        // The string set is terminated by two null bytes, including when it is empty.
+        let strings_end = rest.windows(2).position(|w| w == [0, 0]);
+        let Some(strings_end) = strings_end else {
+            self.bytes = &[];
+            return None;
+        };
+        let strings = &rest[..strings_end];
+        self.bytes = &rest[strings_end + 2..];
+
+        let structure = Structure { formatted, strings };
+        if structure.structure_type() == Structure::END_OF_TABLE {
+            self.bytes = &[];
+        }
+        Some(structure)
+    }
+}
@@ -0,0 +1,283 @@
+//! Typed views of the structures moos cares about.
+//! REF: DMTF DSP0134 3.7.0, 7 Structure definitions
+
+use crate::{SmbiosResult, Structure, StructureTable};
+
+fn expect_type(structure: &Structure<'_>, structure_type: u8) -> SmbiosResult<()> {
+    if structure.structure_type() != structure_type {
+        return Err("Unexpected SMBIOS structure type");
+    }
+    Ok(())
+}
+
+/// BIOS Information (Type 0)
+#[derive(Debug, Clone, Copy)]
+pub struct BiosInformation<'a>(Structure<'a>);
+
+impl<'a> BiosInformation<'a> {
+    pub const TYPE: u8 = 0;
+
+    pub fn new(structure: Structure<'a>) -> SmbiosResult<Self> {
+        expect_type(&structure, Self::TYPE)?;
+        Ok(Self(structure))
+    }
+
+    pub fn vendor(&self) -> Option<&'a str> {
+        self.0.string_at(0x04)
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        self.0.string_at(0x05)
+    }
+
+    pub fn release_date(&self) -> Option<&'a str> {
+        self.0.string_at(0x08)
+    }
+
+    /// (major, minor) of the system BIOS. Present from SMBIOS 2.4.
+    pub fn release(&self) -> Option<(u8, u8)> {
+        Some((self.0.byte(0x14)?, self.0.byte(0x15)?))
+    }
+}
+
+/// System Information (Type 1)
+#[derive(Debug, Clone, Copy)]
+pub struct SystemInformation<'a>(Structure<'a>);
+
+impl<'a> SystemInformation<'a> {
+    pub const TYPE: u8 = 1;
+
+    pub fn new(structure: Structure<'a>) -> SmbiosResult<Self> {
+        expect_type(&structure, Self::TYPE)?;
+        Ok(Self(structure))
+    }
+
+    pub fn manufacturer(&self) -> Option<&'a str> {
+        self.0.string_at(0x04)
+    }
+
+    pub fn product_name(&self) -> Option<&'a str> {
+        self.0.string_at(0x05)
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        self.0.string_at(0x06)
+    }
+
+    pub fn serial_number(&self) -> Option<&'a str> {
+        self.0.string_at(0x07)
+    }
+
+    /// UUID in the byte order of the table. The first three fields are little-endian.
+    pub fn uuid(&self) -> Option<[u8; 16]> {
+        self.0.formatted().get(0x08..0x18)?.try_into().ok()
+    }
+
+    pub fn family(&self) -> Option<&'a str> {
+        self.0.string_at(0x1A)
+    }
+}
+
+/// Processor Information (Type 4)
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessorInformation<'a>(Structure<'a>);
+
+impl<'a> ProcessorInformation<'a> {
+    pub const TYPE: u8 = 4;
+
+    pub fn new(structure: Structure<'a>) -> SmbiosResult<Self> {
+        expect_type(&structure, Self::TYPE)?;
+        Ok(Self(structure))
+    }
+
+    pub fn socket_designation(&self) -> Option<&'a str> {
+        self.0.string_at(0x04)
+    }
+
+    pub fn processor_type(&self) -> Option<u8> {
+        self.0.byte(0x05)
+    }
+
+    pub fn family(&self) -> Option<u8> {
+        self.0.byte(0x06)
+    }
+
+    pub fn manufacturer(&self) -> Option<&'a str> {
+        self.0.string_at(0x07)
+    }
+
+    /// Raw CPUID leaf 1 EAX and EDX on x86.
+    pub fn processor_id(&self) -> Option<u64> {
+        self.0.qword(0x08)
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        self.0.string_at(0x10)
+    }
+
+    /// Maximum speed in MHz, 0 if unknown.
+    pub fn max_speed(&self) -> Option<u16> {
+        self.0.word(0x14)
+    }
+
+    /// Current speed in MHz, 0 if unknown.
+    pub fn current_speed(&self) -> Option<u16> {
+        self.0.word(0x16)
+    }
+
+    /// The socket is populated.
+    pub fn is_populated(&self) -> bool {
+        self.0.byte(0x18).is_some_and(|status| status & 0x40 != 0)
+    }
+
+    /// Present from SMBIOS 2.5. Values over 255 are only available from the 3.0 fields.
+    pub fn core_count(&self) -> Option<u16> {
+        match self.0.byte(0x23)? {
+            0xFF => self.0.word(0x2A),
+            count => Some(count as u16),
+        }
+    }
+
+    pub fn thread_count(&self) -> Option<u16> {
+        match self.0.byte(0x25)? {
+            0xFF => self.0.word(0x2E),
+            count => Some(count as u16),
+        }
+    }
+}
+
+/// Memory Device (Type 17), typically a DIMM slot.
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryDevice<'a>(Structure<'a>);
+
+impl<'a> MemoryDevice<'a> {
+    pub const TYPE: u8 = 17;
+
+    pub fn new(structure: Structure<'a>) -> SmbiosResult<Self> {
+        expect_type(&structure, Self::TYPE)?;
+        Ok(Self(structure))
+    }
+
+    pub fn physical_memory_array_handle(&self) -> Option<u16> {
+        self.0.word(0x04)
+    }
+
+    /// Size in bytes. `None` if unknown, `Some(0)` if the slot is empty.
+    pub fn size(&self) -> Option<u64> {
+        match self.0.word(0x0C)? {
+            0xFFFF => None,
+            // The size is in the Extended Size field in megabytes.
+            0x7FFF => Some((self.0.dword(0x1C)? & 0x7FFF_FFFF) as u64 * 1024 * 1024),
+            size if size & 0x8000 != 0 => Some((size & 0x7FFF) as u64 * 1024),
+            size => Some(size as u64 * 1024 * 1024),
+        }
+    }
+
+    pub fn form_factor(&self) -> Option<u8> {
+        self.0.byte(0x0E)
+    }
+
+    pub fn device_locator(&self) -> Option<&'a str> {
+        self.0.string_at(0x10)
+    }
+
+    pub fn bank_locator(&self) -> Option<&'a str> {
+        self.0.string_at(0x11)
+    }
+
+    /// Memory type, e.g. 0x1A for DDR4 and 0x22 for DDR5.
+    pub fn memory_type(&self) -> Option<u8> {
+        self.0.byte(0x12)
+    }
+
+    /// Maximum speed in MT/s, 0 if unknown.
+    pub fn speed(&self) -> Option<u16> {
+        self.0.word(0x15)
+    }
+
+    pub fn manufacturer(&self) -> Option<&'a str> {
+        self.0.string_at(0x17)
+    }
+
+    pub fn serial_number(&self) -> Option<&'a str> {
+        self.0.string_at(0x18)
+    }
+
+    pub fn part_number(&self) -> Option<&'a str> {
+        self.0.string_at(0x1A)
+    }
+
+    /// Configured speed in MT/s, 0 if unknown.
+    pub fn configured_speed(&self) -> Option<u16> {
+        self.0.word(0x20)
+    }
+}
+
+impl<'a> StructureTable<'a> {
+    pub fn bios_information(&self) -> Option<BiosInformation<'a>> {
+        self.structures_of_type(BiosInformation::TYPE)
+            .find_map(|s| BiosInformation::new(s).ok())
+    }
+
+    pub fn system_information(&self) -> Option<SystemInformation<'a>> {
+        self.structures_of_type(SystemInformation::TYPE)
+            .find_map(|s| SystemInformation::new(s).ok())
+    }
+
+    pub fn processors(&self) -> impl Iterator<Item = ProcessorInformation<'a>> {
+        self.structures_of_type(ProcessorInformation::TYPE)
+            .filter_map(|s| ProcessorInformation::new(s).ok())
+    }
+
+    pub fn memory_devices(&self) -> impl Iterator<Item = MemoryDevice<'a>> {
+        self.structures_of_type(MemoryDevice::TYPE)
+            .filter_map(|s| MemoryDevice::new(s).ok())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Structure table synthesized in the shape OVMF publishes on QEMU q35 with 32 GiB of memory.
+    const SAMPLE_TABLE: &[u8] = include_bytes!("../testdata/sample_table.bin");
+
+    #[test]
+    fn walk_structures() {
+        let table = StructureTable::new(SAMPLE_TABLE);
+        let types: [u8; 5] =
+            core::array::from_fn(|i| table.structures().nth(i).unwrap().structure_type());
+        assert_eq!(types, [0, 1, 4, 17, Structure::END_OF_TABLE]);
+        assert_eq!(table.structures().count(), 5);
+        assert_eq!(table.find_by_handle(0x0400).unwrap().structure_type(), 4);
+    }
+
+    #[test]
+    fn typed_views() {
+        let table = StructureTable::new(SAMPLE_TABLE);
+
+        let bios = table.bios_information().unwrap();
+        assert_eq!(bios.vendor(), Some("EDK II"));
+        assert_eq!(bios.release_date(), Some("02/02/2022"));
+
+        let system = table.system_information().unwrap();
+        assert_eq!(system.manufacturer(), Some("QEMU"));
+        assert_eq!(
+            system.product_name(),
+            Some("Standard PC (Q35 + ICH9, 2009)")
+        );
+        assert_eq!(system.serial_number(), None);
+
+        let processor = table.processors().next().unwrap();
+        assert_eq!(processor.socket_designation(), Some("CPU 0"));
+        assert!(processor.is_populated());
+        assert_eq!(processor.max_speed(), Some(2000));
+        assert_eq!(processor.core_count(), Some(4));
+
+        let dimm = table.memory_devices().next().unwrap();
+        assert_eq!(dimm.device_locator(), Some("DIMM 0"));
+        assert_eq!(dimm.bank_locator(), None);
+        assert_eq!(dimm.size(), Some(32 * 1024 * 1024 * 1024));
+        assert_eq!(dimm.manufacturer(), Some("QEMU"));
+    }
+}
@@ -0,0 +1,251 @@
+//! Arguments given to the image, from the shell or from the optional data of a Boot#### option.
+//!
+//! Arguments are whitespace separated. As in the shell, `"` groups words and `^` escapes the next character.
+//! Options are written as `key=value` or just `key`, e.g. `log=debug video=1280x800 quiet`.
+
+use core::str::Utf8Error;
+
+use crate::{
+    efi_boot_services::{EfiMemoryType, PoolBox},
+    protocol::{
+        loaded_image::EfiLoadedImageProtocol, shell_parameters::EfiShellParametersProtocol,
+    },
+    EfiBootServices, EfiHandle, EfiResult,
+};
+
+const SEPARATOR: char = '\0';
+
+/// UTF-8 arguments of the image, without the image path.
+pub struct CommandLine<'a> {
+    // Arguments each followed by SEPARATOR.
+    buffer: PoolBox<'a, [u8]>,
+    len: usize,
+}
+
+impl<'a> CommandLine<'a> {
+    /// Reads the arguments of `image_handle`.
+    /// Shell parameters are preferred; otherwise LoadOptions are decoded as a UCS-2 string.
+    /// LoadOptions that are not a string, i.e. have odd length, give no arguments.
+    pub fn from_image(
+        boot_services: &'a EfiBootServices,
+        image_handle: EfiHandle,
+    ) -> EfiResult<Self> {
+        if let Ok(shell_parameters) =
+            boot_services.handle_protocol::<EfiShellParametersProtocol>(image_handle)
+        {
+            let capacity = shell_parameters
+                .argv()
+                .skip(1)
+                .map(|arg| arg.len() * MAX_BYTES_PER_UNIT + 1)
+                .sum();
+            let mut command_line = Self::with_capacity(boot_services, capacity)?;
+            for arg in shell_parameters.argv().skip(1) {
+                let mut writer = Writer::new(&mut command_line.buffer[command_line.len..]);
+                for c in char::decode_utf16(arg.code_units()) {
+                    writer.push(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                writer.push(SEPARATOR);
+                command_line.len += writer.len;
+            }
+            return Ok(command_line);
+        }
+
+        let loaded_image = boot_services.handle_protocol::<EfiLoadedImageProtocol>(image_handle)?;
+        let load_options = loaded_image.load_options();
+        if load_options.len() % 2 != 0 {
+            return Self::with_capacity(boot_services, 0);
+        }
+
+        let units = load_options
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]));
+        let mut command_line =
+            Self::with_capacity(boot_services, units.len() * (MAX_BYTES_PER_UNIT + 1))?;
+        command_line.len = split(units, &mut command_line.buffer);
+        Ok(command_line)
+    }
+
+    fn with_capacity(boot_services: &'a EfiBootServices, capacity: usize) -> EfiResult<Self> {
+        Ok(Self {
+            buffer: PoolBox::new_slice(boot_services, EfiMemoryType::EfiLoaderData, capacity, 0)?,
+            len: 0,
+        })
+    }
+
+    pub fn args(&self) -> Args<'_> {
+        Args::new(&self.buffer[..self.len]).unwrap_or_default()
+    }
+
+    pub fn options(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.args().map(parse_option)
+    }
+
+    /// Value of the last `key=value`. `None` if there is no such option or it has no value.
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.args().value(key)
+    }
+
+    /// Whether `key` is given, with or without a value.
+    pub fn contains(&self, key: &str) -> bool {
+        self.args().contains(key)
+    }
+}
+
+impl<'a> core::fmt::Debug for CommandLine<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.args()).finish()
+    }
+}
+
+/// Iterator over arguments stored each followed by a separator.
+#[derive(Debug, Clone, Default)]
+pub struct Args<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Args<'a> {
+    fn new(buffer: &'a [u8]) -> Result<Self, Utf8Error> {
+        Ok(Self {
+            rest: core::str::from_utf8(buffer)?,
+        })
+    }
+
+    pub fn value(self, key: &str) -> Option<&'a str> {
+        self.map(parse_option)
+            .filter(|(k, _)| *k == key)
+            .last()
+            .and_then(|(_, value)| value)
+    }
+
+    pub fn contains(mut self, key: &str) -> bool {
+        self.any(|arg| parse_option(arg).0 == key)
+    }
+}
+
+impl<'a> Iterator for Args<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (arg, rest) = self.rest.split_once(SEPARATOR)?;
+        self.rest = rest;
+        Some(arg)
+    }
+}
+
+/// Splits `key=value` at the first `=`.
+pub fn parse_option(arg: &str) -> (&str, Option<&str>) {
+    match arg.split_once('=') {
+        Some((key, value)) => (key, Some(value)),
+        None => (arg, None),
+    }
+}
+
+/// A UCS-2 code unit takes up to 3 bytes in UTF-8. A surrogate pair takes 4 bytes for 2 units.
+const MAX_BYTES_PER_UNIT: usize = 3;
+
+struct Writer<'b> {
+    buffer: &'b mut [u8],
+    len: usize,
+}
+
+impl<'b> Writer<'b> {
+    fn new(buffer: &'b mut [u8]) -> Self {
+        Self { buffer, len: 0 }
+    }
+
+    fn push(&mut self, c: char) {
+        self.len += c.encode_utf8(&mut self.buffer[self.len..]).len();
+    }
+}
+
+/// Splits a null-terminated or unterminated UCS-2 command line into `buffer`, returning the used length.
+/// `buffer` must hold `MAX_BYTES_PER_UNIT + 1` bytes per unit.
+fn split(units: impl Iterator<Item = u16>, buffer: &mut [u8]) -> usize {
+    let mut writer = Writer::new(buffer);
+    let mut in_arg = false;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for c in char::decode_utf16(units.take_while(|&unit| unit != 0)) {
+        let c = c.unwrap_or(char::REPLACEMENT_CHARACTER);
+        match c {
+            _ if escaped => {
+                writer.push(c);
+                escaped = false;
+            }
+            '^' => {
+                in_arg = true;
+                escaped = true;
+            }
+            '"' => {
+                in_arg = true;
+                in_quotes = !in_quotes;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if in_arg {
+                    writer.push(SEPARATOR);
+                    in_arg = false;
+                }
+            }
+            c => {
+                in_arg = true;
+                writer.push(c);
+            }
+        }
+    }
+
+    if in_arg {
+        writer.push(SEPARATOR);
+    }
+
+    writer.len
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn split_str<'b>(s: &str, buffer: &'b mut [u8]) -> Args<'b> {
+        let len = split(s.encode_utf16(), buffer);
+        Args::new(&buffer[..len]).unwrap()
+    }
+
+    #[test]
+    fn split_args() {
+        let mut buffer = [0; 256];
+        let args = split_str("  log=debug   video=1280x800\tquiet ", &mut buffer);
+        assert!(args.eq(["log=debug", "video=1280x800", "quiet"]));
+
+        let args = split_str("title=\"moos kernel\" a^\"b \"\" ü🦀\0ignored", &mut buffer);
+        assert!(args.eq(["title=moos kernel", "a\"b", "", "ü🦀"]));
+
+        assert_eq!(split_str("", &mut buffer).count(), 0);
+    }
+
+    #[test]
+    fn options() {
+        let mut buffer = [0; 256];
+        let args = split_str("log=info quiet log=debug video= a=b=c", &mut buffer);
+        assert_eq!(args.clone().value("log"), Some("debug"));
+        assert_eq!(args.clone().value("video"), Some(""));
+        assert_eq!(args.clone().value("a"), Some("b=c"));
+        assert_eq!(args.clone().value("quiet"), None);
+        assert!(args.clone().contains("quiet"));
+        assert!(!args.clone().contains("missing"));
+        assert_eq!(parse_option("quiet"), ("quiet", None));
+    }
+
+    #[test]
+    fn load_options() {
+        let mut firmware = crate::mock::MockFirmware::new();
+        firmware.set_load_options("log=debug \"title=moos kernel\" quiet");
+        let boot_services = firmware.boot_services();
+
+        let command_line = CommandLine::from_image(boot_services, firmware.image_handle()).unwrap();
+        assert!(command_line
+            .args()
+            .eq(["log=debug", "title=moos kernel", "quiet"]));
+        assert_eq!(command_line.value("log"), Some("debug"));
+        assert!(command_line.contains("quiet"));
+    }
+}
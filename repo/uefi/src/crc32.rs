@@ -0,0 +1,120 @@
+//! CRC-32 (IEEE 802.3) as used by the EFI table headers and GPT.
+//! REF: https://uefi.org/specs/UEFI/2.10/04_EFI_System_Table.html#efi-table-header
+
+use crate::{EfiBootServices, EfiResult};
+
+const POLYNOMIAL: u32 = 0xEDB88320;
+
+const TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Incremental CRC-32 computation.
+#[derive(Debug, Clone)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc32 {
+    pub const fn new() -> Self {
+        Self { state: !0 }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.state = TABLE[((self.state ^ byte as u32) & 0xFF) as usize] ^ (self.state >> 8);
+        }
+    }
+
+    pub fn finish(&self) -> u32 {
+        !self.state
+    }
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.finish()
+}
+
+/// Compares [`crc32`] with CalculateCrc32 of the firmware, for debug builds to run while boot services are available.
+pub fn self_check(boot_services: &EfiBootServices) -> EfiResult<()> {
+    /// Every byte, so that every entry of the table is used.
+    const ALL_BYTES: [u8; 256] = {
+        let mut bytes = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            bytes[i] = i as u8;
+            i += 1;
+        }
+        bytes
+    };
+    // Not empty, since CalculateCrc32 rejects empty data and calculate_crc32 does not call it for that.
+    const SAMPLES: [&[u8]; 3] = [
+        b"123456789",
+        b"The quick brown fox jumps over the lazy dog",
+        &ALL_BYTES,
+    ];
+
+    for sample in SAMPLES {
+        if boot_services.calculate_crc32(sample)? != crc32(sample) {
+            return Err("CRC32 does not match CalculateCrc32 of the firmware");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn check_value() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414FA339
+        );
+    }
+
+    #[test]
+    fn incremental() {
+        let data = b"The quick brown fox jumps over the lazy dog";
+        let mut crc = Crc32::new();
+        for chunk in data.chunks(5) {
+            crc.update(chunk);
+        }
+        assert_eq!(crc.finish(), crc32(data));
+    }
+
+    #[test]
+    fn firmware() {
+        let firmware = crate::mock::MockFirmware::new();
+        assert_eq!(self_check(firmware.boot_services()), Ok(()));
+        assert!(firmware.system_table().verify_crc32());
+    }
+}
@@ -22,6 +22,16 @@ pub struct EfiVoid {
 #[derive(Debug, Clone, Copy)]
 pub struct EfiHandle(NonNull<EfiVoid>);
 
+impl EfiHandle {
+    pub fn as_ptr(&self) -> *mut EfiVoid {
+        self.0.as_ptr()
+    }
+
+    pub(crate) fn from_ptr(ptr: NonNull<EfiVoid>) -> Self {
+        Self(ptr)
+    }
+}
+
 // REF: https://uefi.org/specs/UEFI/2.11/Apx_D_Status_Codes.html#status-codes
 #[repr(transparent)]
 #[must_use]
@@ -29,74 +39,88 @@ pub struct EfiHandle(NonNull<EfiVoid>);
 pub struct EfiStatus(usize);
 
 impl EfiStatus {
+    const ERROR_BIT: usize = 1 << (usize::BITS - 1);
+
     /// The operation completed successfully.
     pub const SUCCESS: Self = Self(0);
     /// The image failed to load.
-    pub const LOAD_ERROR: Self = Self(1);
+    pub const LOAD_ERROR: Self = Self(Self::ERROR_BIT | 1);
     /// A parameter was incorrect.
-    pub const INVALID_PARAMETER: Self = Self(2);
+    pub const INVALID_PARAMETER: Self = Self(Self::ERROR_BIT | 2);
     /// The operation is not supported.
-    pub const UNSUPPORTED: Self = Self(3);
+    pub const UNSUPPORTED: Self = Self(Self::ERROR_BIT | 3);
     /// The buffer was not the proper size for the request.
-    pub const BAD_BUFFER_SIZE: Self = Self(4);
+    pub const BAD_BUFFER_SIZE: Self = Self(Self::ERROR_BIT | 4);
     /// The buffer is not large enough to hold the requested data. The required buffer size is returned in the appropriate parameter when this error occurs.
-    pub const BUFFER_TOO_SMALL: Self = Self(5);
+    pub const BUFFER_TOO_SMALL: Self = Self(Self::ERROR_BIT | 5);
     /// There is no data pending upon return.
-    pub const NOT_READY: Self = Self(6);
+    pub const NOT_READY: Self = Self(Self::ERROR_BIT | 6);
     /// The physical device reported an error while attempting the operation.
-    pub const DEVICE_ERROR: Self = Self(7);
+    pub const DEVICE_ERROR: Self = Self(Self::ERROR_BIT | 7);
     /// The device cannot be written to.
-    pub const WRITE_PROTECTED: Self = Self(8);
+    pub const WRITE_PROTECTED: Self = Self(Self::ERROR_BIT | 8);
     /// A resource has run out.
-    pub const OUT_OF_RESOURCES: Self = Self(9);
+    pub const OUT_OF_RESOURCES: Self = Self(Self::ERROR_BIT | 9);
     /// An inconstancy was detected on the file system causing the operating to fail.
-    pub const VOLUME_CORRUPTED: Self = Self(10);
+    pub const VOLUME_CORRUPTED: Self = Self(Self::ERROR_BIT | 10);
     /// There is no more space on the file system.
-    pub const VOLUME_FULL: Self = Self(11);
+    pub const VOLUME_FULL: Self = Self(Self::ERROR_BIT | 11);
     /// The device does not contain any medium to perform the operation.
-    pub const NO_MEDIA: Self = Self(12);
+    pub const NO_MEDIA: Self = Self(Self::ERROR_BIT | 12);
     /// The medium in the device has changed since the last access.
-    pub const MEDIA_CHANGED: Self = Self(13);
+    pub const MEDIA_CHANGED: Self = Self(Self::ERROR_BIT | 13);
     /// The item was not found.
-    pub const NOT_FOUND: Self = Self(14);
+    pub const NOT_FOUND: Self = Self(Self::ERROR_BIT | 14);
     /// Access was denied.
-    pub const ACCESS_DENIED: Self = Self(15);
+    pub const ACCESS_DENIED: Self = Self(Self::ERROR_BIT | 15);
     /// The server was not found or did not respond to the request.
-    pub const NO_RESPONSE: Self = Self(16);
+    pub const NO_RESPONSE: Self = Self(Self::ERROR_BIT | 16);
     /// A mapping to a device does not exist.
-    pub const NO_MAPPING: Self = Self(17);
+    pub const NO_MAPPING: Self = Self(Self::ERROR_BIT | 17);
     /// The timeout time expired.
-    pub const TIMEOUT: Self = Self(18);
+    pub const TIMEOUT: Self = Self(Self::ERROR_BIT | 18);
     /// The protocol has not been started.
-    pub const NOT_STARTED: Self = Self(19);
+    pub const NOT_STARTED: Self = Self(Self::ERROR_BIT | 19);
     /// The protocol has already been started.
-    pub const ALREADY_STARTED: Self = Self(20);
+    pub const ALREADY_STARTED: Self = Self(Self::ERROR_BIT | 20);
     /// The operation was aborted.
-    pub const ABORTED: Self = Self(21);
+    pub const ABORTED: Self = Self(Self::ERROR_BIT | 21);
     /// An ICMP error occurred during the network operation.
-    pub const ICMP_ERROR: Self = Self(22);
+    pub const ICMP_ERROR: Self = Self(Self::ERROR_BIT | 22);
     /// A TFTP error occurred during the network operation.
-    pub const TFTP_ERROR: Self = Self(23);
+    pub const TFTP_ERROR: Self = Self(Self::ERROR_BIT | 23);
     /// A protocol error occurred during the network operation.
-    pub const PROTOCOL_ERROR: Self = Self(24);
+    pub const PROTOCOL_ERROR: Self = Self(Self::ERROR_BIT | 24);
     /// The function encountered an internal version that was incompatible with a version requested by the caller.
-    pub const INCOMPATIBLE_VERSION: Self = Self(25);
+    pub const INCOMPATIBLE_VERSION: Self = Self(Self::ERROR_BIT | 25);
     /// The function was not performed due to a security violation.
-    pub const SECURITY_VIOLATION: Self = Self(26);
+    pub const SECURITY_VIOLATION: Self = Self(Self::ERROR_BIT | 26);
     /// A CRC error was detected.
-    pub const CRC_ERROR: Self = Self(27);
+    pub const CRC_ERROR: Self = Self(Self::ERROR_BIT | 27);
     /// Beginning or end of media was reached
-    pub const END_OF_MEDIA: Self = Self(28);
+    pub const END_OF_MEDIA: Self = Self(Self::ERROR_BIT | 28);
     /// The end of the file was reached.
-    pub const END_OF_FILE: Self = Self(31);
+    pub const END_OF_FILE: Self = Self(Self::ERROR_BIT | 31);
     /// The language specified was invalid.
-    pub const INVALID_LANGUAGE: Self = Self(32);
+    pub const INVALID_LANGUAGE: Self = Self(Self::ERROR_BIT | 32);
     /// The security status of the data is unknown or compromised and the data must be updated or replaced to restore a valid security status.
-    pub const COMPROMISED_DATA: Self = Self(33);
+    pub const COMPROMISED_DATA: Self = Self(Self::ERROR_BIT | 33);
     /// There is an address conflict address allocation
-    pub const IP_ADDRESS_CONFLICT: Self = Self(34);
+    pub const IP_ADDRESS_CONFLICT: Self = Self(Self::ERROR_BIT | 34);
     /// A HTTP error occurred during the network operation.
-    pub const HTTP_ERROR: Self = Self(35);
+    pub const HTTP_ERROR: Self = Self(Self::ERROR_BIT | 35);
+
+    pub fn is_success(&self) -> bool {
+        *self == Self::SUCCESS
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.0 & Self::ERROR_BIT != 0
+    }
+
+    pub fn is_warning(&self) -> bool {
+        !self.is_success() && !self.is_error()
+    }
 }
 
 #[repr(C)]
@@ -105,10 +129,54 @@ pub struct EfiGuid(pub u32, pub u16, pub u16, pub [u8; 8]);
 
 const _: () = assert!(size_of::<EfiGuid>() * 8 == 128);
 
+// Registry format, e.g. 09576e91-6d3f-11d2-8e39-00a0c969723b
+impl core::fmt::Display for EfiGuid {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let Self(a, b, c, d) = self;
+        core::write!(
+            f,
+            "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+            a,
+            b,
+            c,
+            d[0],
+            d[1],
+            d[2],
+            d[3],
+            d[4],
+            d[5],
+            d[6],
+            d[7]
+        )
+    }
+}
+
 #[repr(transparent)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct EfiTpl(usize);
 
+// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html#efi-boot-services-raisetpl
+impl EfiTpl {
+    pub const APPLICATION: Self = Self(4);
+    pub const CALLBACK: Self = Self(8);
+    pub const NOTIFY: Self = Self(16);
+    pub const HIGH_LEVEL: Self = Self(31);
+}
+
 #[repr(transparent)]
 #[derive(Debug, Clone, Copy)]
 pub struct EfiEvent(*mut EfiVoid);
+
+impl EfiEvent {
+    /// Passed where an event is optional.
+    pub(crate) const NULL: Self = Self(core::ptr::null_mut());
+
+    pub fn as_ptr(&self) -> *mut EfiVoid {
+        self.0
+    }
+
+    #[cfg(any(test, feature = "mock"))]
+    pub(crate) fn from_ptr(ptr: *mut EfiVoid) -> Self {
+        Self(ptr)
+    }
+}
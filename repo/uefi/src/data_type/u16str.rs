@@ -6,15 +6,55 @@ extern "C" {
 }
 
 impl U16Str {
+    /// # Safety
+    /// `ptr` must point to a null-terminated UCS-2 string that stays alive and unmodified while the result is used.
     pub unsafe fn from_raw_parts(ptr: *const u16) -> *const Self {
         ptr as *const Self
     }
 
+    pub fn as_ptr(&self) -> *const u16 {
+        (self as *const Self) as *const u16
+    }
+
     pub fn code_units(&self) -> CodeUnits<'_> {
         CodeUnits {
             _phantom: PhantomData,
-            ptr: { (self as *const Self) as *const u16 },
+            ptr: self.as_ptr(),
+        }
+    }
+
+    /// Number of code units, excluding the null terminator.
+    pub fn len(&self) -> usize {
+        self.code_units().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.code_units().next().is_none()
+    }
+}
+
+impl core::fmt::Display for U16Str {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use core::fmt::Write;
+
+        for c in char::decode_utf16(self.code_units()) {
+            f.write_char(c.unwrap_or(char::REPLACEMENT_CHARACTER))?;
+        }
+        Ok(())
+    }
+}
+
+impl core::fmt::Debug for U16Str {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use core::fmt::Write;
+
+        f.write_char('"')?;
+        for c in char::decode_utf16(self.code_units()) {
+            for e in c.unwrap_or(char::REPLACEMENT_CHARACTER).escape_debug() {
+                f.write_char(e)?;
+            }
         }
+        f.write_char('"')
     }
 }
 
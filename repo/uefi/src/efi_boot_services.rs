@@ -35,11 +35,13 @@ use crate::{
 };
 
 pub use event::event_group;
+pub use image::ImageExit;
 pub use memory_allocation::{
-    EfiAllocateType, EfiMemoryAttribute, EfiMemoryType, EfiPhysicalAddress, EfiVirtualAddress,
+    EfiAllocateType, EfiMemoryAttribute, EfiMemoryDescriptor, EfiMemoryDescriptors, EfiMemoryMap,
+    EfiMemoryType, EfiPhysicalAddress, EfiVirtualAddress, PoolBox,
 };
 pub use protocol_handler::{
-    EfiLocateSearchType, EfiOpenProtocolAttributes, EfiOpenProtocolInformationEntry,
+    EfiLocateSearchType, EfiOpenProtocolAttributes, EfiOpenProtocolInformationEntry, ProtocolNotify,
 };
 pub use timer::EfiTimerDelay;
 
@@ -47,93 +49,93 @@ pub use timer::EfiTimerDelay;
 #[repr(C)]
 #[derive(Debug)]
 pub struct EfiBootServices {
-    hdr: EfiTableHeader,
+    pub(crate) hdr: EfiTableHeader,
 
     //
     // Task Priority Services
     //
-    raise_tpl: EfiRaiseTpl,
-    restore_tpl: EfiRestoreTpl,
+    pub(crate) raise_tpl: EfiRaiseTpl,
+    pub(crate) restore_tpl: EfiRestoreTpl,
 
     //
     // Memory Services
     //
-    allocate_pages: EfiAllocatePages,
-    free_pages: EfiFreePages,
-    get_memory_map: EfiGetMemoryMap,
-    allocate_pool: EfiAllocatePool,
-    free_pool: EfiFreePool,
+    pub(crate) allocate_pages: EfiAllocatePages,
+    pub(crate) free_pages: EfiFreePages,
+    pub(crate) get_memory_map: EfiGetMemoryMap,
+    pub(crate) allocate_pool: EfiAllocatePool,
+    pub(crate) free_pool: EfiFreePool,
 
     //
     // Event & Timer Services
     //
-    create_event: EfiCreateEvent,
-    set_timer: EfiSetTimer,
-    wait_for_event: EfiWaitForEvent,
-    signal_event: EfiSignalEvent,
-    close_event: EfiCloseEvent,
-    check_event: EfiCheckEvent,
+    pub(crate) create_event: EfiCreateEvent,
+    pub(crate) set_timer: EfiSetTimer,
+    pub(crate) wait_for_event: EfiWaitForEvent,
+    pub(crate) signal_event: EfiSignalEvent,
+    pub(crate) close_event: EfiCloseEvent,
+    pub(crate) check_event: EfiCheckEvent,
 
     //
     // Protocol Handler Services
     //
-    install_protocol_interface: EfiInstallProtocolInterface,
-    reinstall_protocol_interface: EfiReinstallProtoclInterface,
-    uninsatall_protocol_interface: EfiUninstallProtocolInterface,
-    handle_protocol: EfiHandleProtocol,
-    _reserved: *mut EfiVoid,
-    register_protocol_notify: EfiRegisterProtocolNotify,
-    locate_handle: EfiLocateHandle,
-    locate_device_path: EfiLocateDevicePath,
-    install_configuration_table: EfiInstallConfigurationTable,
+    pub(crate) install_protocol_interface: EfiInstallProtocolInterface,
+    pub(crate) reinstall_protocol_interface: EfiReinstallProtoclInterface,
+    pub(crate) uninsatall_protocol_interface: EfiUninstallProtocolInterface,
+    pub(crate) handle_protocol: EfiHandleProtocol,
+    pub(crate) _reserved: *mut EfiVoid,
+    pub(crate) register_protocol_notify: EfiRegisterProtocolNotify,
+    pub(crate) locate_handle: EfiLocateHandle,
+    pub(crate) locate_device_path: EfiLocateDevicePath,
+    pub(crate) install_configuration_table: EfiInstallConfigurationTable,
 
     //
     // Image Services
     //
-    load_image: EfiLoadImage,
-    start_image: EfiStartImage,
-    exit: EfiExit,
-    unload_image: EfiUnloadImage,
-    exit_boot_services: EfiExitBootServices,
+    pub(crate) load_image: EfiLoadImage,
+    pub(crate) start_image: EfiStartImage,
+    pub(crate) exit: EfiExit,
+    pub(crate) unload_image: EfiUnloadImage,
+    pub(crate) exit_boot_services: EfiExitBootServices,
 
     //
     // Miscellaneous Services
     //
-    get_next_monotonic_count: EfiGetNextMonotonicCount,
-    stall: EfiStall,
-    set_watchdog_timer: EfiSetWatchdogTimer,
+    pub(crate) get_next_monotonic_count: EfiGetNextMonotonicCount,
+    pub(crate) stall: EfiStall,
+    pub(crate) set_watchdog_timer: EfiSetWatchdogTimer,
 
     //
     // DriverSupport Services
     //
-    connect_controller: EfiConnectController,
-    disconnect_controller: EfiDisconnectController,
+    pub(crate) connect_controller: EfiConnectController,
+    pub(crate) disconnect_controller: EfiDisconnectController,
 
     //
     // Open and Close Protocol Services
     //
-    open_protocol: EfiOpenProtocol,
-    close_protocol: EfiCloseProtocol,
-    open_protocol_information: EfiOpenProtocolInformation,
+    pub(crate) open_protocol: EfiOpenProtocol,
+    pub(crate) close_protocol: EfiCloseProtocol,
+    pub(crate) open_protocol_information: EfiOpenProtocolInformation,
 
     //
     // Library Services
     //
-    protocols_per_handle: EfiProtocolsPerHandle,
-    locate_handle_buffer: EfiLocateHandleBuffer,
-    locate_protocol: EfiLocateProtocol,
-    install_multiple_protocol_interfaces: EfiInstallMultipleProtocolInterfaces,
-    uninstall_multiple_protocol_interfaces: EfiUninstallMultipleProtocolInterfaces,
+    pub(crate) protocols_per_handle: EfiProtocolsPerHandle,
+    pub(crate) locate_handle_buffer: EfiLocateHandleBuffer,
+    pub(crate) locate_protocol: EfiLocateProtocol,
+    pub(crate) install_multiple_protocol_interfaces: EfiInstallMultipleProtocolInterfaces,
+    pub(crate) uninstall_multiple_protocol_interfaces: EfiUninstallMultipleProtocolInterfaces,
 
     //
     // 32-bit CRC Services
     //
-    calcurate_crc32: EfiCalculateCrc32,
+    pub(crate) calcurate_crc32: EfiCalculateCrc32,
 
     //
     // Miscellaneous Services
     //
-    copy_mem: EfiCopyMem,
-    set_mem: EfiSetMem,
-    create_event_ex: EfiCreateEventEx,
+    pub(crate) copy_mem: EfiCopyMem,
+    pub(crate) set_mem: EfiSetMem,
+    pub(crate) create_event_ex: EfiCreateEventEx,
 }
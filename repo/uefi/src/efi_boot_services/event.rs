@@ -1,7 +1,7 @@
 //! REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#event-timer-and-task-priority-services
 
-use crate::{EfiEvent, EfiGuid, EfiStatus, EfiTpl, EfiVoid};
-use core::ptr::NonNull;
+use crate::{EfiBootServices, EfiEvent, EfiGuid, EfiResult, EfiStatus, EfiTpl, EfiVoid};
+use core::{mem::MaybeUninit, ptr::NonNull};
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-createevent
 #[repr(transparent)]
@@ -29,6 +29,10 @@ impl EfiEventType {
     pub const SIGNAL_EXIT_BOOT_SERVICES: Self = Self(0x00000201);
     pub const SIGNAL_VIRTUAL_ADDRESS_CHANGE: Self = Self(0x60000202);
     pub const NOTIFY_SIGNAL: Self = Self(0x00000200);
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
 }
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-createevent
@@ -122,3 +126,89 @@ pub type EfiWaitForEvent = extern "efiapi" fn(
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-waitforevent
 pub type EfiCheckEvent = extern "efiapi" fn(event: EfiEvent) -> EfiStatus;
+
+impl EfiBootServices {
+    pub fn create_event(
+        &self,
+        event_type: EfiEventType,
+        notify_tpl: EfiTpl,
+        notify_function: Option<EfiEventNotify>,
+        notify_context: Option<NonNull<EfiVoid>>,
+    ) -> EfiResult<EfiEvent> {
+        let mut event = MaybeUninit::<EfiEvent>::uninit();
+        let status = (self.create_event)(
+            event_type,
+            notify_tpl,
+            notify_function,
+            notify_context,
+            NonNull::from(&mut event).cast(),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to create event");
+        }
+
+        Ok(unsafe { event.assume_init() })
+    }
+
+    pub fn close_event(&self, event: EfiEvent) -> EfiResult<()> {
+        let status = (self.close_event)(event);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to close event");
+        }
+
+        Ok(())
+    }
+
+    pub fn signal_event(&self, event: EfiEvent) -> EfiResult<()> {
+        let status = (self.signal_event)(event);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to signal event");
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::MockFirmware;
+    use core::cell::Cell;
+
+    extern "efiapi" fn count(_event: EfiEvent, context: NonNull<EfiVoid>) {
+        let count = unsafe { context.cast::<Cell<u32>>().as_ref() };
+        count.set(count.get() + 1);
+    }
+
+    #[test]
+    fn notify_waits_for_tpl() {
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+        let notified = Cell::new(0u32);
+        let event = boot_services
+            .create_event(
+                EfiEventType::NOTIFY_SIGNAL,
+                EfiTpl::CALLBACK,
+                Some(count),
+                Some(NonNull::from(&notified).cast()),
+            )
+            .unwrap();
+
+        boot_services.signal_event(event).unwrap();
+        assert_eq!(notified.get(), 1);
+
+        // The notify function is deferred until the TPL drops below its notify TPL.
+        let old_tpl = boot_services.raise_tpl(EfiTpl::NOTIFY);
+        boot_services.signal_event(event).unwrap();
+        assert_eq!(notified.get(), 1);
+        boot_services.restore_tpl(old_tpl);
+        assert_eq!(notified.get(), 2);
+        assert_eq!(firmware.tpl(), EfiTpl::APPLICATION);
+
+        boot_services.close_event(event).unwrap();
+        assert!(boot_services.signal_event(event).is_err());
+    }
+}
@@ -1,12 +1,14 @@
 //! REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#image-services
 
-use core::ptr::NonNull;
+use core::{mem::MaybeUninit, ptr::NonNull};
 
 use crate::{
-    efi_system_table::EfiSystemTable, protocol::device_path::EfiDevicePathProtocol, EfiHandle,
-    EfiStatus, EfiVoid, U16Str,
+    efi_system_table::EfiSystemTable, protocol::device_path::EfiDevicePathProtocol,
+    EfiBootServices, EfiHandle, EfiResult, EfiStatus, EfiVoid, U16Str,
 };
 
+use super::PoolBox;
+
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-loadimage
 pub type EfiLoadImage = extern "efiapi" fn(
     boot_policy: bool,
@@ -42,3 +44,188 @@ pub type EfiExit = extern "efiapi" fn(
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-exitbootservices
 pub type EfiExitBootServices =
     extern "efiapi" fn(image_handle: EfiHandle, map_key: usize) -> EfiStatus;
+
+/// Result of an image started by [`EfiBootServices::start_image`].
+#[derive(Debug)]
+pub struct ImageExit<'a> {
+    /// Status returned from the entry point or passed to Exit() by the image.
+    pub status: EfiStatus,
+    exit_data: Option<PoolBox<'a, U16Str>>,
+    exit_data_size: usize,
+}
+
+impl<'a> ImageExit<'a> {
+    /// Null-terminated string the image passed to Exit(), if any.
+    pub fn exit_data(&self) -> Option<&U16Str> {
+        self.exit_data.as_deref()
+    }
+
+    /// Size of the exit data in bytes. Binary data may follow the string.
+    pub fn exit_data_size(&self) -> usize {
+        self.exit_data_size
+    }
+}
+
+impl EfiBootServices {
+    /// Loads a PE/COFF image already read into memory.
+    pub fn load_image_from_buffer(
+        &self,
+        parent_image_handle: EfiHandle,
+        source_buffer: &[u8],
+    ) -> EfiResult<EfiHandle> {
+        let mut image_handle = MaybeUninit::<EfiHandle>::uninit();
+        let status = (self.load_image)(
+            false,
+            parent_image_handle,
+            None,
+            NonNull::new(source_buffer.as_ptr() as *mut EfiVoid),
+            source_buffer.len(),
+            NonNull::from(&mut image_handle).cast(),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to load image from buffer");
+        }
+
+        Ok(unsafe { image_handle.assume_init() })
+    }
+
+    /// Loads an image through the device path, e.g. a file on a simple file system.
+    /// `boot_policy` is true when the request originates from the boot manager.
+    pub fn load_image_from_device_path(
+        &self,
+        parent_image_handle: EfiHandle,
+        device_path: &EfiDevicePathProtocol,
+        boot_policy: bool,
+    ) -> EfiResult<EfiHandle> {
+        let mut image_handle = MaybeUninit::<EfiHandle>::uninit();
+        let status = (self.load_image)(
+            boot_policy,
+            parent_image_handle,
+            Some(NonNull::from(device_path)),
+            None,
+            0,
+            NonNull::from(&mut image_handle).cast(),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to load image from device path");
+        }
+
+        Ok(unsafe { image_handle.assume_init() })
+    }
+
+    /// Transfers control to a loaded image and returns when it exits.
+    pub fn start_image(&self, image_handle: EfiHandle) -> ImageExit<'_> {
+        let mut exit_data_size = 0;
+        let mut exit_data: *mut U16Str = core::ptr::null_mut::<u16>() as *mut U16Str;
+        let status = (self.start_image)(
+            image_handle,
+            NonNull::from(&mut exit_data_size),
+            Some(NonNull::from(&mut exit_data)),
+        );
+
+        let exit_data = NonNull::new(exit_data).map(|ptr| unsafe { PoolBox::from_raw(self, ptr) });
+
+        ImageExit {
+            status,
+            exit_data,
+            exit_data_size,
+        }
+    }
+
+    pub fn unload_image(&self, image_handle: EfiHandle) -> EfiResult<()> {
+        let status = (self.unload_image)(image_handle);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to unload image");
+        }
+
+        Ok(())
+    }
+
+    /// Terminates the image. This returns only if `image_handle` is not the running image.
+    pub fn exit(&self, image_handle: EfiHandle, exit_status: EfiStatus) -> EfiStatus {
+        (self.exit)(image_handle, exit_status, 0, None)
+    }
+
+    /// Terminates boot services. Fails if `map_key` is not of the current memory map,
+    /// in which case the memory map must be retrieved again.
+    ///
+    /// # Safety
+    /// Boot services and boot services memory must not be used once this succeeds.
+    pub unsafe fn exit_boot_services(
+        &self,
+        image_handle: EfiHandle,
+        map_key: usize,
+    ) -> EfiResult<()> {
+        let status = (self.exit_boot_services)(image_handle, map_key);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to exit boot services");
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::MockFirmware;
+    use std::format;
+
+    /// Enough for the mock firmware, which only checks the signature.
+    const IMAGE: &[u8] = b"MZ\x90\x00";
+
+    #[test]
+    fn start_image_with_exit_data() {
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+
+        let image = boot_services
+            .load_image_from_buffer(firmware.image_handle(), IMAGE)
+            .unwrap();
+        firmware.set_image_exit(image, EfiStatus::ABORTED, Some("No config"));
+
+        let exit = boot_services.start_image(image);
+        assert_eq!(exit.status, EfiStatus::ABORTED);
+        assert_eq!(format!("{}", exit.exit_data().unwrap()), "No config");
+        // Including the null terminator.
+        assert_eq!(exit.exit_data_size(), 10 * 2);
+        assert_eq!(firmware.pool_allocations(), 1);
+
+        // The exit data is freed with the result.
+        drop(exit);
+        assert_eq!(firmware.pool_allocations(), 0);
+
+        boot_services.unload_image(image).unwrap();
+        assert_eq!(firmware.loaded_images(), 0);
+        assert!(boot_services.unload_image(image).is_err());
+    }
+
+    #[test]
+    fn start_image_without_exit_data() {
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+
+        let image = boot_services
+            .load_image_from_buffer(firmware.image_handle(), IMAGE)
+            .unwrap();
+        let exit = boot_services.start_image(image);
+        assert_eq!(exit.status, EfiStatus::SUCCESS);
+        assert!(exit.exit_data().is_none());
+        assert_eq!(exit.exit_data_size(), 0);
+        assert_eq!(firmware.pool_allocations(), 0);
+    }
+
+    #[test]
+    fn load_image_rejects_non_pe() {
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+
+        let result = boot_services.load_image_from_buffer(firmware.image_handle(), b"ELF");
+        assert!(result.is_err());
+        assert_eq!(firmware.loaded_images(), 0);
+    }
+}
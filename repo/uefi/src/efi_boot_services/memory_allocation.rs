@@ -1,8 +1,12 @@
 //! REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#memory-allocation-services
 
-use core::ptr::NonNull;
+use core::{
+    mem::size_of,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
 
-use crate::{EfiStatus, EfiVoid};
+use crate::{EfiBootServices, EfiResult, EfiStatus, EfiVoid};
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-allocatepages
 pub type EfiAllocatePages = extern "efiapi" fn(
@@ -23,7 +27,7 @@ pub enum EfiAllocateType {
 }
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-allocatepages
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 pub enum EfiMemoryType {
     EfiReservedMemoryType,
@@ -45,11 +49,51 @@ pub enum EfiMemoryType {
     EfiMaxMemoryType,
 }
 
+impl TryFrom<u32> for EfiMemoryType {
+    type Error = u32;
+
+    /// Fails for OEM (0x70000000..) and OS loader (0x80000000..) defined types.
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        const TYPES: [EfiMemoryType; 16] = [
+            EfiMemoryType::EfiReservedMemoryType,
+            EfiMemoryType::EfiLoaderCode,
+            EfiMemoryType::EfiLoaderData,
+            EfiMemoryType::EfiBootServicesCode,
+            EfiMemoryType::EfiBootServicesData,
+            EfiMemoryType::EfiRuntimeServicesCode,
+            EfiMemoryType::EfiRuntimeServicesData,
+            EfiMemoryType::EfiConventionalMemory,
+            EfiMemoryType::EfiUnusableMemory,
+            EfiMemoryType::EfiACPIReclaimMemory,
+            EfiMemoryType::EfiACPIMemoryNVS,
+            EfiMemoryType::EfiMemoryMappedIO,
+            EfiMemoryType::EfiMemoryMappedIOPortSpace,
+            EfiMemoryType::EfiPalCode,
+            EfiMemoryType::EfiPersistentMemory,
+            EfiMemoryType::EfiUnacceptedMemoryType,
+        ];
+
+        TYPES.get(value as usize).copied().ok_or(value)
+    }
+}
+
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-allocatepages
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(transparent)]
 pub struct EfiPhysicalAddress(u64);
 
+impl From<u64> for EfiPhysicalAddress {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<EfiPhysicalAddress> for u64 {
+    fn from(value: EfiPhysicalAddress) -> Self {
+        value.0
+    }
+}
+
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-freepages
 pub type EfiFreePages = extern "efiapi" fn(memory: EfiPhysicalAddress, pages: usize) -> EfiStatus;
 
@@ -63,25 +107,160 @@ pub type EfiGetMemoryMap = extern "efiapi" fn(
 ) -> EfiStatus;
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-getmemorymap
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[repr(C)]
 pub struct EfiMemoryDescriptor {
-    memory_type: EfiMemoryType,
+    // Not EfiMemoryType since the firmware may report OEM defined types.
+    memory_type: u32,
     physical_start: EfiPhysicalAddress,
     virtual_start: EfiVirtualAddress,
     number_of_pages: u64,
     attribute: EfiMemoryAttribute,
 }
 
-/// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-getmemorymap
+impl EfiMemoryDescriptor {
+    pub const PAGE_SIZE: u64 = 4096;
+
+    pub fn new(
+        memory_type: EfiMemoryType,
+        physical_start: EfiPhysicalAddress,
+        number_of_pages: u64,
+        attribute: EfiMemoryAttribute,
+    ) -> Self {
+        Self {
+            memory_type: memory_type as u32,
+            physical_start,
+            virtual_start: EfiVirtualAddress(0),
+            number_of_pages,
+            attribute,
+        }
+    }
+
+    pub fn memory_type(&self) -> Result<EfiMemoryType, u32> {
+        EfiMemoryType::try_from(self.memory_type)
+    }
+
+    pub fn physical_start(&self) -> EfiPhysicalAddress {
+        self.physical_start
+    }
+
+    pub fn virtual_start(&self) -> EfiVirtualAddress {
+        self.virtual_start
+    }
+
+    /// Number of 4 KiB pages.
+    pub fn number_of_pages(&self) -> u64 {
+        self.number_of_pages
+    }
+
+    pub fn attribute(&self) -> EfiMemoryAttribute {
+        self.attribute
+    }
+}
+
+/// Descriptors laid out with a stride of the descriptor size reported by the firmware,
+/// which may be larger than `size_of::<EfiMemoryDescriptor>()`.
+#[derive(Debug, Clone)]
+pub struct EfiMemoryDescriptors<'a> {
+    buffer: &'a [u8],
+    descriptor_size: usize,
+}
+
+impl<'a> EfiMemoryDescriptors<'a> {
+    /// # Safety
+    /// `buffer` must hold descriptors placed every `descriptor_size` bytes, aligned for [`EfiMemoryDescriptor`].
+    pub unsafe fn new(buffer: &'a [u8], descriptor_size: usize) -> Self {
+        assert!(descriptor_size >= size_of::<EfiMemoryDescriptor>());
+        Self {
+            buffer,
+            descriptor_size,
+        }
+    }
+}
+
+/// Memory map written by [`EfiBootServices::get_memory_map`].
 #[derive(Debug)]
+pub struct EfiMemoryMap<'a> {
+    buffer: &'a [u8],
+    map_key: usize,
+    descriptor_size: usize,
+    descriptor_version: u32,
+}
+
+impl<'a> EfiMemoryMap<'a> {
+    /// Identifies this snapshot of the memory map for ExitBootServices.
+    pub fn map_key(&self) -> usize {
+        self.map_key
+    }
+
+    pub fn descriptor_size(&self) -> usize {
+        self.descriptor_size
+    }
+
+    pub fn descriptor_version(&self) -> u32 {
+        self.descriptor_version
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len() / self.descriptor_size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> EfiMemoryDescriptors<'a> {
+        unsafe { EfiMemoryDescriptors::new(self.buffer, self.descriptor_size) }
+    }
+}
+
+impl<'a> Iterator for EfiMemoryDescriptors<'a> {
+    type Item = &'a EfiMemoryDescriptor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.len() < self.descriptor_size {
+            return None;
+        }
+
+        let descriptor = unsafe { &*(self.buffer.as_ptr() as *const EfiMemoryDescriptor) };
+        self.buffer = &self.buffer[self.descriptor_size..];
+        Some(descriptor)
+    }
+}
+
+/// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-getmemorymap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(transparent)]
 pub struct EfiVirtualAddress(u64);
 
-#[derive(Debug)]
+impl From<u64> for EfiVirtualAddress {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<EfiVirtualAddress> for u64 {
+    fn from(value: EfiVirtualAddress) -> Self {
+        value.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(transparent)]
 pub struct EfiMemoryAttribute(u64);
 
+impl From<u64> for EfiMemoryAttribute {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<EfiMemoryAttribute> for u64 {
+    fn from(value: EfiMemoryAttribute) -> Self {
+        value.0
+    }
+}
+
 impl core::ops::BitOr for EfiMemoryAttribute {
     type Output = Self;
 
@@ -90,6 +269,14 @@ impl core::ops::BitOr for EfiMemoryAttribute {
     }
 }
 
+impl core::ops::BitAnd for EfiMemoryAttribute {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
 impl EfiMemoryAttribute {
     /// Memory cacheability attribute: The memory region supports being configured as not cacheable.
     pub const MEMORY_UC: Self = Self(0x0000000000000001);
@@ -123,6 +310,22 @@ impl EfiMemoryAttribute {
     pub const MEMORY_ISA_VALID: Self = Self(0x4000000000000000);
     /// Defines the bits reserved for describing optional ISA-specific cacheability attributes that are not covered by the standard UEFI Memory Attributes cacheability bits (EFI_MEMORY_UC, EFI_MEMORY_WC, EFI_MEMORY_WT, EFI_MEMORY_WB and EFI_MEMORY_UCE). See Calling Conventions for further ISA-specific enumeration of these bits.
     pub const MEMORY_ISA_MASK: Self = Self(0x0FFFF00000000000);
+
+    /// Attributes accepted by EFI_MEMORY_ATTRIBUTE_PROTOCOL.
+    pub const MEMORY_ACCESS_MASK: Self =
+        Self(Self::MEMORY_RP.0 | Self::MEMORY_XP.0 | Self::MEMORY_RO.0);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn intersects(&self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
 }
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-allocatepool
@@ -134,3 +337,262 @@ pub type EfiAllocatePool = extern "efiapi" fn(
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-freepool
 pub type EfiFreePool = extern "efiapi" fn(buffer: NonNull<EfiVoid>) -> EfiStatus;
+
+impl EfiBootServices {
+    /// Returns a buffer size enough for [`EfiBootServices::get_memory_map`].
+    /// It has room for a few more descriptors, since allocating the buffer itself may split a region.
+    pub fn memory_map_size(&self) -> EfiResult<usize> {
+        let mut memory_map_size = 0;
+        let mut map_key = 0;
+        let mut descriptor_size = 0;
+        let mut descriptor_version = 0;
+        let status = (self.get_memory_map)(
+            NonNull::from(&mut memory_map_size),
+            NonNull::dangling(),
+            NonNull::from(&mut map_key),
+            NonNull::from(&mut descriptor_size),
+            NonNull::from(&mut descriptor_version),
+        );
+
+        if status != EfiStatus::BUFFER_TOO_SMALL {
+            return Err("Failed to get memory map size");
+        }
+
+        Ok(memory_map_size + 4 * descriptor_size)
+    }
+
+    /// Writes the current memory map into `buffer`, which must be 8-byte aligned.
+    pub fn get_memory_map<'b>(&self, buffer: &'b mut [u8]) -> EfiResult<EfiMemoryMap<'b>> {
+        if buffer
+            .as_ptr()
+            .align_offset(core::mem::align_of::<EfiMemoryDescriptor>())
+            != 0
+        {
+            return Err("Memory map buffer is not aligned");
+        }
+
+        let mut memory_map_size = buffer.len();
+        let mut map_key = 0;
+        let mut descriptor_size = 0;
+        let mut descriptor_version = 0;
+        let status = (self.get_memory_map)(
+            NonNull::from(&mut memory_map_size),
+            NonNull::from(&mut *buffer).cast(),
+            NonNull::from(&mut map_key),
+            NonNull::from(&mut descriptor_size),
+            NonNull::from(&mut descriptor_version),
+        );
+
+        if status == EfiStatus::BUFFER_TOO_SMALL {
+            return Err("Memory map buffer is too small");
+        }
+        if status != EfiStatus::SUCCESS || descriptor_size < size_of::<EfiMemoryDescriptor>() {
+            return Err("Failed to get memory map");
+        }
+
+        Ok(EfiMemoryMap {
+            buffer: &buffer[..memory_map_size],
+            map_key,
+            descriptor_size,
+            descriptor_version,
+        })
+    }
+}
+
+/// Buffer allocated from pool by the firmware. It is released with FreePool when dropped.
+pub struct PoolBox<'a, T: ?Sized> {
+    boot_services: &'a EfiBootServices,
+    ptr: NonNull<T>,
+}
+
+impl<'a, T> PoolBox<'a, T> {
+    /// Moves `value` into a buffer allocated with AllocatePool.
+    pub fn new(
+        boot_services: &'a EfiBootServices,
+        pool_type: EfiMemoryType,
+        value: T,
+    ) -> EfiResult<Self> {
+        let mut buffer = core::ptr::null_mut();
+        let status =
+            (boot_services.allocate_pool)(pool_type, size_of::<T>(), NonNull::from(&mut buffer));
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to allocate pool");
+        }
+
+        // AllocatePool returns 8-byte aligned buffers.
+        let ptr = NonNull::new(buffer as *mut T).ok_or("Failed to allocate pool")?;
+        assert!(core::mem::align_of::<T>() <= 8);
+        unsafe { ptr.as_ptr().write(value) };
+        Ok(Self { boot_services, ptr })
+    }
+}
+
+impl<'a, T: Copy> PoolBox<'a, [T]> {
+    /// Allocates `len` elements initialized with `value`.
+    pub fn new_slice(
+        boot_services: &'a EfiBootServices,
+        pool_type: EfiMemoryType,
+        len: usize,
+        value: T,
+    ) -> EfiResult<Self> {
+        let size = size_of::<T>()
+            .checked_mul(len)
+            .ok_or("Failed to allocate pool")?;
+        let mut buffer = core::ptr::null_mut();
+        // AllocatePool may fail for size 0, so that at least 1 byte is requested.
+        let status =
+            (boot_services.allocate_pool)(pool_type, size.max(1), NonNull::from(&mut buffer));
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to allocate pool");
+        }
+
+        let ptr = NonNull::new(buffer as *mut T).ok_or("Failed to allocate pool")?;
+        assert!(core::mem::align_of::<T>() <= 8);
+        for i in 0..len {
+            unsafe { ptr.as_ptr().add(i).write(value) };
+        }
+        Ok(Self {
+            boot_services,
+            ptr: NonNull::slice_from_raw_parts(ptr, len),
+        })
+    }
+}
+
+impl<'a, T: ?Sized> PoolBox<'a, T> {
+    /// # Safety
+    /// `ptr` must be allocated with AllocatePool of `boot_services` and must not be freed elsewhere.
+    pub unsafe fn from_raw(boot_services: &'a EfiBootServices, ptr: NonNull<T>) -> Self {
+        Self { boot_services, ptr }
+    }
+
+    pub fn as_ptr(&self) -> NonNull<T> {
+        self.ptr
+    }
+
+    /// Gives up the ownership without freeing the buffer.
+    pub fn into_raw(self) -> NonNull<T> {
+        let ptr = self.ptr;
+        core::mem::forget(self);
+        ptr
+    }
+}
+
+impl<'a, T: ?Sized> Deref for PoolBox<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for PoolBox<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for PoolBox<'a, T> {
+    fn drop(&mut self) {
+        unsafe { core::ptr::drop_in_place(self.ptr.as_ptr()) };
+        let _ = (self.boot_services.free_pool)(self.ptr.cast());
+    }
+}
+
+impl<'a, T: ?Sized + core::fmt::Debug> core::fmt::Debug for PoolBox<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockFirmware, DESCRIPTOR_SIZE};
+
+    #[test]
+    fn pool_box() {
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+
+        let value = PoolBox::new(boot_services, EfiMemoryType::EfiLoaderData, 42u64).unwrap();
+        let mut slice =
+            PoolBox::new_slice(boot_services, EfiMemoryType::EfiLoaderData, 3, 7u8).unwrap();
+        slice[1] = 8;
+        assert_eq!(*value, 42);
+        assert_eq!(*slice, [7, 8, 7]);
+        assert_eq!(firmware.pool_allocations(), 2);
+
+        drop(value);
+        drop(slice);
+        assert_eq!(firmware.pool_allocations(), 0);
+    }
+
+    #[test]
+    fn memory_map() {
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+        firmware.set_memory_map(std::vec![
+            EfiMemoryDescriptor::new(
+                EfiMemoryType::EfiConventionalMemory,
+                EfiPhysicalAddress(0x1000),
+                0x9F,
+                EfiMemoryAttribute::MEMORY_WB,
+            ),
+            EfiMemoryDescriptor::new(
+                EfiMemoryType::EfiLoaderCode,
+                EfiPhysicalAddress(0x100000),
+                0x10,
+                EfiMemoryAttribute::MEMORY_WB,
+            ),
+        ]);
+
+        let mut buffer = [0u64; 64];
+        let buffer = unsafe {
+            core::slice::from_raw_parts_mut(
+                buffer.as_mut_ptr().cast::<u8>(),
+                core::mem::size_of_val(&buffer),
+            )
+        };
+        assert!(boot_services.memory_map_size().unwrap() <= buffer.len());
+        let memory_map = boot_services.get_memory_map(buffer).unwrap();
+        assert_eq!(memory_map.descriptor_size(), DESCRIPTOR_SIZE);
+        assert_eq!(memory_map.len(), 2);
+
+        let descriptors: std::vec::Vec<_> = memory_map.iter().collect();
+        assert_eq!(
+            descriptors[1].memory_type(),
+            Ok(EfiMemoryType::EfiLoaderCode)
+        );
+        assert_eq!(u64::from(descriptors[1].physical_start()), 0x100000);
+        assert_eq!(descriptors[1].number_of_pages(), 0x10);
+    }
+
+    #[test]
+    fn exit_boot_services_with_stale_map_key() {
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+
+        let mut buffer = [0u64; 64];
+        let buffer = unsafe {
+            core::slice::from_raw_parts_mut(
+                buffer.as_mut_ptr().cast::<u8>(),
+                core::mem::size_of_val(&buffer),
+            )
+        };
+        let map_key = boot_services.get_memory_map(buffer).unwrap().map_key();
+
+        // Allocating changes the memory map, so the key no longer matches.
+        let value = PoolBox::new(boot_services, EfiMemoryType::EfiLoaderData, 0u8).unwrap();
+        let result = unsafe { boot_services.exit_boot_services(firmware.image_handle(), map_key) };
+        assert!(result.is_err());
+        assert!(!firmware.exited_boot_services());
+        drop(value);
+
+        let map_key = boot_services.get_memory_map(buffer).unwrap().map_key();
+        let result = unsafe { boot_services.exit_boot_services(firmware.image_handle(), map_key) };
+        assert!(result.is_ok());
+        assert!(firmware.exited_boot_services());
+    }
+}
@@ -1,7 +1,7 @@
 //! REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#miscellaneous-boot-services
 
-use crate::{EfiGuid, EfiStatus, EfiVoid, U16Str};
-use core::ptr::NonNull;
+use crate::{EfiBootServices, EfiGuid, EfiResult, EfiStatus, EfiVoid, U16Str};
+use core::{ptr::NonNull, time::Duration};
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-setwatchdogtimer
 pub type EfiSetWatchdogTimer = extern "efiapi" fn(
@@ -31,3 +31,123 @@ pub type EfiInstallConfigurationTable =
 /// https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-calculatecrc32
 pub type EfiCalculateCrc32 =
     extern "efiapi" fn(data: NonNull<EfiVoid>, data_size: usize, crc32: NonNull<u32>) -> EfiStatus;
+
+impl EfiBootServices {
+    /// Disables the watchdog timer.
+    /// The firmware arms it for 5 minutes before starting a boot option, after which the platform is reset.
+    pub fn disable_watchdog(&self) -> EfiResult<()> {
+        let status = (self.set_watchdog_timer)(0, 0, 0, None);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to disable watchdog timer");
+        }
+
+        Ok(())
+    }
+
+    /// Rearms the watchdog timer with `timeout`, rounded up to seconds.
+    /// `watchdog_code` must be greater than 0xFFFF since lower codes are reserved for the firmware.
+    /// `reason` is logged by the firmware when the watchdog fires.
+    pub fn set_watchdog(
+        &self,
+        timeout: Duration,
+        watchdog_code: u64,
+        reason: &U16Str,
+    ) -> EfiResult<()> {
+        if timeout.is_zero() {
+            return Err("Watchdog timeout must not be zero");
+        }
+        if watchdog_code <= 0xFFFF {
+            return Err("Watchdog codes up to 0xFFFF are reserved for the firmware");
+        }
+
+        let seconds = timeout.as_secs() + u64::from(timeout.subsec_nanos() != 0);
+        // WatchdogData is a null-terminated string and DataSize counts its terminator.
+        let data_size = (reason.len() + 1) * core::mem::size_of::<u16>();
+        let status = (self.set_watchdog_timer)(
+            usize::try_from(seconds).unwrap_or(usize::MAX),
+            watchdog_code,
+            data_size,
+            Some(NonNull::from(reason)),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to set watchdog timer");
+        }
+
+        Ok(())
+    }
+
+    /// Busy-waits for at least `duration`, rounded up to microseconds.
+    pub fn stall(&self, duration: Duration) -> EfiResult<()> {
+        let microseconds = duration.as_micros() + u128::from(duration.subsec_nanos() % 1000 != 0);
+        let microseconds = usize::try_from(microseconds).unwrap_or(usize::MAX);
+        let status = (self.stall)(microseconds);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to stall");
+        }
+
+        Ok(())
+    }
+
+    pub fn calculate_crc32(&self, data: &[u8]) -> EfiResult<u32> {
+        // CalculateCrc32 rejects empty data with INVALID_PARAMETER.
+        if data.is_empty() {
+            return Ok(0);
+        }
+
+        let mut crc32 = 0;
+        let status = (self.calcurate_crc32)(
+            NonNull::from(data).cast(),
+            data.len(),
+            NonNull::from(&mut crc32),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to calculate crc32");
+        }
+
+        Ok(crc32)
+    }
+
+    /// Returns a platform counter that increases by each call.
+    /// The upper 32 bits only change across platform resets or on overflow of the lower 32 bits.
+    pub fn get_next_monotonic_count(&self) -> EfiResult<u64> {
+        let mut count = 0;
+        let status = (self.get_next_monotonic_count)(NonNull::from(&mut count));
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to get next monotonic count");
+        }
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::MockFirmware;
+
+    #[test]
+    fn stall_rounds_up() {
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+
+        boot_services.stall(Duration::from_nanos(500)).unwrap();
+        assert_eq!(firmware.stalled(), Duration::from_micros(1));
+        boot_services.stall(Duration::from_micros(20)).unwrap();
+        assert_eq!(firmware.stalled(), Duration::from_micros(21));
+        boot_services.stall(Duration::from_nanos(2_001)).unwrap();
+        assert_eq!(firmware.stalled(), Duration::from_micros(24));
+    }
+
+    #[test]
+    fn crc32_of_empty_data() {
+        let firmware = MockFirmware::new();
+        // The firmware rejects empty data, for which the CRC32 is 0 anyway.
+        assert_eq!(firmware.boot_services().calculate_crc32(b""), Ok(0));
+        assert_eq!(crate::crc32::crc32(b""), 0);
+    }
+}
@@ -1,9 +1,20 @@
 //! REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#protocol-handler-services
 
 use crate::{
-    protocol::device_path::EfiDevicePathProtocol, EfiEvent, EfiGuid, EfiHandle, EfiStatus, EfiVoid,
+    protocol::{device_path::EfiDevicePathProtocol, Protocol},
+    EfiBootServices, EfiEvent, EfiGuid, EfiHandle, EfiResult, EfiStatus, EfiTpl, EfiVoid,
 };
-use core::ptr::NonNull;
+use core::{
+    async_iter::AsyncIterator,
+    cell::Cell,
+    marker::PhantomData,
+    mem::{size_of, MaybeUninit},
+    pin::Pin,
+    ptr::NonNull,
+    task::{Context, Poll, Waker},
+};
+
+use super::{event::EfiEventType, EfiMemoryType, PoolBox};
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-installprotocolinterface
 pub type EfiInstallProtocolInterface = extern "efiapi" fn(
@@ -112,6 +123,48 @@ impl EfiOpenProtocolAttributes {
 
     /// Used by a driver to gain exclusive access to a protocol interface. If any other drivers have the protocol interface opened with an attribute of BY_DRIVER, then an attempt will be made to remove them with DisconnectController().
     pub const BY_DRIVER_OR_EXCLUSIVE: Self = Self(Self::BY_DRIVER.0 | Self::EXCLUSIVE.0);
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl core::fmt::Display for EfiOpenProtocolAttributes {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        const NAMES: [(EfiOpenProtocolAttributes, &str); 6] = [
+            (
+                EfiOpenProtocolAttributes::BY_HANDLE_PROTOCOL,
+                "BY_HANDLE_PROTOCOL",
+            ),
+            (EfiOpenProtocolAttributes::GET_PROTOCOL, "GET_PROTOCOL"),
+            (EfiOpenProtocolAttributes::TEST_PROTOCOL, "TEST_PROTOCOL"),
+            (
+                EfiOpenProtocolAttributes::BY_CHILD_CONTROLLER,
+                "BY_CHILD_CONTROLLER",
+            ),
+            (EfiOpenProtocolAttributes::BY_DRIVER, "BY_DRIVER"),
+            (EfiOpenProtocolAttributes::EXCLUSIVE, "EXCLUSIVE"),
+        ];
+
+        let mut rest = self.0;
+        for (attribute, name) in NAMES {
+            if self.contains(attribute) {
+                if rest != self.0 {
+                    f.write_str("|")?;
+                }
+                f.write_str(name)?;
+                rest &= !attribute.0;
+            }
+        }
+
+        if rest == self.0 {
+            core::write!(f, "{:#x}", self.0)
+        } else if rest != 0 {
+            core::write!(f, "|{:#x}", rest)
+        } else {
+            Ok(())
+        }
+    }
 }
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-closeprotocol
@@ -131,13 +184,31 @@ pub type EfiOpenProtocolInformation = extern "efiapi" fn(
 ) -> EfiStatus;
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-openprotocolinformation
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct EfiOpenProtocolInformationEntry {
-    agent_handle: EfiHandle,
-    controller_handle: EfiHandle,
-    attributes: EfiOpenProtocolAttributes,
-    open_count: u32,
+    pub(crate) agent_handle: Option<EfiHandle>,
+    pub(crate) controller_handle: Option<EfiHandle>,
+    pub(crate) attributes: EfiOpenProtocolAttributes,
+    pub(crate) open_count: u32,
+}
+
+impl EfiOpenProtocolInformationEntry {
+    pub fn agent_handle(&self) -> Option<EfiHandle> {
+        self.agent_handle
+    }
+
+    pub fn controller_handle(&self) -> Option<EfiHandle> {
+        self.controller_handle
+    }
+
+    pub fn attributes(&self) -> EfiOpenProtocolAttributes {
+        self.attributes
+    }
+
+    pub fn open_count(&self) -> u32 {
+        self.open_count
+    }
 }
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-connectcontroller
@@ -185,3 +256,419 @@ pub type EfiInstallMultipleProtocolInterfaces =
 // REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-uninstallmultipleprotocolinterfaces
 pub type EfiUninstallMultipleProtocolInterfaces =
     extern "efiapi" fn(handle: EfiHandle, ...) -> EfiStatus;
+
+impl EfiBootServices {
+    /// Installs `interface` on `handle`. A new handle is created when `handle` is `None`.
+    pub fn install_protocol_interface<P: Protocol>(
+        &self,
+        handle: Option<EfiHandle>,
+        interface: &'static P,
+    ) -> EfiResult<EfiHandle> {
+        let mut handle = handle;
+        let status = (self.install_protocol_interface)(
+            NonNull::from(&mut handle).cast(),
+            NonNull::from(&P::GUID),
+            EfiInterfaceType::EfiNativeInterface,
+            NonNull::from(interface).cast(),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to install protocol interface");
+        }
+
+        handle.ok_or("Failed to install protocol interface")
+    }
+
+    /// Removes `interface` from `handle`. The handle is freed when no protocols remain on it.
+    pub fn uninstall_protocol_interface<P: Protocol>(
+        &self,
+        handle: EfiHandle,
+        interface: &'static P,
+    ) -> EfiResult<()> {
+        let status = (self.uninsatall_protocol_interface)(
+            handle,
+            NonNull::from(&P::GUID),
+            NonNull::from(interface).cast(),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to uninstall protocol interface");
+        }
+
+        Ok(())
+    }
+
+    /// Replaces `old_interface` on `handle` with `new_interface`.
+    /// Drivers that opened the old interface BY_DRIVER are stopped and reconnected.
+    pub fn reinstall_protocol_interface<P: Protocol>(
+        &self,
+        handle: EfiHandle,
+        old_interface: &'static P,
+        new_interface: &'static P,
+    ) -> EfiResult<()> {
+        let status = (self.reinstall_protocol_interface)(
+            handle,
+            NonNull::from(&P::GUID),
+            NonNull::from(old_interface).cast(),
+            NonNull::from(new_interface).cast(),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to reinstall protocol interface");
+        }
+
+        Ok(())
+    }
+
+    /// Connects drivers to `controller_handle`.
+    /// When `driver_image_handle` is given, only that driver is tried.
+    pub fn connect_controller(
+        &self,
+        controller_handle: EfiHandle,
+        driver_image_handle: Option<EfiHandle>,
+        remaining_device_path: Option<&EfiDevicePathProtocol>,
+        recursive: bool,
+    ) -> EfiResult<()> {
+        // DriverImageHandle is a null-terminated list of handles.
+        let mut driver_image_handles = [driver_image_handle, None];
+        let status = (self.connect_controller)(
+            controller_handle,
+            driver_image_handle.map(|_| NonNull::from(&mut driver_image_handles).cast()),
+            remaining_device_path.map(NonNull::from),
+            recursive,
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to connect controller");
+        }
+
+        Ok(())
+    }
+
+    pub fn disconnect_controller(
+        &self,
+        controller_handle: EfiHandle,
+        driver_image_handle: Option<EfiHandle>,
+        child_handle: Option<EfiHandle>,
+    ) -> EfiResult<()> {
+        let status =
+            (self.disconnect_controller)(controller_handle, driver_image_handle, child_handle);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to disconnect controller");
+        }
+
+        Ok(())
+    }
+}
+
+/// Handles on which a protocol `P` has been installed since registration.
+/// Created by [`EfiBootServices::register_protocol_notify`].
+pub struct ProtocolNotify<'a, P: Protocol> {
+    boot_services: &'a EfiBootServices,
+    event: EfiEvent,
+    registration: NonNull<EfiVoid>,
+    waker: PoolBox<'a, Cell<Option<Waker>>>,
+    _protocol: PhantomData<&'a P>,
+}
+
+impl<'a, P: Protocol> ProtocolNotify<'a, P> {
+    /// Returns the next handle without waiting.
+    pub fn try_next(&mut self) -> Option<EfiHandle> {
+        let mut handle = MaybeUninit::<EfiHandle>::uninit();
+        let mut buffer_size = size_of::<EfiHandle>();
+        // Each call with ByRegisterNotify returns one handle not yet returned for the registration.
+        let status = (self.boot_services.locate_handle)(
+            EfiLocateSearchType::ByRegisterNotify,
+            None,
+            Some(self.registration),
+            NonNull::from(&mut buffer_size),
+            NonNull::from(&mut handle).cast(),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return None;
+        }
+
+        Some(unsafe { handle.assume_init() })
+    }
+
+    /// Waits for the next handle.
+    pub async fn next(&mut self) -> EfiHandle {
+        core::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx))
+            .await
+            .unwrap()
+    }
+}
+
+impl<'a, P: Protocol> AsyncIterator for ProtocolNotify<'a, P> {
+    type Item = EfiHandle;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(handle) = self.try_next() {
+            return Poll::Ready(Some(handle));
+        }
+
+        // The notify function runs at TPL_CALLBACK. Block it while the waker is stored,
+        // so that an installation between the check and the store is not missed.
+        let old_tpl = self.boot_services.raise_tpl(EfiTpl::CALLBACK);
+        let handle = self.try_next();
+        if handle.is_none() {
+            self.waker.set(Some(cx.waker().clone()));
+        }
+        self.boot_services.restore_tpl(old_tpl);
+
+        match handle {
+            Some(handle) => Poll::Ready(Some(handle)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<'a, P: Protocol> Drop for ProtocolNotify<'a, P> {
+    fn drop(&mut self) {
+        // Closing the event also cancels the registration.
+        let _ = self.boot_services.close_event(self.event);
+    }
+}
+
+extern "efiapi" fn wake_protocol_notify(_event: EfiEvent, context: NonNull<EfiVoid>) {
+    let waker = unsafe { context.cast::<Cell<Option<Waker>>>().as_ref() };
+    if let Some(waker) = waker.take() {
+        waker.wake();
+    }
+}
+
+impl EfiBootServices {
+    /// Registers for notification of new instances of `P`.
+    pub fn register_protocol_notify<P: Protocol>(&self) -> EfiResult<ProtocolNotify<'_, P>> {
+        let waker = PoolBox::new(self, EfiMemoryType::EfiLoaderData, Cell::new(None))?;
+        let event = self.create_event(
+            EfiEventType::NOTIFY_SIGNAL,
+            EfiTpl::CALLBACK,
+            Some(wake_protocol_notify),
+            Some(waker.as_ptr().cast()),
+        )?;
+
+        let mut registration = core::ptr::null_mut();
+        let status = (self.register_protocol_notify)(
+            NonNull::from(&P::GUID),
+            event,
+            NonNull::from(&mut registration),
+        );
+
+        let Some(registration) =
+            NonNull::new(registration).filter(|_| status == EfiStatus::SUCCESS)
+        else {
+            let _ = self.close_event(event);
+            return Err("Failed to register protocol notify");
+        };
+
+        Ok(ProtocolNotify {
+            boot_services: self,
+            event,
+            registration,
+            waker,
+            _protocol: PhantomData,
+        })
+    }
+}
+
+impl EfiBootServices {
+    /// Returns handles that match the search. `protocol` is required for [`EfiLocateSearchType::ByProtocol`].
+    pub fn locate_handle_buffer(
+        &self,
+        search_type: EfiLocateSearchType,
+        protocol: Option<&EfiGuid>,
+    ) -> EfiResult<PoolBox<'_, [EfiHandle]>> {
+        let mut no_handles = 0;
+        let mut buffer = core::ptr::null_mut();
+        let status = (self.locate_handle_buffer)(
+            search_type,
+            protocol.map(NonNull::from),
+            None,
+            NonNull::from(&mut no_handles),
+            NonNull::from(&mut buffer),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to locate handle buffer");
+        }
+
+        let buffer = NonNull::new(buffer).ok_or("Failed to locate handle buffer")?;
+        Ok(unsafe { PoolBox::from_raw(self, NonNull::slice_from_raw_parts(buffer, no_handles)) })
+    }
+
+    /// Returns GUIDs of the protocols installed on `handle`.
+    /// The GUIDs themselves are owned by the firmware and stay valid while the protocols are installed.
+    pub fn protocols_per_handle(
+        &self,
+        handle: EfiHandle,
+    ) -> EfiResult<PoolBox<'_, [NonNull<EfiGuid>]>> {
+        let mut protocol_buffer = core::ptr::null_mut();
+        let mut protocol_buffer_count = 0;
+        let status = (self.protocols_per_handle)(
+            handle,
+            NonNull::from(&mut protocol_buffer),
+            NonNull::from(&mut protocol_buffer_count),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to retrieve protocols per handle");
+        }
+
+        let buffer = NonNull::new(protocol_buffer as *mut NonNull<EfiGuid>)
+            .ok_or("Failed to retrieve protocols per handle")?;
+        Ok(unsafe {
+            PoolBox::from_raw(
+                self,
+                NonNull::slice_from_raw_parts(buffer, protocol_buffer_count),
+            )
+        })
+    }
+
+    /// Returns the agents that have `protocol` on `handle` open.
+    pub fn open_protocol_information(
+        &self,
+        handle: EfiHandle,
+        protocol: &EfiGuid,
+    ) -> EfiResult<PoolBox<'_, [EfiOpenProtocolInformationEntry]>> {
+        let mut entry_buffer = core::ptr::null_mut();
+        let mut entry_count = 0;
+        let status = (self.open_protocol_information)(
+            handle,
+            NonNull::from(protocol),
+            NonNull::from(&mut entry_buffer),
+            NonNull::from(&mut entry_count),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to retrieve open protocol information");
+        }
+
+        let buffer =
+            NonNull::new(entry_buffer).ok_or("Failed to retrieve open protocol information")?;
+        Ok(unsafe { PoolBox::from_raw(self, NonNull::slice_from_raw_parts(buffer, entry_count)) })
+    }
+
+    /// Returns the first interface of `P` found in the handle database.
+    pub fn locate_protocol<P: Protocol>(&self) -> EfiResult<&P> {
+        let mut interface = core::ptr::null_mut();
+        let status =
+            (self.locate_protocol)(NonNull::from(&P::GUID), None, NonNull::from(&mut interface));
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to locate protocol");
+        }
+
+        let interface = NonNull::new(interface).ok_or("Failed to locate protocol")?;
+        Ok(unsafe { interface.cast::<P>().as_ref() })
+    }
+
+    /// Returns the interface of `P` installed on `handle`.
+    pub fn handle_protocol<P: Protocol>(&self, handle: EfiHandle) -> EfiResult<&P> {
+        let mut interface = core::ptr::null_mut();
+        let status = (self.handle_protocol)(
+            handle,
+            NonNull::from(&P::GUID),
+            NonNull::from(&mut interface),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to handle protocol");
+        }
+
+        let interface = NonNull::new(interface).ok_or("Failed to handle protocol")?;
+        Ok(unsafe { interface.cast::<P>().as_ref() })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::MockFirmware;
+
+    struct TestProtocol(u32);
+
+    unsafe impl Protocol for TestProtocol {
+        const GUID: EfiGuid = EfiGuid(
+            0x6b2c8a37,
+            0x1f4e,
+            0x4d0a,
+            [0x9c, 0x51, 0x2e, 0x7a, 0x40, 0xd3, 0x88, 0x16],
+        );
+    }
+
+    static FIRST: TestProtocol = TestProtocol(1);
+    static SECOND: TestProtocol = TestProtocol(2);
+
+    #[test]
+    fn install_and_locate() {
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+        assert!(boot_services.locate_protocol::<TestProtocol>().is_err());
+
+        let handle = boot_services
+            .install_protocol_interface(None, &FIRST)
+            .unwrap();
+        assert_eq!(
+            boot_services.locate_protocol::<TestProtocol>().unwrap().0,
+            1
+        );
+        assert_eq!(
+            boot_services
+                .handle_protocol::<TestProtocol>(handle)
+                .unwrap()
+                .0,
+            1
+        );
+
+        let handles = boot_services
+            .locate_handle_buffer(EfiLocateSearchType::ByProtocol, Some(&TestProtocol::GUID))
+            .unwrap();
+        assert_eq!(handles.len(), 1);
+        assert_eq!(handles[0].as_ptr(), handle.as_ptr());
+
+        let protocols = boot_services.protocols_per_handle(handle).unwrap();
+        assert_eq!(protocols.len(), 1);
+        assert_eq!(unsafe { protocols[0].as_ref() }, &TestProtocol::GUID);
+
+        boot_services
+            .reinstall_protocol_interface(handle, &FIRST, &SECOND)
+            .unwrap();
+        assert_eq!(
+            boot_services.locate_protocol::<TestProtocol>().unwrap().0,
+            2
+        );
+
+        boot_services
+            .uninstall_protocol_interface(handle, &SECOND)
+            .unwrap();
+        assert!(boot_services
+            .handle_protocol::<TestProtocol>(handle)
+            .is_err());
+        drop((handles, protocols));
+        assert_eq!(firmware.pool_allocations(), 0);
+    }
+
+    #[test]
+    fn protocol_notify() {
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+
+        let mut notify = boot_services
+            .register_protocol_notify::<TestProtocol>()
+            .unwrap();
+        assert!(notify.try_next().is_none());
+
+        let first = boot_services
+            .install_protocol_interface(None, &FIRST)
+            .unwrap();
+        let second = boot_services
+            .install_protocol_interface(None, &SECOND)
+            .unwrap();
+        assert_eq!(notify.try_next().unwrap().as_ptr(), first.as_ptr());
+        assert_eq!(notify.try_next().unwrap().as_ptr(), second.as_ptr());
+        assert!(notify.try_next().is_none());
+    }
+}
@@ -1,9 +1,20 @@
 //! REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#event-timer-and-task-priority-services
 
-use crate::EfiTpl;
+use crate::{EfiBootServices, EfiTpl};
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-raisetpl
 pub type EfiRaiseTpl = extern "efiapi" fn(new_tpl: EfiTpl) -> EfiTpl;
 
 /// REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html?highlight=efi_raise_tpl#efi-boot-services-restoretpl
 pub type EfiRestoreTpl = extern "efiapi" fn(old_tpl: EfiTpl);
+
+impl EfiBootServices {
+    /// Raises the task priority level and returns the previous one to pass to [`EfiBootServices::restore_tpl`].
+    pub fn raise_tpl(&self, new_tpl: EfiTpl) -> EfiTpl {
+        (self.raise_tpl)(new_tpl)
+    }
+
+    pub fn restore_tpl(&self, old_tpl: EfiTpl) {
+        (self.restore_tpl)(old_tpl)
+    }
+}
@@ -1 +1,65 @@
-pub struct EfiConfigurationTable {}
+pub mod memory_attributes_table;
+
+use crate::{EfiGuid, EfiVoid};
+
+pub use memory_attributes_table::EfiMemoryAttributesTable;
+
+// REF: https://uefi.org/specs/UEFI/2.10/04_EFI_System_Table.html#efi-configuration-table
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiConfigurationTable {
+    vendor_guid: EfiGuid,
+    vendor_table: *mut EfiVoid,
+}
+
+impl EfiConfigurationTable {
+    pub fn vendor_guid(&self) -> &EfiGuid {
+        &self.vendor_guid
+    }
+
+    /// Address of the table. It is a physical address which is identity-mapped during boot services.
+    pub fn vendor_table(&self) -> *mut EfiVoid {
+        self.vendor_table
+    }
+}
+
+// REF: https://uefi.org/specs/UEFI/2.10/04_EFI_System_Table.html#industry-standard-configuration-tables
+pub mod guid {
+    use crate::EfiGuid;
+
+    pub const EFI_ACPI_20_TABLE_GUID: EfiGuid = EfiGuid(
+        0x8868e871,
+        0xe4f1,
+        0x11d3,
+        [0xbc, 0x22, 0x00, 0x80, 0xc7, 0x3c, 0x88, 0x81],
+    );
+
+    pub const ACPI_TABLE_GUID: EfiGuid = EfiGuid(
+        0xeb9d2d30,
+        0x2d88,
+        0x11d3,
+        [0x9a, 0x16, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d],
+    );
+
+    pub const SMBIOS_TABLE_GUID: EfiGuid = EfiGuid(
+        0xeb9d2d31,
+        0x2d88,
+        0x11d3,
+        [0x9a, 0x16, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d],
+    );
+
+    pub const SMBIOS3_TABLE_GUID: EfiGuid = EfiGuid(
+        0xf2fd1544,
+        0x9794,
+        0x4a2c,
+        [0x99, 0x2e, 0xe5, 0xbb, 0xcf, 0x20, 0xe3, 0x94],
+    );
+
+    // REF: https://uefi.org/specs/UEFI/2.10/04_EFI_System_Table.html#efi-memory-attributes-table
+    pub const EFI_MEMORY_ATTRIBUTES_TABLE_GUID: EfiGuid = EfiGuid(
+        0xdcfa911d,
+        0x26eb,
+        0x469f,
+        [0xa2, 0x20, 0x38, 0xb7, 0xdc, 0x46, 0x12, 0x20],
+    );
+}
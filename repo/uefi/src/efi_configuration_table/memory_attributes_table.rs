@@ -0,0 +1,161 @@
+//! REF: https://uefi.org/specs/UEFI/2.10/04_EFI_System_Table.html#efi-memory-attributes-table
+
+use core::mem::size_of;
+
+use crate::efi_boot_services::{EfiMemoryAttribute, EfiMemoryDescriptor, EfiMemoryDescriptors};
+
+/// Permissions of the runtime services images, published by the firmware as a configuration table.
+/// Entries are runtime code/data descriptors whose attributes only carry RO and XP.
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiMemoryAttributesTable {
+    version: u32,
+    number_of_entries: u32,
+    descriptor_size: u32,
+    flags: u32,
+}
+
+impl EfiMemoryAttributesTable {
+    /// Runtime code is compiled with forward control flow guard (e.g. IBT).
+    pub const RT_FORWARD_CONTROL_FLOW_GUARD: u32 = 0x1;
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn number_of_entries(&self) -> usize {
+        self.number_of_entries as usize
+    }
+
+    pub fn descriptor_size(&self) -> usize {
+        self.descriptor_size as usize
+    }
+
+    /// Reserved in version 1.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Empty if the reported descriptor size cannot hold a descriptor.
+    pub fn entries(&self) -> EfiMemoryDescriptors<'_> {
+        if self.descriptor_size() < size_of::<EfiMemoryDescriptor>() {
+            return unsafe { EfiMemoryDescriptors::new(&[], size_of::<EfiMemoryDescriptor>()) };
+        }
+
+        unsafe {
+            let first = (self as *const Self).add(1) as *const u8;
+            let buffer = core::slice::from_raw_parts(
+                first,
+                self.number_of_entries() * self.descriptor_size(),
+            );
+            EfiMemoryDescriptors::new(buffer, self.descriptor_size())
+        }
+    }
+
+    /// Returns the attributes of the entry containing `address`.
+    /// Entries extending past the end of the address space are malformed and skipped.
+    pub fn attribute_of(&self, address: u64) -> Option<EfiMemoryAttribute> {
+        self.entries()
+            .find(|entry| {
+                let start = u64::from(entry.physical_start());
+                let end = entry
+                    .number_of_pages()
+                    .checked_mul(EfiMemoryDescriptor::PAGE_SIZE)
+                    .and_then(|size| start.checked_add(size));
+                end.is_some_and(|end| (start..end).contains(&address))
+            })
+            .map(|entry| entry.attribute() & EfiMemoryAttribute::MEMORY_ACCESS_MASK)
+    }
+}
+
+const _: () = assert!(size_of::<EfiMemoryAttributesTable>() == 16);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::efi_boot_services::EfiMemoryType;
+
+    #[repr(C, align(8))]
+    struct Aligned<const N: usize>([u8; N]);
+
+    fn descriptor(memory_type: u32, start: u64, pages: u64, attribute: u64) -> [u8; 48] {
+        let mut bytes = [0; 48];
+        bytes[0..4].copy_from_slice(&memory_type.to_le_bytes());
+        bytes[8..16].copy_from_slice(&start.to_le_bytes());
+        bytes[16..24].copy_from_slice(&start.to_le_bytes());
+        bytes[24..32].copy_from_slice(&pages.to_le_bytes());
+        bytes[32..40].copy_from_slice(&attribute.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn entries() {
+        const RUNTIME: u64 = 0x8000_0000_0000_0000;
+        let mut table = Aligned([0; 16 + 48 * 2]);
+        table.0[0..4].copy_from_slice(&1u32.to_le_bytes());
+        table.0[4..8].copy_from_slice(&2u32.to_le_bytes());
+        table.0[8..12].copy_from_slice(&48u32.to_le_bytes());
+        table.0[16..64].copy_from_slice(&descriptor(5, 0x1000, 2, RUNTIME | 0x20000));
+        table.0[64..112].copy_from_slice(&descriptor(6, 0x3000, 1, RUNTIME | 0x4000));
+
+        let table = unsafe { &*(table.0.as_ptr() as *const EfiMemoryAttributesTable) };
+        assert_eq!(table.entries().count(), 2);
+
+        let code = table.entries().next().unwrap();
+        assert_eq!(
+            code.memory_type(),
+            Ok(EfiMemoryType::EfiRuntimeServicesCode)
+        );
+        assert!(code
+            .attribute()
+            .contains(EfiMemoryAttribute::MEMORY_RUNTIME));
+
+        assert_eq!(
+            table.attribute_of(0x2fff),
+            Some(EfiMemoryAttribute::MEMORY_RO)
+        );
+        assert_eq!(
+            table.attribute_of(0x3000),
+            Some(EfiMemoryAttribute::MEMORY_XP)
+        );
+        assert_eq!(table.attribute_of(0x4000), None);
+    }
+
+    #[test]
+    fn overflowing_entries() {
+        const RUNTIME: u64 = 0x8000_0000_0000_0000;
+        let mut table = Aligned([0; 16 + 48 * 3]);
+        table.0[0..4].copy_from_slice(&1u32.to_le_bytes());
+        table.0[4..8].copy_from_slice(&3u32.to_le_bytes());
+        table.0[8..12].copy_from_slice(&48u32.to_le_bytes());
+        // The size in bytes overflows, then the end address does.
+        table.0[16..64].copy_from_slice(&descriptor(5, 0x1000, u64::MAX, RUNTIME | 0x20000));
+        table.0[64..112].copy_from_slice(&descriptor(
+            5,
+            0xFFFF_FFFF_FFFF_F000,
+            2,
+            RUNTIME | 0x20000,
+        ));
+        table.0[112..160].copy_from_slice(&descriptor(6, 0x1000, 1, RUNTIME | 0x4000));
+
+        let table = unsafe { &*(table.0.as_ptr() as *const EfiMemoryAttributesTable) };
+        assert_eq!(
+            table.attribute_of(0x1000),
+            Some(EfiMemoryAttribute::MEMORY_XP)
+        );
+        assert_eq!(table.attribute_of(0xFFFF_FFFF_FFFF_F800), None);
+    }
+
+    #[test]
+    fn short_descriptor_size() {
+        let mut table = Aligned([0; 16 + 48]);
+        table.0[0..4].copy_from_slice(&1u32.to_le_bytes());
+        table.0[4..8].copy_from_slice(&1u32.to_le_bytes());
+        table.0[8..12].copy_from_slice(&8u32.to_le_bytes());
+        table.0[16..64].copy_from_slice(&descriptor(5, 0x1000, 1, 0x20000));
+
+        let table = unsafe { &*(table.0.as_ptr() as *const EfiMemoryAttributesTable) };
+        assert_eq!(table.entries().count(), 0);
+        assert_eq!(table.attribute_of(0x1000), None);
+    }
+}
@@ -11,9 +11,9 @@ impl From<u32> for EfiRevision {
     }
 }
 
-impl Into<u32> for EfiRevision {
-    fn into(self) -> u32 {
-        self.value
+impl From<EfiRevision> for u32 {
+    fn from(value: EfiRevision) -> Self {
+        value.value
     }
 }
 
@@ -1 +1,65 @@
-pub struct EfiRuntimeServices {}
+pub mod miscellaneous;
+pub mod time;
+pub mod variable;
+pub mod virtual_memory;
+
+use crate::{
+    efi_runtime_services::{
+        miscellaneous::{
+            EfiGetNextHighMonotonicCount, EfiQueryCapsuleCapabilities, EfiResetSystem,
+            EfiUpdateCapsule,
+        },
+        time::{EfiGetTime, EfiGetWakeupTime, EfiSetTime, EfiSetWakeupTime},
+        variable::{EfiGetNextVariableName, EfiGetVariable, EfiQueryVariableInfo, EfiSetVariable},
+        virtual_memory::{EfiConvertPointer, EfiSetVirtualAddressMap},
+    },
+    EfiTableHeader,
+};
+
+pub use miscellaneous::EfiResetType;
+pub use time::{EfiTime, EfiTimeCapabilities};
+
+/// REF: https://uefi.org/specs/UEFI/2.10/04_EFI_System_Table.html#efi-runtime-services
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiRuntimeServices {
+    pub(crate) hdr: EfiTableHeader,
+
+    //
+    // Time Services
+    //
+    pub(crate) get_time: EfiGetTime,
+    pub(crate) set_time: EfiSetTime,
+    pub(crate) get_wakeup_time: EfiGetWakeupTime,
+    pub(crate) set_wakeup_time: EfiSetWakeupTime,
+
+    //
+    // Virtual Memory Services
+    //
+    pub(crate) set_virtual_address_map: EfiSetVirtualAddressMap,
+    pub(crate) convert_pointer: EfiConvertPointer,
+
+    //
+    // Variable Services
+    //
+    pub(crate) get_variable: EfiGetVariable,
+    pub(crate) get_next_variable_name: EfiGetNextVariableName,
+    pub(crate) set_variable: EfiSetVariable,
+
+    //
+    // Miscellaneous Services
+    //
+    pub(crate) get_next_high_monotonic_count: EfiGetNextHighMonotonicCount,
+    pub(crate) reset_system: EfiResetSystem,
+
+    //
+    // UEFI 2.0 Capsule Services
+    //
+    pub(crate) update_capsule: EfiUpdateCapsule,
+    pub(crate) query_capsule_capabilities: EfiQueryCapsuleCapabilities,
+
+    //
+    // Miscellaneous UEFI 2.0 Service
+    //
+    pub(crate) query_variable_info: EfiQueryVariableInfo,
+}
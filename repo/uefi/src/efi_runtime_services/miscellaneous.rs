@@ -0,0 +1,41 @@
+//! REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#miscellaneous-runtime-services
+
+use core::ptr::NonNull;
+
+use crate::{efi_boot_services::EfiPhysicalAddress, EfiStatus, EfiVoid};
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#getnexthighmonotoniccount
+pub type EfiGetNextHighMonotonicCount = extern "efiapi" fn(high_count: NonNull<u32>) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#resetsystem
+pub type EfiResetSystem = extern "efiapi" fn(
+    reset_type: EfiResetType,
+    reset_status: EfiStatus,
+    data_size: usize,
+    reset_data: Option<NonNull<EfiVoid>>,
+) -> !;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#resetsystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum EfiResetType {
+    EfiResetCold,
+    EfiResetWarm,
+    EfiResetShutdown,
+    EfiResetPlatformSpecific,
+}
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#updatecapsule
+pub type EfiUpdateCapsule = extern "efiapi" fn(
+    capsule_header_array: NonNull<NonNull<EfiVoid>>,
+    capsule_count: usize,
+    scatter_gather_list: EfiPhysicalAddress,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#querycapsulecapabilities
+pub type EfiQueryCapsuleCapabilities = extern "efiapi" fn(
+    capsule_header_array: NonNull<NonNull<EfiVoid>>,
+    capsule_count: usize,
+    maximum_capsule_size: NonNull<u64>,
+    reset_type: NonNull<EfiResetType>,
+) -> EfiStatus;
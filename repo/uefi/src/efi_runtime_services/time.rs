@@ -0,0 +1,435 @@
+//! REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#time-services
+
+use core::{
+    fmt,
+    mem::{size_of, MaybeUninit},
+    ptr::NonNull,
+};
+
+use crate::{EfiResult, EfiRuntimeServices, EfiStatus};
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#gettime
+pub type EfiGetTime = extern "efiapi" fn(
+    time: NonNull<EfiTime>,
+    capabilities: Option<NonNull<EfiTimeCapabilities>>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#settime
+pub type EfiSetTime = extern "efiapi" fn(time: NonNull<EfiTime>) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#getwakeuptime
+pub type EfiGetWakeupTime = extern "efiapi" fn(
+    enabled: NonNull<bool>,
+    pending: NonNull<bool>,
+    time: NonNull<EfiTime>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#setwakeuptime
+pub type EfiSetWakeupTime =
+    extern "efiapi" fn(enable: bool, time: Option<NonNull<EfiTime>>) -> EfiStatus;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+const MIN_YEAR: u16 = 1900;
+const MAX_YEAR: u16 = 9999;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#gettime
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EfiTime {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    _pad1: u8,
+    nanosecond: u32,
+    time_zone: i16,
+    daylight: u8,
+    _pad2: u8,
+}
+
+const _: () = assert!(size_of::<EfiTime>() == 16);
+
+impl EfiTime {
+    /// `time_zone` telling that the time is local time of an unknown zone.
+    pub const UNSPECIFIED_TIMEZONE: i16 = 0x07FF;
+    /// `daylight` bit: the time is affected by daylight saving time.
+    pub const ADJUST_DAYLIGHT: u8 = 0x01;
+    /// `daylight` bit: the time has been adjusted for daylight saving time.
+    pub const IN_DAYLIGHT: u8 = 0x02;
+
+    /// Creates a UTC time.
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> EfiResult<Self> {
+        let time = Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            _pad1: 0,
+            nanosecond: 0,
+            time_zone: 0,
+            daylight: 0,
+            _pad2: 0,
+        };
+        time.validate()?;
+        Ok(time)
+    }
+
+    pub fn with_nanosecond(self, nanosecond: u32) -> EfiResult<Self> {
+        let time = Self { nanosecond, ..self };
+        time.validate()?;
+        Ok(time)
+    }
+
+    /// Replaces the zone without converting the fields. `None` makes the time local time of an unknown zone.
+    pub fn with_time_zone(self, time_zone: Option<i16>) -> EfiResult<Self> {
+        let time = Self {
+            time_zone: time_zone.unwrap_or(Self::UNSPECIFIED_TIMEZONE),
+            ..self
+        };
+        time.validate()?;
+        Ok(time)
+    }
+
+    pub fn with_daylight(self, daylight: u8) -> EfiResult<Self> {
+        let time = Self { daylight, ..self };
+        time.validate()?;
+        Ok(time)
+    }
+
+    /// 1900 - 9999
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// 1 - 12
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// 1 - 31
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// 0 - 23
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// 0 - 59
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// 0 - 59
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    /// 0 - 999,999,999
+    pub fn nanosecond(&self) -> u32 {
+        self.nanosecond
+    }
+
+    /// Offset of the local time from UTC in minutes, i.e. local time = UTC + offset as of UEFI 2.10.
+    /// `None` if the zone is unspecified.
+    pub fn time_zone(&self) -> Option<i16> {
+        (self.time_zone != Self::UNSPECIFIED_TIMEZONE).then_some(self.time_zone)
+    }
+
+    pub fn daylight(&self) -> u8 {
+        self.daylight
+    }
+
+    /// Checks every field against the range allowed by the specification.
+    pub fn validate(&self) -> EfiResult<()> {
+        if !(MIN_YEAR..=MAX_YEAR).contains(&self.year) {
+            return Err("Year is out of range");
+        }
+        if !(1..=12).contains(&self.month) {
+            return Err("Month is out of range");
+        }
+        if self.day == 0 || self.day > days_in_month(self.year, self.month) {
+            return Err("Day is out of range");
+        }
+        if self.hour > 23 || self.minute > 59 || self.second > 59 {
+            return Err("Time of day is out of range");
+        }
+        if self.nanosecond > 999_999_999 {
+            return Err("Nanosecond is out of range");
+        }
+        if !(-1440..=1440).contains(&self.time_zone) && self.time_zone != Self::UNSPECIFIED_TIMEZONE
+        {
+            return Err("Time zone is out of range");
+        }
+        if self.daylight & !(Self::ADJUST_DAYLIGHT | Self::IN_DAYLIGHT) != 0 {
+            return Err("Daylight has unknown bits");
+        }
+        Ok(())
+    }
+
+    /// Creates a UTC time from seconds since 1970-01-01T00:00:00Z.
+    pub fn from_unix_timestamp(seconds: i64, nanosecond: u32) -> EfiResult<Self> {
+        let days = seconds.div_euclid(SECONDS_PER_DAY);
+        let seconds_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        let year = u16::try_from(year)
+            .ok()
+            .filter(|year| (MIN_YEAR..=MAX_YEAR).contains(year))
+            .ok_or("Year is out of range")?;
+
+        Self::new(
+            year,
+            month,
+            day,
+            (seconds_of_day / 3600) as u8,
+            (seconds_of_day / 60 % 60) as u8,
+            (seconds_of_day % 60) as u8,
+        )?
+        .with_nanosecond(nanosecond)
+    }
+
+    /// Seconds since 1970-01-01T00:00:00Z. A time of an unspecified zone is taken as UTC.
+    pub fn to_unix_timestamp(&self) -> i64 {
+        let days = days_from_civil(i64::from(self.year), self.month, self.day);
+        let seconds = days * SECONDS_PER_DAY
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second);
+        seconds - i64::from(self.time_zone().unwrap_or(0)) * 60
+    }
+}
+
+/// ISO 8601, e.g. `2024-01-01T09:30:00.5+09:00`. The zone is omitted when unspecified.
+impl fmt::Display for EfiTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )?;
+
+        if self.nanosecond != 0 {
+            let mut fraction = self.nanosecond;
+            let mut width = 9;
+            while fraction % 10 == 0 {
+                fraction /= 10;
+                width -= 1;
+            }
+            write!(f, ".{:0width$}", fraction, width = width)?;
+        }
+
+        match self.time_zone() {
+            None => Ok(()),
+            Some(0) => write!(f, "Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                write!(f, "{}{:02}:{:02}", sign, offset / 60, offset % 60)
+            }
+        }
+    }
+}
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#gettime
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct EfiTimeCapabilities {
+    pub(crate) resolution: u32,
+    pub(crate) accuracy: u32,
+    pub(crate) sets_to_zero: bool,
+}
+
+impl EfiTimeCapabilities {
+    /// Counts per second of the real time clock, 1 for a normal PC-AT CMOS RTC.
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    /// Error rate in parts per million, times 1,000,000.
+    pub fn accuracy(&self) -> u32 {
+        self.accuracy
+    }
+
+    /// Whether SetTime clears the time below the resolution.
+    pub fn sets_to_zero(&self) -> bool {
+        self.sets_to_zero
+    }
+}
+
+impl EfiRuntimeServices {
+    /// Reads the real time clock. Fails if the firmware reports a malformed time.
+    pub fn get_time(&self) -> EfiResult<EfiTime> {
+        let mut time = MaybeUninit::<EfiTime>::uninit();
+        let status = (self.get_time)(NonNull::from(&mut time).cast(), None);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to get time");
+        }
+
+        let time = unsafe { time.assume_init() };
+        time.validate()?;
+        Ok(time)
+    }
+
+    pub fn get_time_capabilities(&self) -> EfiResult<EfiTimeCapabilities> {
+        let mut time = MaybeUninit::<EfiTime>::uninit();
+        let mut capabilities = MaybeUninit::<EfiTimeCapabilities>::uninit();
+        let status = (self.get_time)(
+            NonNull::from(&mut time).cast(),
+            Some(NonNull::from(&mut capabilities).cast()),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to get time capabilities");
+        }
+
+        Ok(unsafe { capabilities.assume_init() })
+    }
+
+    pub fn set_time(&self, time: &EfiTime) -> EfiResult<()> {
+        time.validate()?;
+
+        let mut time = *time;
+        let status = (self.set_time)(NonNull::from(&mut time));
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to set time");
+        }
+
+        Ok(())
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// REF: http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+// REF: http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u8;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u8;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn validation() {
+        assert!(EfiTime::new(2024, 2, 29, 0, 0, 0).is_ok());
+        assert!(EfiTime::new(2023, 2, 29, 0, 0, 0).is_err());
+        assert!(EfiTime::new(1899, 12, 31, 0, 0, 0).is_err());
+        assert!(EfiTime::new(2024, 13, 1, 0, 0, 0).is_err());
+        assert!(EfiTime::new(2024, 4, 31, 0, 0, 0).is_err());
+        assert!(EfiTime::new(2024, 1, 1, 24, 0, 0).is_err());
+
+        let time = EfiTime::new(2024, 1, 1, 0, 0, 0).unwrap();
+        assert!(time.with_nanosecond(1_000_000_000).is_err());
+        assert!(time.with_time_zone(Some(1441)).is_err());
+        assert!(time.with_time_zone(None).is_ok());
+        assert!(time.with_daylight(0x04).is_err());
+    }
+
+    #[test]
+    fn unix_timestamp() {
+        let epoch = EfiTime::new(1970, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(epoch.to_unix_timestamp(), 0);
+        assert_eq!(EfiTime::from_unix_timestamp(0, 0), Ok(epoch));
+
+        let time = EfiTime::new(2024, 2, 29, 12, 34, 56).unwrap();
+        assert_eq!(time.to_unix_timestamp(), 1_709_210_096);
+        assert_eq!(EfiTime::from_unix_timestamp(1_709_210_096, 0), Ok(time));
+
+        let before_epoch = EfiTime::new(1900, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(before_epoch.to_unix_timestamp(), -2_208_988_800);
+        assert_eq!(
+            EfiTime::from_unix_timestamp(-2_208_988_800, 0),
+            Ok(before_epoch)
+        );
+        assert!(EfiTime::from_unix_timestamp(-2_208_988_801, 0).is_err());
+
+        let tokyo = time.with_time_zone(Some(9 * 60)).unwrap();
+        assert_eq!(tokyo.to_unix_timestamp(), 1_709_210_096 - 9 * 3600);
+    }
+
+    #[test]
+    fn display() {
+        extern crate std;
+        use std::string::ToString;
+
+        let time = EfiTime::new(2024, 1, 2, 3, 4, 5).unwrap();
+        assert_eq!(time.to_string(), "2024-01-02T03:04:05Z");
+        assert_eq!(
+            time.with_nanosecond(500_000_000).unwrap().to_string(),
+            "2024-01-02T03:04:05.5Z"
+        );
+        assert_eq!(
+            time.with_time_zone(Some(-330)).unwrap().to_string(),
+            "2024-01-02T03:04:05-05:30"
+        );
+        assert_eq!(
+            time.with_time_zone(None).unwrap().to_string(),
+            "2024-01-02T03:04:05"
+        );
+    }
+
+    #[test]
+    fn firmware_clock() {
+        let firmware = crate::mock::MockFirmware::new();
+        let runtime_services = firmware.runtime_services();
+
+        let time = EfiTime::new(2024, 2, 29, 12, 34, 56)
+            .unwrap()
+            .with_time_zone(Some(60))
+            .unwrap();
+        runtime_services.set_time(&time).unwrap();
+        assert_eq!(runtime_services.get_time(), Ok(time));
+        assert_eq!(firmware.time(), time);
+        assert_eq!(
+            runtime_services
+                .get_time_capabilities()
+                .unwrap()
+                .resolution(),
+            1
+        );
+    }
+}
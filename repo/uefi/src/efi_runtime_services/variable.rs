@@ -0,0 +1,38 @@
+//! REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#variable-services
+
+use core::ptr::NonNull;
+
+use crate::{EfiGuid, EfiStatus, EfiVoid, U16Str};
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#getvariable
+pub type EfiGetVariable = extern "efiapi" fn(
+    variable_name: NonNull<U16Str>,
+    vendor_guid: NonNull<EfiGuid>,
+    attributes: Option<NonNull<u32>>,
+    data_size: NonNull<usize>,
+    data: Option<NonNull<EfiVoid>>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#getnextvariablename
+pub type EfiGetNextVariableName = extern "efiapi" fn(
+    variable_name_size: NonNull<usize>,
+    variable_name: NonNull<u16>,
+    vendor_guid: NonNull<EfiGuid>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#setvariable
+pub type EfiSetVariable = extern "efiapi" fn(
+    variable_name: NonNull<U16Str>,
+    vendor_guid: NonNull<EfiGuid>,
+    attributes: u32,
+    data_size: usize,
+    data: Option<NonNull<EfiVoid>>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#queryvariableinfo
+pub type EfiQueryVariableInfo = extern "efiapi" fn(
+    attributes: u32,
+    maximum_variable_storage_size: NonNull<u64>,
+    remaining_variable_storage_size: NonNull<u64>,
+    maximum_variable_size: NonNull<u64>,
+) -> EfiStatus;
@@ -0,0 +1,17 @@
+//! REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#virtual-memory-services
+
+use core::ptr::NonNull;
+
+use crate::{efi_boot_services::EfiMemoryDescriptor, EfiStatus, EfiVoid};
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#setvirtualaddressmap
+pub type EfiSetVirtualAddressMap = extern "efiapi" fn(
+    memory_map_size: usize,
+    descriptor_size: usize,
+    descriptor_version: u32,
+    virtual_map: NonNull<EfiMemoryDescriptor>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/08_Services_Runtime_Services.html#convertpointer
+pub type EfiConvertPointer =
+    extern "efiapi" fn(debug_disposition: usize, address: NonNull<*mut EfiVoid>) -> EfiStatus;
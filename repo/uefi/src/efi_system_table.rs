@@ -1,27 +1,85 @@
 use core::ptr::NonNull;
 
-use crate::efi_configuration_table::EfiConfigurationTable;
+use crate::efi_configuration_table::{guid, EfiConfigurationTable, EfiMemoryAttributesTable};
 use crate::protocol::simple_text::{SimpleTextInputProtocol, SimpleTextOutputProtocol};
 use crate::{EfiBootServices, EfiRuntimeServices};
 
 use super::u16str::U16Str;
-use super::{EfiHandle, EfiTableHeader};
+use super::{EfiGuid, EfiHandle, EfiTableHeader, EfiVoid};
 
 // REF: https://uefi.org/specs/UEFI/2.11/04_EFI_System_Table.html#id6
 #[repr(C)]
 #[derive(Debug, Clone)]
 pub struct EfiSystemTable {
-    hdr: EfiTableHeader,
-    firmware_vendor: NonNull<U16Str>,
-    firmware_revision: u32,
-    console_in_handle: EfiHandle,
-    console_in: NonNull<SimpleTextInputProtocol>,
-    console_out_handle: EfiHandle,
-    console_out: NonNull<SimpleTextOutputProtocol>,
-    standard_error_handle: EfiHandle,
-    std_err: NonNull<SimpleTextOutputProtocol>,
-    runtime_services: NonNull<EfiRuntimeServices>,
-    boot_services: NonNull<EfiBootServices>,
-    number_of_table_entries: usize,
-    efi_configuration_table: NonNull<EfiConfigurationTable>,
+    pub(crate) hdr: EfiTableHeader,
+    pub(crate) firmware_vendor: NonNull<U16Str>,
+    pub(crate) firmware_revision: u32,
+    pub(crate) console_in_handle: EfiHandle,
+    pub(crate) console_in: NonNull<SimpleTextInputProtocol>,
+    pub(crate) console_out_handle: EfiHandle,
+    pub(crate) console_out: NonNull<SimpleTextOutputProtocol>,
+    pub(crate) standard_error_handle: EfiHandle,
+    pub(crate) std_err: NonNull<SimpleTextOutputProtocol>,
+    pub(crate) runtime_services: NonNull<EfiRuntimeServices>,
+    pub(crate) boot_services: NonNull<EfiBootServices>,
+    pub(crate) number_of_table_entries: usize,
+    pub(crate) efi_configuration_table: NonNull<EfiConfigurationTable>,
+}
+
+impl EfiSystemTable {
+    pub fn header(&self) -> &EfiTableHeader {
+        &self.hdr
+    }
+
+    /// Validates the table with the CRC32 in its header. This works without boot services.
+    pub fn verify_crc32(&self) -> bool {
+        unsafe { self.hdr.verify_crc32() }
+    }
+
+    pub fn firmware_vendor(&self) -> &U16Str {
+        unsafe { self.firmware_vendor.as_ref() }
+    }
+
+    pub fn firmware_revision(&self) -> u32 {
+        self.firmware_revision
+    }
+
+    pub fn console_out(&self) -> &SimpleTextOutputProtocol {
+        unsafe { self.console_out.as_ref() }
+    }
+
+    pub fn std_err(&self) -> &SimpleTextOutputProtocol {
+        unsafe { self.std_err.as_ref() }
+    }
+
+    pub fn runtime_services(&self) -> &EfiRuntimeServices {
+        unsafe { self.runtime_services.as_ref() }
+    }
+
+    pub fn boot_services(&self) -> &EfiBootServices {
+        unsafe { self.boot_services.as_ref() }
+    }
+
+    pub fn configuration_tables(&self) -> &[EfiConfigurationTable] {
+        unsafe {
+            core::slice::from_raw_parts(
+                self.efi_configuration_table.as_ptr(),
+                self.number_of_table_entries,
+            )
+        }
+    }
+
+    /// Returns the address of the configuration table identified by `guid`.
+    pub fn find_configuration_table(&self, guid: &EfiGuid) -> Option<NonNull<EfiVoid>> {
+        self.configuration_tables()
+            .iter()
+            .find(|table| table.vendor_guid() == guid)
+            .and_then(|table| NonNull::new(table.vendor_table()))
+    }
+
+    /// Returns the memory attributes table if the firmware publishes one.
+    pub fn memory_attributes_table(&self) -> Option<&EfiMemoryAttributesTable> {
+        self.find_configuration_table(&guid::EFI_MEMORY_ATTRIBUTES_TABLE_GUID)
+            .map(|table| unsafe { table.cast::<EfiMemoryAttributesTable>().as_ref() })
+    }
 }
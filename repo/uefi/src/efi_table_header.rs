@@ -1,12 +1,54 @@
+use core::mem::{offset_of, size_of};
+
+use crate::crc32::Crc32;
+
 use super::EfiRevision;
 
 // REF: https://uefi.org/specs/UEFI/2.11/04_EFI_System_Table.html#id4
 #[repr(C)]
 #[derive(Debug, Clone)]
 pub struct EfiTableHeader {
-    signeture: u64,
-    revision: EfiRevision,
-    header_size: u32,
-    crc32: u32,
-    reserved: u32,
+    pub(crate) signeture: u64,
+    pub(crate) revision: EfiRevision,
+    pub(crate) header_size: u32,
+    pub(crate) crc32: u32,
+    pub(crate) reserved: u32,
+}
+
+impl EfiTableHeader {
+    pub fn signature(&self) -> u64 {
+        self.signeture
+    }
+
+    pub fn revision(&self) -> EfiRevision {
+        self.revision
+    }
+
+    /// Size of the entire table including this header.
+    pub fn header_size(&self) -> u32 {
+        self.header_size
+    }
+
+    pub fn crc32(&self) -> u32 {
+        self.crc32
+    }
+
+    /// Recomputes the CRC32 of the table with the crc32 field cleared and compares it with the recorded one.
+    ///
+    /// # Safety
+    /// `self` must be the header of a table that spans `header_size` bytes.
+    pub unsafe fn verify_crc32(&self) -> bool {
+        let size = self.header_size as usize;
+        if size < size_of::<Self>() {
+            return false;
+        }
+
+        let table = core::slice::from_raw_parts((self as *const Self).cast::<u8>(), size);
+        let crc32_offset = offset_of!(Self, crc32);
+        let mut crc = Crc32::new();
+        crc.update(&table[..crc32_offset]);
+        crc.update(&[0; size_of::<u32>()]);
+        crc.update(&table[crc32_offset + size_of::<u32>()..]);
+        crc.finish() == self.crc32
+    }
 }
@@ -0,0 +1,58 @@
+//! REF: https://uefi.org/specs/UEFI/2.10/07_Services_Boot_Services.html#efi-image-entry-point
+
+/// Defines `efi_main`, the entry point of the image, which calls `$main`.
+///
+/// `$main` must be a `fn(EfiHandle, &'static EfiSystemTable) -> EfiStatus`.
+/// Any other signature fails to compile instead of being called with the wrong ABI or arguments.
+///
+/// ```ignore
+/// uefi::entry!(main);
+///
+/// fn main(image_handle: EfiHandle, system_table: &'static EfiSystemTable) -> EfiStatus {
+///     EfiStatus::SUCCESS
+/// }
+/// ```
+#[macro_export]
+macro_rules! entry {
+    ($main:path) => {
+        #[export_name = "efi_main"]
+        extern "efiapi" fn __efi_main(
+            image_handle: $crate::EfiHandle,
+            system_table: ::core::ptr::NonNull<$crate::EfiSystemTable>,
+        ) -> $crate::EfiStatus {
+            const MAIN: fn(
+                $crate::EfiHandle,
+                &'static $crate::EfiSystemTable,
+            ) -> $crate::EfiStatus = $main;
+
+            // The firmware keeps the system table alive and at the same address for the lifetime of the image.
+            MAIN(image_handle, unsafe { system_table.as_ref() })
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use core::ptr::NonNull;
+
+    use crate::{mock::MockFirmware, EfiHandle, EfiStatus, EfiSystemTable};
+
+    fn main(_image_handle: EfiHandle, system_table: &'static EfiSystemTable) -> EfiStatus {
+        match system_table.verify_crc32() {
+            true => EfiStatus::SUCCESS,
+            false => EfiStatus::CRC_ERROR,
+        }
+    }
+
+    crate::entry!(main);
+
+    #[test]
+    fn entry_point() {
+        let firmware = MockFirmware::new();
+        let status = __efi_main(
+            firmware.image_handle(),
+            NonNull::from(firmware.system_table()),
+        );
+        assert_eq!(status, EfiStatus::SUCCESS);
+    }
+}
@@ -0,0 +1,335 @@
+//! Inspection of the handle database, like the `dh` command of the UEFI shell.
+
+use core::fmt::Write;
+
+use crate::{
+    efi_boot_services::EfiLocateSearchType,
+    protocol::{
+        device_path::EFI_DEVICE_PATH_PROTOCOL_GUID,
+        driver_binding::EFI_DRIVER_BINDING_PROTOCOL_GUID,
+        graphics::EFI_GRAPHICS_OUTPUT_PROTOCOL_GUID,
+        loaded_image::EFI_LOADED_IMAGE_PROTOCOL_GUID,
+        memory_attribute::EFI_MEMORY_ATTRIBUTE_PROTOCOL_GUID,
+        mp_services::EFI_MP_SERVICES_PROTOCOL_GUID,
+        shell_parameters::EFI_SHELL_PARAMETERS_PROTOCOL_GUID,
+        simple_text::{EFI_SIMPLE_TEXT_INPUT_PROTOCOL_GUID, EFI_SIMPLE_TEXT_OUTPUT_PROTOCOL_GUID},
+        timestamp::EFI_TIMESTAMP_PROTOCOL_GUID,
+    },
+    EfiBootServices, EfiGuid, EfiHandle, EfiResult,
+};
+
+/// Names of well-known protocol GUIDs.
+pub const KNOWN_PROTOCOLS: &[(EfiGuid, &str)] = &[
+    (EFI_DEVICE_PATH_PROTOCOL_GUID, "DevicePath"),
+    (EFI_DRIVER_BINDING_PROTOCOL_GUID, "DriverBinding"),
+    (EFI_GRAPHICS_OUTPUT_PROTOCOL_GUID, "GraphicsOutput"),
+    (EFI_MEMORY_ATTRIBUTE_PROTOCOL_GUID, "MemoryAttribute"),
+    (EFI_MP_SERVICES_PROTOCOL_GUID, "MpServices"),
+    (EFI_SHELL_PARAMETERS_PROTOCOL_GUID, "ShellParameters"),
+    (EFI_TIMESTAMP_PROTOCOL_GUID, "Timestamp"),
+    (EFI_LOADED_IMAGE_PROTOCOL_GUID, "LoadedImage"),
+    (
+        EfiGuid(
+            0xbc62157e,
+            0x3e33,
+            0x4fec,
+            [0x99, 0x20, 0x2d, 0x3b, 0x36, 0xd7, 0x50, 0xdf],
+        ),
+        "LoadedImageDevicePath",
+    ),
+    (EFI_SIMPLE_TEXT_INPUT_PROTOCOL_GUID, "SimpleTextInput"),
+    (
+        EfiGuid(
+            0xdd9e7534,
+            0x7762,
+            0x4698,
+            [0x8c, 0x14, 0xf5, 0x85, 0x17, 0xa6, 0x25, 0xaa],
+        ),
+        "SimpleTextInputEx",
+    ),
+    (EFI_SIMPLE_TEXT_OUTPUT_PROTOCOL_GUID, "SimpleTextOutput"),
+    (
+        EfiGuid(
+            0x31878c87,
+            0x0b75,
+            0x11d5,
+            [0x9a, 0x4f, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d],
+        ),
+        "SimplePointer",
+    ),
+    (
+        EfiGuid(
+            0x964e5b22,
+            0x6459,
+            0x11d2,
+            [0x8e, 0x39, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
+        ),
+        "SimpleFileSystem",
+    ),
+    (
+        EfiGuid(
+            0x964e5b21,
+            0x6459,
+            0x11d2,
+            [0x8e, 0x39, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
+        ),
+        "BlockIo",
+    ),
+    (
+        EfiGuid(
+            0xa77b2472,
+            0xe282,
+            0x4e9f,
+            [0xa2, 0x45, 0xc2, 0xc0, 0xe2, 0x7b, 0xbc, 0xc1],
+        ),
+        "BlockIo2",
+    ),
+    (
+        EfiGuid(
+            0xce345171,
+            0xba0b,
+            0x11d2,
+            [0x8e, 0x4f, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
+        ),
+        "DiskIo",
+    ),
+    (
+        EfiGuid(
+            0x151c8eae,
+            0x7f2c,
+            0x472c,
+            [0x9e, 0x54, 0x98, 0x28, 0x19, 0x4f, 0x6a, 0x88],
+        ),
+        "DiskIo2",
+    ),
+    (
+        EfiGuid(
+            0x6a7a5cff,
+            0xe8d9,
+            0x4f70,
+            [0xba, 0xda, 0x75, 0xab, 0x30, 0x25, 0xce, 0x14],
+        ),
+        "ComponentName2",
+    ),
+    (
+        EfiGuid(
+            0x4cf5b200,
+            0x68b8,
+            0x4ca5,
+            [0x9e, 0xec, 0xb2, 0x3e, 0x3f, 0x50, 0x02, 0x9a],
+        ),
+        "PciIo",
+    ),
+    (
+        EfiGuid(
+            0x2f707ebb,
+            0x4a1a,
+            0x11d4,
+            [0x9a, 0x38, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d],
+        ),
+        "PciRootBridgeIo",
+    ),
+    (
+        EfiGuid(
+            0x2b2f68d6,
+            0x0cd2,
+            0x44cf,
+            [0x8e, 0x8b, 0xbb, 0xa2, 0x0b, 0x1b, 0x5b, 0x75],
+        ),
+        "UsbIo",
+    ),
+    (
+        EfiGuid(
+            0xbb25cf6f,
+            0xf1d4,
+            0x11d2,
+            [0x9a, 0x0c, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0xfd],
+        ),
+        "SerialIo",
+    ),
+    (
+        EfiGuid(
+            0x8b843e20,
+            0x8132,
+            0x4852,
+            [0x90, 0xcc, 0x55, 0x1a, 0x4e, 0x4a, 0x7f, 0x1c],
+        ),
+        "DevicePathToText",
+    ),
+    (
+        EfiGuid(
+            0x0379be4e,
+            0xd706,
+            0x437d,
+            [0xb0, 0x37, 0xed, 0xb8, 0x2f, 0xb7, 0x72, 0xa4],
+        ),
+        "DevicePathUtilities",
+    ),
+    (
+        EfiGuid(
+            0x3152bca5,
+            0xeade,
+            0x433d,
+            [0x86, 0x2e, 0xc0, 0x1c, 0xdc, 0x29, 0x1f, 0x44],
+        ),
+        "Rng",
+    ),
+];
+
+pub fn protocol_name(guid: &EfiGuid) -> Option<&'static str> {
+    KNOWN_PROTOCOLS
+        .iter()
+        .find(|(known, _)| known == guid)
+        .map(|(_, name)| *name)
+}
+
+const WRITE_ERROR: &str = "Failed to write handle database";
+
+/// Writes every handle with its protocols and their open information.
+/// A handle that cannot be inspected, e.g. since it went away meanwhile, gets its error written instead.
+pub fn dump_handle_database(
+    boot_services: &EfiBootServices,
+    writer: &mut impl Write,
+) -> EfiResult<()> {
+    let handles = boot_services.locate_handle_buffer(EfiLocateSearchType::AllHandles, None)?;
+    for &handle in handles.iter() {
+        match dump_handle(boot_services, handle, writer) {
+            Err(WRITE_ERROR) => return Err(WRITE_ERROR),
+            Err(message) => writeln!(writer, "  {}", message).map_err(|_| WRITE_ERROR)?,
+            Ok(()) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Writes the protocols installed on `handle` and who has them open.
+pub fn dump_handle(
+    boot_services: &EfiBootServices,
+    handle: EfiHandle,
+    writer: &mut impl Write,
+) -> EfiResult<()> {
+    writeln!(writer, "Handle {:p}", handle.as_ptr()).map_err(|_| WRITE_ERROR)?;
+
+    let protocols = boot_services.protocols_per_handle(handle)?;
+    for protocol in protocols.iter() {
+        let guid = unsafe { protocol.as_ref() };
+        match protocol_name(guid) {
+            Some(name) => writeln!(writer, "  {} ({})", name, guid),
+            None => writeln!(writer, "  {}", guid),
+        }
+        .map_err(|_| WRITE_ERROR)?;
+
+        let entries = boot_services.open_protocol_information(handle, guid)?;
+        for entry in entries.iter() {
+            let agent = entry
+                .agent_handle()
+                .map_or(core::ptr::null_mut(), |h| h.as_ptr());
+            let controller = entry
+                .controller_handle()
+                .map_or(core::ptr::null_mut(), |h| h.as_ptr());
+            writeln!(
+                writer,
+                "    opened by agent {:p} controller {:p} {} count {}",
+                agent,
+                controller,
+                entry.attributes(),
+                entry.open_count()
+            )
+            .map_err(|_| WRITE_ERROR)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use core::{fmt::Write as _, ptr::NonNull};
+    use std::{format, string::String};
+
+    use super::*;
+    use crate::{
+        efi_boot_services::EfiOpenProtocolAttributes, mock::MockFirmware, protocol::Protocol,
+        EfiStatus,
+    };
+
+    struct Timestamp;
+
+    unsafe impl Protocol for Timestamp {
+        const GUID: EfiGuid = EFI_TIMESTAMP_PROTOCOL_GUID;
+    }
+
+    struct Unknown;
+
+    unsafe impl Protocol for Unknown {
+        const GUID: EfiGuid = EfiGuid(
+            0x0d4f6a2e,
+            0x59c1,
+            0x4b7e,
+            [0x8a, 0x13, 0x2c, 0x64, 0xe0, 0x9b, 0x71, 0x5f],
+        );
+    }
+
+    #[test]
+    fn dump() {
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+        let handle = boot_services
+            .install_protocol_interface(None, &Timestamp)
+            .unwrap();
+        boot_services
+            .install_protocol_interface(Some(handle), &Unknown)
+            .unwrap();
+
+        let mut interface = core::ptr::null_mut();
+        let status = (boot_services.open_protocol)(
+            handle,
+            NonNull::from(&Timestamp::GUID),
+            Some(NonNull::from(&mut interface)),
+            firmware.image_handle(),
+            handle,
+            EfiOpenProtocolAttributes::BY_DRIVER | EfiOpenProtocolAttributes::EXCLUSIVE,
+        );
+        assert_eq!(status, EfiStatus::SUCCESS);
+
+        let mut expected = String::new();
+        writeln!(expected, "Handle {:p}", handle.as_ptr()).unwrap();
+        writeln!(expected, "  Timestamp ({})", Timestamp::GUID).unwrap();
+        writeln!(
+            expected,
+            "    opened by agent {:p} controller {:p} BY_DRIVER|EXCLUSIVE count 1",
+            firmware.image_handle().as_ptr(),
+            handle.as_ptr()
+        )
+        .unwrap();
+        writeln!(expected, "  {}", Unknown::GUID).unwrap();
+        assert_eq!(
+            format!("{}", Unknown::GUID),
+            "0d4f6a2e-59c1-4b7e-8a13-2c64e09b715f"
+        );
+
+        let mut output = String::new();
+        dump_handle(boot_services, handle, &mut output).unwrap();
+        assert_eq!(output, expected);
+
+        let mut database = String::new();
+        dump_handle_database(boot_services, &mut database).unwrap();
+        assert!(database.contains(&expected));
+        assert!(database.contains("  SimpleTextOutput ("));
+    }
+
+    #[test]
+    fn dump_vanished_handle() {
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+        let handle = boot_services
+            .install_protocol_interface(None, &Unknown)
+            .unwrap();
+        boot_services
+            .uninstall_protocol_interface(handle, &Unknown)
+            .unwrap();
+
+        let mut output = String::new();
+        assert!(dump_handle(boot_services, handle, &mut output).is_err());
+    }
+}
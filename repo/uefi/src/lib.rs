@@ -1,23 +1,36 @@
 #![no_std]
-#![no_main]
+#![cfg_attr(not(test), no_main)]
 #![feature(offset_of)]
 #![feature(extern_types)]
 #![feature(extended_varargs_abi_support)]
+#![feature(async_iterator)]
+
+#[cfg(any(test, feature = "mock"))]
+extern crate std;
 
-mod efi_configuration_table;
 mod efi_revision;
-mod efi_runtime_services;
 mod efi_system_table;
 mod efi_table_header;
+mod entry;
 
+pub mod command_line;
+pub mod crc32;
 pub mod data_type;
 pub mod efi_boot_services;
+pub mod efi_configuration_table;
+pub mod efi_runtime_services;
+pub mod handle_database;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod profiler;
 pub mod protocol;
 
 pub use data_type::*;
 pub use efi_boot_services::EfiBootServices;
+pub use efi_configuration_table::EfiConfigurationTable;
 pub use efi_revision::EfiRevision;
 pub use efi_runtime_services::EfiRuntimeServices;
+pub use efi_system_table::EfiSystemTable;
 pub use efi_table_header::EfiTableHeader;
 
 pub type EfiResult<T> = core::result::Result<T, &'static str>;
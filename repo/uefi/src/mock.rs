@@ -0,0 +1,657 @@
+//! Fake firmware for unit tests on the host, enabled by the `mock` feature.
+//!
+//! [`MockFirmware`] builds an [`EfiSystemTable`] whose services are implemented in Rust over in-memory state:
+//! a pool and page allocator, a handle database, events, a fixed memory map, a text output that is captured
+//! and MP services whose APs run on the calling thread.
+//! The state is per thread, so tests running in parallel do not interfere.
+//! Only one `MockFirmware` can be alive on a thread at a time.
+//!
+//! Services that are not emulated return [`EfiStatus::UNSUPPORTED`].
+
+mod boot_services;
+mod mp_services;
+mod runtime_services;
+mod text_output;
+
+use core::{mem::size_of, ptr::NonNull, time::Duration};
+use std::{
+    alloc::Layout, boxed::Box, cell::RefCell, collections::HashMap, collections::VecDeque,
+    string::String, thread_local, vec, vec::Vec,
+};
+
+use crate::{
+    crc32::crc32,
+    efi_boot_services::{
+        event::{EfiEventNotify, EfiEventType},
+        EfiMemoryAttribute, EfiMemoryDescriptor, EfiMemoryType, EfiOpenProtocolAttributes,
+    },
+    efi_runtime_services::EfiTime,
+    protocol::{
+        loaded_image::EfiLoadedImageProtocol,
+        mp_services::EfiMpServicesProtocol,
+        simple_text::{SimpleTextOutputMode, SimpleTextOutputProtocol},
+        Protocol,
+    },
+    EfiBootServices, EfiGuid, EfiHandle, EfiRuntimeServices, EfiStatus, EfiSystemTable,
+    EfiTableHeader, EfiTpl, EfiVoid, U16Str,
+};
+
+/// Stride of the descriptors in the memory map. Larger than the descriptor, as firmware is allowed to do.
+pub const DESCRIPTOR_SIZE: usize = size_of::<EfiMemoryDescriptor>() + 8;
+
+const EFI_SYSTEM_TABLE_SIGNATURE: u64 = 0x5453595320494249;
+const EFI_BOOT_SERVICES_SIGNATURE: u64 = 0x56524553544f4f42;
+const EFI_RUNTIME_SERVICES_SIGNATURE: u64 = 0x56524553544e5552;
+const EFI_2_100_SYSTEM_TABLE_REVISION: u32 = (2 << 16) | 100;
+
+static FIRMWARE_VENDOR: [u16; 14] = ucs2("Mock firmware");
+
+const fn ucs2<const N: usize>(s: &str) -> [u16; N] {
+    let bytes = s.as_bytes();
+    let mut units = [0; N];
+    let mut i = 0;
+    while i < bytes.len() {
+        units[i] = bytes[i] as u16;
+        i += 1;
+    }
+    units
+}
+
+thread_local! {
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with the state of the firmware alive on this thread.
+/// `f` must not call back into services, since the state stays borrowed.
+///
+/// Services return the error instead of panicking, which cannot unwind through their ABI:
+/// NOT_STARTED without a `MockFirmware` on this thread, and ACCESS_DENIED when the state is borrowed.
+fn with_state<R>(f: impl FnOnce(&mut State) -> R) -> Result<R, EfiStatus> {
+    STATE.with(|state| {
+        let mut state = state
+            .try_borrow_mut()
+            .map_err(|_| EfiStatus::ACCESS_DENIED)?;
+        Ok(f(state.as_mut().ok_or(EfiStatus::NOT_STARTED)?))
+    })
+}
+
+/// [`with_state`] for the methods of [`MockFirmware`], which are called from tests and may panic.
+fn state<R>(f: impl FnOnce(&mut State) -> R) -> R {
+    with_state(f).expect("state of the MockFirmware is not available")
+}
+
+struct HandleEntry {
+    handle: usize,
+    // GUIDs are boxed, since ProtocolsPerHandle hands out pointers to them.
+    protocols: Vec<(Box<EfiGuid>, *mut EfiVoid)>,
+}
+
+/// Agent and controller holding a protocol of `handle` opened with OpenProtocol.
+struct OpenEntry {
+    handle: usize,
+    protocol: EfiGuid,
+    agent: usize,
+    controller: usize,
+    attributes: EfiOpenProtocolAttributes,
+    open_count: u32,
+}
+
+struct EventEntry {
+    event: usize,
+    event_type: EfiEventType,
+    notify_tpl: EfiTpl,
+    notify_function: Option<EfiEventNotify>,
+    notify_context: Option<NonNull<EfiVoid>>,
+    group: Option<EfiGuid>,
+    signaled: bool,
+}
+
+/// Image loaded with LoadImage. Starting it returns the exit set with [`MockFirmware::set_image_exit`].
+struct ImageEntry {
+    image: usize,
+    exit_status: EfiStatus,
+    // Null-terminated, as passed to Exit().
+    exit_data: Option<Vec<u16>>,
+}
+
+struct Registration {
+    registration: usize,
+    protocol: EfiGuid,
+    event: usize,
+    handles: VecDeque<usize>,
+}
+
+struct State {
+    tpl: EfiTpl,
+    next_id: usize,
+    pool: HashMap<usize, Layout>,
+    pages: HashMap<u64, Layout>,
+    handles: Vec<HandleEntry>,
+    opens: Vec<OpenEntry>,
+    events: Vec<EventEntry>,
+    // Events signaled while the TPL was not lower than their notify TPL.
+    pending_notifies: Vec<usize>,
+    registrations: Vec<Registration>,
+    images: Vec<ImageEntry>,
+    memory_map: Vec<EfiMemoryDescriptor>,
+    map_key: usize,
+    monotonic_count: u64,
+    stalled: Duration,
+    watchdog_timeout: Option<Duration>,
+    exited_boot_services: bool,
+    output: String,
+    time: EfiTime,
+    processors: usize,
+    hung_processors: Vec<usize>,
+    // Processor number returned by WhoAmI, set while an AP runs a procedure.
+    current_processor: usize,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            tpl: EfiTpl::APPLICATION,
+            next_id: 0x1000,
+            pool: HashMap::new(),
+            pages: HashMap::new(),
+            handles: Vec::new(),
+            opens: Vec::new(),
+            events: Vec::new(),
+            pending_notifies: Vec::new(),
+            registrations: Vec::new(),
+            images: Vec::new(),
+            memory_map: default_memory_map(),
+            map_key: 1,
+            monotonic_count: 0,
+            stalled: Duration::ZERO,
+            watchdog_timeout: None,
+            exited_boot_services: false,
+            output: String::new(),
+            time: EfiTime::new(2024, 1, 1, 0, 0, 0).unwrap(),
+            processors: 1,
+            hung_processors: Vec::new(),
+            current_processor: 0,
+        }
+    }
+
+    /// Fake addresses for handles, events and registrations. They are never dereferenced.
+    fn new_id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 0x10;
+        id
+    }
+
+    fn allocate_pool(&mut self, size: usize) -> Option<NonNull<u8>> {
+        let layout = Layout::from_size_align(size.max(1), 8).ok()?;
+        let ptr = NonNull::new(unsafe { std::alloc::alloc(layout) })?;
+        self.pool.insert(ptr.as_ptr() as usize, layout);
+        self.map_key += 1;
+        Some(ptr)
+    }
+
+    fn free_pool(&mut self, ptr: *mut u8) -> bool {
+        match self.pool.remove(&(ptr as usize)) {
+            Some(layout) => {
+                unsafe { std::alloc::dealloc(ptr, layout) };
+                self.map_key += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn handle(&self, handle: usize) -> Option<&HandleEntry> {
+        self.handles.iter().find(|entry| entry.handle == handle)
+    }
+
+    fn interface(&self, handle: usize, protocol: &EfiGuid) -> Option<*mut EfiVoid> {
+        self.handle(handle)?
+            .protocols
+            .iter()
+            .find(|(guid, _)| **guid == *protocol)
+            .map(|(_, interface)| *interface)
+    }
+
+    fn handles_with(&self, protocol: Option<&EfiGuid>) -> Vec<usize> {
+        self.handles
+            .iter()
+            .filter(|entry| match protocol {
+                Some(protocol) => entry.protocols.iter().any(|(guid, _)| **guid == *protocol),
+                None => true,
+            })
+            .map(|entry| entry.handle)
+            .collect()
+    }
+
+    fn install(
+        &mut self,
+        handle: Option<usize>,
+        protocol: EfiGuid,
+        interface: *mut EfiVoid,
+    ) -> Option<usize> {
+        let handle = match handle {
+            Some(handle) => {
+                let entry = self
+                    .handles
+                    .iter_mut()
+                    .find(|entry| entry.handle == handle)?;
+                if entry.protocols.iter().any(|(guid, _)| **guid == protocol) {
+                    return None;
+                }
+                entry.protocols.push((Box::new(protocol), interface));
+                handle
+            }
+            None => {
+                let handle = self.new_id();
+                self.handles.push(HandleEntry {
+                    handle,
+                    protocols: vec![(Box::new(protocol), interface)],
+                });
+                handle
+            }
+        };
+        Some(handle)
+    }
+
+    /// Queues `handle` for the registrations of `protocol` and returns their events to signal.
+    fn notify_installation(&mut self, handle: usize, protocol: &EfiGuid) -> Vec<usize> {
+        self.registrations
+            .iter_mut()
+            .filter(|registration| registration.protocol == *protocol)
+            .map(|registration| {
+                registration.handles.push_back(handle);
+                registration.event
+            })
+            .collect()
+    }
+
+    /// Marks `event` and the events in its group signaled.
+    /// Returns the events whose notify function can run now at the current TPL.
+    fn signal(&mut self, event: usize) -> Option<Vec<usize>> {
+        let group = self.events.iter().find(|entry| entry.event == event)?.group;
+        let mut runnable = Vec::new();
+        for entry in self.events.iter_mut() {
+            let in_group = group.is_some() && entry.group == group;
+            if entry.event != event && !in_group {
+                continue;
+            }
+
+            entry.signaled = true;
+            if !entry.event_type.contains(EfiEventType::NOTIFY_SIGNAL)
+                || entry.notify_function.is_none()
+            {
+                continue;
+            }
+            if self.tpl < entry.notify_tpl {
+                runnable.push(entry.event);
+            } else if !self.pending_notifies.contains(&entry.event) {
+                self.pending_notifies.push(entry.event);
+            }
+        }
+        Some(runnable)
+    }
+
+    /// Takes the pending events that can run at the current TPL, highest notify TPL first.
+    fn take_runnable_pending(&mut self) -> Vec<usize> {
+        let tpl = self.tpl;
+        let events = &self.events;
+        let notify_tpl = |event: &usize| {
+            events
+                .iter()
+                .find(|entry| entry.event == *event)
+                .map(|entry| entry.notify_tpl)
+        };
+        let (mut runnable, pending): (Vec<usize>, Vec<usize>) = self
+            .pending_notifies
+            .iter()
+            .partition(|event| notify_tpl(event).map_or(false, |notify_tpl| tpl < notify_tpl));
+        runnable.sort_by_key(|event| core::cmp::Reverse(notify_tpl(event)));
+        self.pending_notifies = pending;
+        runnable
+    }
+}
+
+/// Calls the notify functions of `events` at their notify TPL, without holding the state.
+fn dispatch(events: Vec<usize>) {
+    for event in events {
+        let notify = with_state(|state| {
+            let entry = state.events.iter().find(|entry| entry.event == event)?;
+            let notify = (
+                entry.notify_function?,
+                entry.notify_context,
+                entry.notify_tpl,
+                state.tpl,
+            );
+            state.tpl = entry.notify_tpl;
+            Some(notify)
+        });
+
+        if let Ok(Some((notify_function, notify_context, notify_tpl, old_tpl))) = notify {
+            debug_assert!(old_tpl < notify_tpl);
+            notify_function(
+                crate::EfiEvent::from_ptr(event as *mut EfiVoid),
+                notify_context.unwrap_or(NonNull::dangling()),
+            );
+            // Only fails if the notify function misused the mock, which its caller reports.
+            let _ = with_state(|state| state.tpl = old_tpl);
+        }
+    }
+}
+
+fn default_memory_map() -> Vec<EfiMemoryDescriptor> {
+    let cacheable = EfiMemoryAttribute::MEMORY_UC
+        | EfiMemoryAttribute::MEMORY_WC
+        | EfiMemoryAttribute::MEMORY_WT
+        | EfiMemoryAttribute::MEMORY_WB;
+    let descriptor = |memory_type, start: u64, pages, attribute| {
+        EfiMemoryDescriptor::new(memory_type, start.into(), pages, attribute)
+    };
+
+    vec![
+        descriptor(EfiMemoryType::EfiBootServicesCode, 0x0, 0x1, cacheable),
+        descriptor(
+            EfiMemoryType::EfiConventionalMemory,
+            0x1000,
+            0x9F,
+            cacheable,
+        ),
+        descriptor(EfiMemoryType::EfiLoaderCode, 0x100000, 0x100, cacheable),
+        descriptor(EfiMemoryType::EfiLoaderData, 0x200000, 0x100, cacheable),
+        descriptor(
+            EfiMemoryType::EfiBootServicesData,
+            0x300000,
+            0x100,
+            cacheable,
+        ),
+        descriptor(
+            EfiMemoryType::EfiRuntimeServicesCode,
+            0x400000,
+            0x10,
+            cacheable | EfiMemoryAttribute::MEMORY_RUNTIME,
+        ),
+        descriptor(
+            EfiMemoryType::EfiRuntimeServicesData,
+            0x410000,
+            0x10,
+            cacheable | EfiMemoryAttribute::MEMORY_RUNTIME,
+        ),
+        descriptor(
+            EfiMemoryType::EfiConventionalMemory,
+            0x500000,
+            0x3A00,
+            cacheable,
+        ),
+        descriptor(
+            EfiMemoryType::EfiACPIReclaimMemory,
+            0x3F00000,
+            0x80,
+            cacheable,
+        ),
+        descriptor(EfiMemoryType::EfiACPIMemoryNVS, 0x3F80000, 0x80, cacheable),
+    ]
+}
+
+/// Owner of the fake system table. Dropping it frees everything allocated through it.
+pub struct MockFirmware {
+    system_table: NonNull<EfiSystemTable>,
+    boot_services: NonNull<EfiBootServices>,
+    runtime_services: NonNull<EfiRuntimeServices>,
+    console_out: NonNull<SimpleTextOutputProtocol>,
+    console_out_mode: NonNull<SimpleTextOutputMode>,
+    mp_services: NonNull<EfiMpServicesProtocol>,
+    loaded_image: NonNull<EfiLoadedImageProtocol>,
+    load_options: Vec<u16>,
+    image_handle: EfiHandle,
+}
+
+impl MockFirmware {
+    /// # Panics
+    /// Panics if another `MockFirmware` is alive on this thread.
+    pub fn new() -> Self {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            assert!(
+                state.is_none(),
+                "MockFirmware is already alive on this thread"
+            );
+            *state = Some(State::new());
+        });
+
+        let boot_services = leak(boot_services::table());
+        let runtime_services = leak(runtime_services::table());
+        let console_out_mode = leak(text_output::mode());
+        let console_out = leak(text_output::protocol(console_out_mode));
+        let mp_services = leak(mp_services::protocol());
+
+        let console_handle = state(|state| {
+            state
+                .install(
+                    None,
+                    SimpleTextOutputProtocol::GUID,
+                    console_out.as_ptr().cast(),
+                )
+                .unwrap()
+        });
+        let console_handle =
+            EfiHandle::from_ptr(NonNull::new(console_handle as *mut EfiVoid).unwrap());
+        state(|state| {
+            state
+                .install(
+                    None,
+                    EfiMpServicesProtocol::GUID,
+                    mp_services.as_ptr().cast(),
+                )
+                .unwrap()
+        });
+
+        let mut system_table = EfiSystemTable {
+            hdr: header::<EfiSystemTable>(EFI_SYSTEM_TABLE_SIGNATURE),
+            firmware_vendor: NonNull::new(FIRMWARE_VENDOR.as_ptr() as *mut U16Str).unwrap(),
+            firmware_revision: 0x10000,
+            console_in_handle: console_handle,
+            // SimpleTextInputProtocol has no members yet.
+            console_in: NonNull::dangling(),
+            console_out_handle: console_handle,
+            console_out,
+            standard_error_handle: console_handle,
+            std_err: console_out,
+            runtime_services,
+            boot_services,
+            number_of_table_entries: 0,
+            efi_configuration_table: NonNull::dangling(),
+        };
+        system_table.hdr.crc32 = table_crc32(&system_table);
+        let system_table = leak(system_table);
+
+        let loaded_image = leak(EfiLoadedImageProtocol {
+            revision: 0x1000,
+            parent_handle: None,
+            system_table,
+            device_handle: None,
+            file_path: None,
+            _reserved: core::ptr::null_mut(),
+            load_options_size: 0,
+            load_options: core::ptr::null_mut(),
+            image_base: core::ptr::null_mut(),
+            image_size: 0,
+            image_code_type: EfiMemoryType::EfiLoaderCode as u32,
+            image_data_type: EfiMemoryType::EfiLoaderData as u32,
+            unload: None,
+        });
+        let image_handle = state(|state| {
+            state
+                .install(
+                    None,
+                    EfiLoadedImageProtocol::GUID,
+                    loaded_image.as_ptr().cast(),
+                )
+                .unwrap()
+        });
+
+        Self {
+            system_table,
+            boot_services,
+            runtime_services,
+            console_out,
+            console_out_mode,
+            mp_services,
+            loaded_image,
+            load_options: Vec::new(),
+            image_handle: EfiHandle::from_ptr(NonNull::new(image_handle as *mut EfiVoid).unwrap()),
+        }
+    }
+
+    pub fn system_table(&self) -> &EfiSystemTable {
+        unsafe { self.system_table.as_ref() }
+    }
+
+    pub fn boot_services(&self) -> &EfiBootServices {
+        unsafe { self.boot_services.as_ref() }
+    }
+
+    pub fn runtime_services(&self) -> &EfiRuntimeServices {
+        unsafe { self.runtime_services.as_ref() }
+    }
+
+    /// Handle of the running image, with a LoadedImage protocol installed.
+    pub fn image_handle(&self) -> EfiHandle {
+        self.image_handle
+    }
+
+    /// Sets the LoadOptions of the running image to `options` as a null-terminated UCS-2 string.
+    pub fn set_load_options(&mut self, options: &str) {
+        self.load_options = options.encode_utf16().chain([0]).collect();
+        let loaded_image = unsafe { self.loaded_image.as_mut() };
+        loaded_image.load_options_size = (self.load_options.len() * size_of::<u16>()) as u32;
+        loaded_image.load_options = self.load_options.as_mut_ptr().cast();
+    }
+
+    /// Takes the text written to the console so far.
+    pub fn take_output(&self) -> String {
+        state(|state| core::mem::take(&mut state.output))
+    }
+
+    /// Number of pool buffers not yet freed.
+    pub fn pool_allocations(&self) -> usize {
+        state(|state| state.pool.len())
+    }
+
+    pub fn set_memory_map(&self, memory_map: Vec<EfiMemoryDescriptor>) {
+        state(|state| {
+            state.memory_map = memory_map;
+            state.map_key += 1;
+        })
+    }
+
+    /// Total time passed to Stall. Stall returns immediately.
+    pub fn stalled(&self) -> Duration {
+        state(|state| state.stalled)
+    }
+
+    /// Timeout of the watchdog timer, `None` if disabled.
+    pub fn watchdog_timeout(&self) -> Option<Duration> {
+        state(|state| state.watchdog_timeout)
+    }
+
+    /// Sets what starting `image` returns: the status of its entry point, and the string it passes to Exit().
+    ///
+    /// # Panics
+    /// Panics if `image` was not loaded with LoadImage.
+    pub fn set_image_exit(&self, image: EfiHandle, status: EfiStatus, exit_data: Option<&str>) {
+        let image = image.as_ptr() as usize;
+        state(|state| {
+            let entry = state
+                .images
+                .iter_mut()
+                .find(|entry| entry.image == image)
+                .expect("not a loaded image");
+            entry.exit_status = status;
+            entry.exit_data = exit_data.map(|data| data.encode_utf16().chain([0]).collect());
+        })
+    }
+
+    /// Number of images loaded and not yet unloaded.
+    pub fn loaded_images(&self) -> usize {
+        state(|state| state.images.len())
+    }
+
+    pub fn exited_boot_services(&self) -> bool {
+        state(|state| state.exited_boot_services)
+    }
+
+    /// Current TPL, to check that raised TPLs are restored.
+    pub fn tpl(&self) -> EfiTpl {
+        state(|state| state.tpl)
+    }
+
+    /// Time returned by GetTime.
+    pub fn time(&self) -> EfiTime {
+        state(|state| state.time)
+    }
+
+    pub fn set_time(&self, time: EfiTime) {
+        state(|state| state.time = time)
+    }
+
+    /// Sets the number of processors of the MP services, BSP included. All of them are enabled.
+    /// There is only the BSP by default.
+    pub fn set_processors(&self, processors: usize) {
+        state(|state| state.processors = processors.max(1))
+    }
+
+    /// Makes the AP `processor_number` never finish, so that waiting for it times out.
+    pub fn hang_processor(&self, processor_number: usize) {
+        state(|state| state.hung_processors.push(processor_number))
+    }
+}
+
+impl Default for MockFirmware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for MockFirmware {
+    fn drop(&mut self) {
+        let state = STATE.with(|state| state.borrow_mut().take());
+        if let Some(state) = state {
+            for (ptr, layout) in state.pool {
+                unsafe { std::alloc::dealloc(ptr as *mut u8, layout) };
+            }
+            for (address, layout) in state.pages {
+                unsafe { std::alloc::dealloc(address as *mut u8, layout) };
+            }
+        }
+
+        unsafe {
+            drop(Box::from_raw(self.loaded_image.as_ptr()));
+            drop(Box::from_raw(self.system_table.as_ptr()));
+            drop(Box::from_raw(self.console_out.as_ptr()));
+            drop(Box::from_raw(self.console_out_mode.as_ptr()));
+            drop(Box::from_raw(self.mp_services.as_ptr()));
+            drop(Box::from_raw(self.runtime_services.as_ptr()));
+            drop(Box::from_raw(self.boot_services.as_ptr()));
+        }
+    }
+}
+
+fn leak<T>(value: T) -> NonNull<T> {
+    NonNull::from(Box::leak(Box::new(value)))
+}
+
+fn header<T>(signature: u64) -> EfiTableHeader {
+    EfiTableHeader {
+        signeture: signature,
+        revision: EFI_2_100_SYSTEM_TABLE_REVISION.into(),
+        header_size: size_of::<T>() as u32,
+        crc32: 0,
+        reserved: 0,
+    }
+}
+
+/// CRC32 of a table whose header has the crc32 field cleared.
+fn table_crc32<T>(table: &T) -> u32 {
+    let bytes =
+        unsafe { core::slice::from_raw_parts((table as *const T).cast::<u8>(), size_of::<T>()) };
+    crc32(bytes)
+}
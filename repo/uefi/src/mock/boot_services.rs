@@ -0,0 +1,1095 @@
+//! Boot services of the mock firmware.
+
+use core::{
+    convert::identity,
+    mem::{size_of, size_of_val},
+    ptr::NonNull,
+    time::Duration,
+};
+use std::{collections::VecDeque, vec::Vec};
+
+use crate::{
+    efi_boot_services::{
+        event::{event_group, EfiEventNotify, EfiEventType},
+        protocol_handler::{EfiInstallMultipleProtocolInterfaces, EfiInterfaceType},
+        EfiAllocateType, EfiLocateSearchType, EfiMemoryDescriptor, EfiMemoryType,
+        EfiOpenProtocolAttributes, EfiOpenProtocolInformationEntry, EfiPhysicalAddress,
+        EfiTimerDelay,
+    },
+    protocol::device_path::EfiDevicePathProtocol,
+    EfiBootServices, EfiEvent, EfiGuid, EfiHandle, EfiStatus, EfiTpl, EfiVoid, U16Str,
+};
+
+use super::{
+    dispatch, header, table_crc32, with_state, EventEntry, ImageEntry, OpenEntry, Registration,
+    DESCRIPTOR_SIZE, EFI_BOOT_SERVICES_SIGNATURE,
+};
+
+pub(super) fn table() -> EfiBootServices {
+    let mut table = EfiBootServices {
+        hdr: header::<EfiBootServices>(EFI_BOOT_SERVICES_SIGNATURE),
+        raise_tpl,
+        restore_tpl,
+        allocate_pages,
+        free_pages,
+        get_memory_map,
+        allocate_pool,
+        free_pool,
+        create_event,
+        set_timer,
+        wait_for_event,
+        signal_event,
+        close_event,
+        check_event,
+        install_protocol_interface,
+        reinstall_protocol_interface,
+        uninsatall_protocol_interface: uninstall_protocol_interface,
+        handle_protocol,
+        _reserved: core::ptr::null_mut(),
+        register_protocol_notify,
+        locate_handle,
+        locate_device_path,
+        install_configuration_table,
+        load_image,
+        start_image,
+        exit,
+        unload_image,
+        exit_boot_services,
+        get_next_monotonic_count,
+        stall,
+        set_watchdog_timer,
+        connect_controller,
+        disconnect_controller,
+        open_protocol,
+        close_protocol,
+        open_protocol_information,
+        protocols_per_handle,
+        locate_handle_buffer,
+        locate_protocol,
+        install_multiple_protocol_interfaces: multiple_protocol_interfaces(),
+        // Both take the handle followed by varargs, so the same stub serves for either.
+        uninstall_multiple_protocol_interfaces: unsafe {
+            core::mem::transmute(multiple_protocol_interfaces())
+        },
+        calcurate_crc32: calculate_crc32,
+        copy_mem,
+        set_mem,
+        create_event_ex,
+    };
+    table.hdr.crc32 = table_crc32(&table);
+    table
+}
+
+fn id(ptr: *mut EfiVoid) -> usize {
+    ptr as usize
+}
+
+fn handle_of(id: usize) -> EfiHandle {
+    EfiHandle::from_ptr(NonNull::new(id as *mut EfiVoid).unwrap())
+}
+
+/// Copies `items` into a pool buffer, as the services returning buffers to free with FreePool do.
+fn pool_copy<T: Copy>(items: &[T]) -> Result<NonNull<T>, EfiStatus> {
+    let buffer = with_state(|state| state.allocate_pool(size_of_val(items)))?
+        .ok_or(EfiStatus::OUT_OF_RESOURCES)?
+        .cast::<T>();
+    unsafe { core::ptr::copy_nonoverlapping(items.as_ptr(), buffer.as_ptr(), items.len()) };
+    Ok(buffer)
+}
+
+//
+// Task Priority Services
+//
+
+/// Without the state, there is no TPL to raise, and restoring the returned one does nothing.
+extern "efiapi" fn raise_tpl(new_tpl: EfiTpl) -> EfiTpl {
+    with_state(|state| core::mem::replace(&mut state.tpl, new_tpl)).unwrap_or(new_tpl)
+}
+
+extern "efiapi" fn restore_tpl(old_tpl: EfiTpl) {
+    let runnable = with_state(|state| {
+        state.tpl = old_tpl;
+        state.take_runnable_pending()
+    });
+    if let Ok(runnable) = runnable {
+        dispatch(runnable);
+    }
+}
+
+//
+// Memory Services
+//
+
+extern "efiapi" fn allocate_pages(
+    allocate_type: EfiAllocateType,
+    _memory_type: EfiMemoryType,
+    pages: usize,
+    mut memory: NonNull<EfiPhysicalAddress>,
+) -> EfiStatus {
+    if !matches!(allocate_type, EfiAllocateType::AllocateAnyPages) {
+        return EfiStatus::UNSUPPORTED;
+    }
+
+    let Some(size) = pages.checked_mul(EfiMemoryDescriptor::PAGE_SIZE as usize) else {
+        return EfiStatus::OUT_OF_RESOURCES;
+    };
+    let Ok(layout) = std::alloc::Layout::from_size_align(size.max(1), 4096) else {
+        return EfiStatus::OUT_OF_RESOURCES;
+    };
+    let ptr = unsafe { std::alloc::alloc(layout) };
+    if ptr.is_null() {
+        return EfiStatus::OUT_OF_RESOURCES;
+    }
+
+    let address = ptr as u64;
+    let recorded = with_state(|state| {
+        state.pages.insert(address, layout);
+        state.map_key += 1;
+    });
+    if let Err(status) = recorded {
+        unsafe { std::alloc::dealloc(ptr, layout) };
+        return status;
+    }
+    unsafe { *memory.as_mut() = address.into() };
+    EfiStatus::SUCCESS
+}
+
+extern "efiapi" fn free_pages(memory: EfiPhysicalAddress, pages: usize) -> EfiStatus {
+    let address = u64::from(memory);
+    let layout = with_state(|state| {
+        let layout = state.pages.get(&address)?;
+        if layout.size() != (pages * EfiMemoryDescriptor::PAGE_SIZE as usize).max(1) {
+            return None;
+        }
+        state.map_key += 1;
+        state.pages.remove(&address)
+    });
+
+    match layout {
+        Ok(Some(layout)) => {
+            unsafe { std::alloc::dealloc(address as *mut u8, layout) };
+            EfiStatus::SUCCESS
+        }
+        Ok(None) => EfiStatus::NOT_FOUND,
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn get_memory_map(
+    mut memory_map_size: NonNull<usize>,
+    memory_map: NonNull<EfiMemoryDescriptor>,
+    mut map_key: NonNull<usize>,
+    mut descriptor_size: NonNull<usize>,
+    mut descriptor_version: NonNull<u32>,
+) -> EfiStatus {
+    with_state(|state| {
+        let size = state.memory_map.len() * DESCRIPTOR_SIZE;
+        let available = unsafe { core::mem::replace(memory_map_size.as_mut(), size) };
+        unsafe {
+            *descriptor_size.as_mut() = DESCRIPTOR_SIZE;
+            *descriptor_version.as_mut() = 1;
+        }
+        if available < size {
+            return EfiStatus::BUFFER_TOO_SMALL;
+        }
+
+        let buffer = memory_map.cast::<u8>().as_ptr();
+        unsafe { buffer.write_bytes(0, size) };
+        for (i, descriptor) in state.memory_map.iter().enumerate() {
+            unsafe {
+                buffer
+                    .add(i * DESCRIPTOR_SIZE)
+                    .cast::<EfiMemoryDescriptor>()
+                    .write(descriptor.clone())
+            };
+        }
+        unsafe { *map_key.as_mut() = state.map_key };
+        EfiStatus::SUCCESS
+    })
+    .unwrap_or_else(identity)
+}
+
+extern "efiapi" fn allocate_pool(
+    _pool_type: EfiMemoryType,
+    size: usize,
+    mut buffer: NonNull<*mut EfiVoid>,
+) -> EfiStatus {
+    match with_state(|state| state.allocate_pool(size)) {
+        Ok(Some(ptr)) => {
+            unsafe { *buffer.as_mut() = ptr.as_ptr().cast() };
+            EfiStatus::SUCCESS
+        }
+        Ok(None) => EfiStatus::OUT_OF_RESOURCES,
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn free_pool(buffer: NonNull<EfiVoid>) -> EfiStatus {
+    match with_state(|state| state.free_pool(buffer.as_ptr().cast())) {
+        Ok(true) => EfiStatus::SUCCESS,
+        Ok(false) => EfiStatus::INVALID_PARAMETER,
+        Err(status) => status,
+    }
+}
+
+//
+// Event & Timer Services
+//
+
+extern "efiapi" fn create_event(
+    event_type: EfiEventType,
+    notify_tpl: EfiTpl,
+    notify_function: Option<EfiEventNotify>,
+    notify_context: Option<NonNull<EfiVoid>>,
+    event: NonNull<EfiEvent>,
+) -> EfiStatus {
+    create_event_ex(
+        event_type,
+        notify_tpl,
+        notify_function,
+        notify_context,
+        None,
+        event,
+    )
+}
+
+extern "efiapi" fn create_event_ex(
+    event_type: EfiEventType,
+    notify_tpl: EfiTpl,
+    notify_function: Option<EfiEventNotify>,
+    notify_context: Option<NonNull<EfiVoid>>,
+    event_group: Option<NonNull<EfiGuid>>,
+    mut event: NonNull<EfiEvent>,
+) -> EfiStatus {
+    let notifies = event_type.contains(EfiEventType::NOTIFY_SIGNAL)
+        || event_type.contains(EfiEventType::NOTIFY_WAIT);
+    if notifies
+        && (notify_function.is_none()
+            || notify_tpl <= EfiTpl::APPLICATION
+            || notify_tpl >= EfiTpl::HIGH_LEVEL)
+    {
+        return EfiStatus::INVALID_PARAMETER;
+    }
+
+    // Events of these types are implicitly in the corresponding groups.
+    let group = event_group.map(|group| unsafe { *group.as_ref() }).or(
+        if event_type == EfiEventType::SIGNAL_EXIT_BOOT_SERVICES {
+            Some(event_group::EXIT_BOOT_SERVICES)
+        } else if event_type == EfiEventType::SIGNAL_VIRTUAL_ADDRESS_CHANGE {
+            Some(event_group::EFI_EVENT_GROUP_VIRTUAL_ADDRESS_CHANGE)
+        } else {
+            None
+        },
+    );
+
+    let id = with_state(|state| {
+        let id = state.new_id();
+        state.events.push(EventEntry {
+            event: id,
+            event_type,
+            notify_tpl,
+            notify_function,
+            notify_context,
+            group,
+            signaled: false,
+        });
+        id
+    });
+    let id = match id {
+        Ok(id) => id,
+        Err(status) => return status,
+    };
+    unsafe { *event.as_mut() = EfiEvent::from_ptr(id as *mut EfiVoid) };
+    EfiStatus::SUCCESS
+}
+
+/// Timers need a clock, which the mock firmware does not have.
+extern "efiapi" fn set_timer(
+    _event: EfiEvent,
+    _type: EfiTimerDelay,
+    _trigger_time: u64,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+/// Returns NOT_READY instead of blocking when no event is signaled, since nothing could signal it.
+extern "efiapi" fn wait_for_event(
+    number_of_events: usize,
+    event: NonNull<EfiEvent>,
+    mut index: NonNull<usize>,
+) -> EfiStatus {
+    if number_of_events == 0 {
+        return EfiStatus::INVALID_PARAMETER;
+    }
+    match with_state(|state| state.tpl) {
+        Ok(EfiTpl::APPLICATION) => {}
+        Ok(_) => return EfiStatus::UNSUPPORTED,
+        Err(status) => return status,
+    }
+
+    let events = unsafe { core::slice::from_raw_parts(event.as_ptr(), number_of_events) };
+    for (i, &event) in events.iter().enumerate() {
+        match check_event(event) {
+            EfiStatus::NOT_READY => continue,
+            EfiStatus::SUCCESS => {
+                unsafe { *index.as_mut() = i };
+                return EfiStatus::SUCCESS;
+            }
+            status => {
+                unsafe { *index.as_mut() = i };
+                return status;
+            }
+        }
+    }
+
+    EfiStatus::NOT_READY
+}
+
+extern "efiapi" fn signal_event(event: EfiEvent) -> EfiStatus {
+    match with_state(|state| state.signal(id(event.as_ptr()))) {
+        Ok(Some(runnable)) => {
+            dispatch(runnable);
+            EfiStatus::SUCCESS
+        }
+        Ok(None) => EfiStatus::INVALID_PARAMETER,
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn close_event(event: EfiEvent) -> EfiStatus {
+    let event = id(event.as_ptr());
+    with_state(|state| {
+        let Some(position) = state.events.iter().position(|entry| entry.event == event) else {
+            return EfiStatus::INVALID_PARAMETER;
+        };
+        state.events.remove(position);
+        state.pending_notifies.retain(|pending| *pending != event);
+        state
+            .registrations
+            .retain(|registration| registration.event != event);
+        EfiStatus::SUCCESS
+    })
+    .unwrap_or_else(identity)
+}
+
+extern "efiapi" fn check_event(event: EfiEvent) -> EfiStatus {
+    let event = id(event.as_ptr());
+    let take_signaled = || {
+        with_state(|state| {
+            let entry = state.events.iter_mut().find(|entry| entry.event == event)?;
+            Some(core::mem::replace(&mut entry.signaled, false))
+        })
+        .ok()
+        .flatten()
+    };
+
+    let notify_wait = with_state(|state| {
+        let entry = state.events.iter().find(|entry| entry.event == event)?;
+        Some((
+            entry.event_type.contains(EfiEventType::NOTIFY_SIGNAL),
+            entry.event_type.contains(EfiEventType::NOTIFY_WAIT) && state.tpl < entry.notify_tpl,
+        ))
+    });
+    let (notify_signal, notify_wait) = match notify_wait {
+        Ok(Some(notify_wait)) => notify_wait,
+        Ok(None) => return EfiStatus::INVALID_PARAMETER,
+        Err(status) => return status,
+    };
+    if notify_signal {
+        return EfiStatus::INVALID_PARAMETER;
+    }
+
+    if take_signaled() == Some(true) {
+        return EfiStatus::SUCCESS;
+    }
+    // The notify function of a wait event is what signals it.
+    if notify_wait {
+        dispatch(Vec::from([event]));
+        if take_signaled() == Some(true) {
+            return EfiStatus::SUCCESS;
+        }
+    }
+
+    EfiStatus::NOT_READY
+}
+
+//
+// Protocol Handler Services
+//
+
+/// Signals the events registered for `protocol`, now that it has been installed on `handle`.
+fn notify_installation(handle: usize, protocol: &EfiGuid) {
+    let events = with_state(|state| state.notify_installation(handle, protocol));
+    for event in events.unwrap_or_default() {
+        if let Ok(Some(runnable)) = with_state(|state| state.signal(event)) {
+            dispatch(runnable);
+        }
+    }
+}
+
+extern "efiapi" fn install_protocol_interface(
+    handle: NonNull<EfiHandle>,
+    protocol: NonNull<EfiGuid>,
+    _interface_type: EfiInterfaceType,
+    interface: NonNull<EfiVoid>,
+) -> EfiStatus {
+    let handle = handle.cast::<Option<EfiHandle>>().as_ptr();
+    let protocol = unsafe { *protocol.as_ref() };
+    let requested = unsafe { *handle }.map(|handle| id(handle.as_ptr()));
+
+    let installed = match with_state(|state| state.install(requested, protocol, interface.as_ptr()))
+    {
+        Ok(Some(installed)) => installed,
+        Ok(None) => return EfiStatus::INVALID_PARAMETER,
+        Err(status) => return status,
+    };
+    unsafe { *handle = Some(handle_of(installed)) };
+
+    notify_installation(installed, &protocol);
+    EfiStatus::SUCCESS
+}
+
+extern "efiapi" fn uninstall_protocol_interface(
+    handle: EfiHandle,
+    protocol: NonNull<EfiGuid>,
+    interface: NonNull<EfiVoid>,
+) -> EfiStatus {
+    let handle = id(handle.as_ptr());
+    let protocol = unsafe { protocol.as_ref() };
+    with_state(|state| {
+        let Some(position) = state
+            .handles
+            .iter()
+            .position(|entry| entry.handle == handle)
+        else {
+            return EfiStatus::INVALID_PARAMETER;
+        };
+
+        let protocols = &mut state.handles[position].protocols;
+        let Some(installed) = protocols
+            .iter()
+            .position(|(guid, installed)| **guid == *protocol && *installed == interface.as_ptr())
+        else {
+            return EfiStatus::NOT_FOUND;
+        };
+        protocols.remove(installed);
+        state
+            .opens
+            .retain(|open| open.handle != handle || open.protocol != *protocol);
+
+        // The handle goes away with its last protocol.
+        if protocols.is_empty() {
+            state.handles.remove(position);
+        }
+        EfiStatus::SUCCESS
+    })
+    .unwrap_or_else(identity)
+}
+
+extern "efiapi" fn reinstall_protocol_interface(
+    handle: EfiHandle,
+    protocol: NonNull<EfiGuid>,
+    old_interface: NonNull<EfiVoid>,
+    new_interface: NonNull<EfiVoid>,
+) -> EfiStatus {
+    let handle = id(handle.as_ptr());
+    let protocol = unsafe { *protocol.as_ref() };
+    let status =
+        with_state(|state| {
+            let Some(entry) = state
+                .handles
+                .iter_mut()
+                .find(|entry| entry.handle == handle)
+            else {
+                return EfiStatus::INVALID_PARAMETER;
+            };
+            let Some((_, installed)) = entry.protocols.iter_mut().find(|(guid, installed)| {
+                **guid == protocol && *installed == old_interface.as_ptr()
+            }) else {
+                return EfiStatus::NOT_FOUND;
+            };
+            *installed = new_interface.as_ptr();
+            EfiStatus::SUCCESS
+        })
+        .unwrap_or_else(identity);
+
+    if status == EfiStatus::SUCCESS {
+        notify_installation(handle, &protocol);
+    }
+    status
+}
+
+extern "efiapi" fn handle_protocol(
+    handle: EfiHandle,
+    protocol: NonNull<EfiGuid>,
+    mut interface: NonNull<*mut EfiVoid>,
+) -> EfiStatus {
+    let handle = id(handle.as_ptr());
+    let protocol = unsafe { protocol.as_ref() };
+    match with_state(|state| state.interface(handle, protocol)) {
+        Ok(Some(found)) => {
+            unsafe { *interface.as_mut() = found };
+            EfiStatus::SUCCESS
+        }
+        Ok(None) => EfiStatus::UNSUPPORTED,
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn register_protocol_notify(
+    protocol: NonNull<EfiGuid>,
+    event: EfiEvent,
+    mut registration: NonNull<*mut EfiVoid>,
+) -> EfiStatus {
+    let protocol = unsafe { *protocol.as_ref() };
+    let event = id(event.as_ptr());
+    let id = with_state(|state| {
+        if !state.events.iter().any(|entry| entry.event == event) {
+            return None;
+        }
+        let id = state.new_id();
+        state.registrations.push(Registration {
+            registration: id,
+            protocol,
+            event,
+            handles: VecDeque::new(),
+        });
+        Some(id)
+    });
+
+    match id {
+        Ok(Some(id)) => {
+            unsafe { *registration.as_mut() = id as *mut EfiVoid };
+            EfiStatus::SUCCESS
+        }
+        Ok(None) => EfiStatus::INVALID_PARAMETER,
+        Err(status) => status,
+    }
+}
+
+/// Handles matching the search. ByRegisterNotify consumes the next handle of the registration.
+fn search(
+    search_type: EfiLocateSearchType,
+    protocol: Option<NonNull<EfiGuid>>,
+    search_key: Option<NonNull<EfiVoid>>,
+) -> Result<Vec<usize>, EfiStatus> {
+    let protocol = protocol.map(|protocol| unsafe { *protocol.as_ref() });
+    let handles = with_state(|state| match search_type {
+        EfiLocateSearchType::AllHandles => Ok(state.handles_with(None)),
+        EfiLocateSearchType::ByProtocol => protocol
+            .map(|protocol| state.handles_with(Some(&protocol)))
+            .ok_or(EfiStatus::INVALID_PARAMETER),
+        EfiLocateSearchType::ByRegisterNotify => {
+            let search_key = search_key.ok_or(EfiStatus::INVALID_PARAMETER)?;
+            let registration = state
+                .registrations
+                .iter_mut()
+                .find(|registration| registration.registration == id(search_key.as_ptr()))
+                .ok_or(EfiStatus::INVALID_PARAMETER)?;
+            Ok(registration.handles.pop_front().into_iter().collect())
+        }
+    })??;
+
+    if handles.is_empty() {
+        return Err(EfiStatus::NOT_FOUND);
+    }
+    Ok(handles)
+}
+
+extern "efiapi" fn locate_handle(
+    search_type: EfiLocateSearchType,
+    protocol: Option<NonNull<EfiGuid>>,
+    search_key: Option<NonNull<EfiVoid>>,
+    mut buffer_size: NonNull<usize>,
+    buffer: NonNull<EfiHandle>,
+) -> EfiStatus {
+    let handles = match search(search_type, protocol, search_key) {
+        Ok(handles) => handles,
+        Err(status) => return status,
+    };
+
+    let size = handles.len() * size_of::<EfiHandle>();
+    let available = unsafe { core::mem::replace(buffer_size.as_mut(), size) };
+    if available < size {
+        return EfiStatus::BUFFER_TOO_SMALL;
+    }
+
+    for (i, handle) in handles.into_iter().enumerate() {
+        unsafe { buffer.as_ptr().add(i).write(handle_of(handle)) };
+    }
+    EfiStatus::SUCCESS
+}
+
+extern "efiapi" fn locate_handle_buffer(
+    search_type: EfiLocateSearchType,
+    protocol: Option<NonNull<EfiGuid>>,
+    search_key: Option<NonNull<EfiVoid>>,
+    mut no_handles: NonNull<usize>,
+    mut buffer: NonNull<*mut EfiHandle>,
+) -> EfiStatus {
+    let handles = match search(search_type, protocol, search_key) {
+        Ok(handles) => handles,
+        Err(status) => return status,
+    };
+
+    let handles: Vec<EfiHandle> = handles.into_iter().map(handle_of).collect();
+    let pool = match pool_copy(&handles) {
+        Ok(pool) => pool,
+        Err(status) => return status,
+    };
+    unsafe {
+        *no_handles.as_mut() = handles.len();
+        *buffer.as_mut() = pool.as_ptr();
+    }
+    EfiStatus::SUCCESS
+}
+
+extern "efiapi" fn locate_protocol(
+    protocol: NonNull<EfiGuid>,
+    registration: Option<NonNull<EfiVoid>>,
+    mut interface: NonNull<*mut EfiVoid>,
+) -> EfiStatus {
+    let search_type = match registration {
+        Some(_) => EfiLocateSearchType::ByRegisterNotify,
+        None => EfiLocateSearchType::ByProtocol,
+    };
+    let handle = match search(search_type, Some(protocol), registration) {
+        Ok(handles) => handles[0],
+        Err(_) => return EfiStatus::NOT_FOUND,
+    };
+
+    let protocol = unsafe { protocol.as_ref() };
+    match with_state(|state| state.interface(handle, protocol)) {
+        Ok(Some(found)) => {
+            unsafe { *interface.as_mut() = found };
+            EfiStatus::SUCCESS
+        }
+        Ok(None) => EfiStatus::NOT_FOUND,
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn locate_device_path(
+    _protocol: NonNull<EfiGuid>,
+    _device_path: NonNull<NonNull<EfiDevicePathProtocol>>,
+    _device: NonNull<EfiHandle>,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+extern "efiapi" fn install_configuration_table(
+    _guid: NonNull<EfiGuid>,
+    _table: NonNull<EfiVoid>,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+//
+// Image Services
+//
+
+/// Loads only from buffers, as there are no file systems to load from.
+/// Any buffer with the MZ signature of a PE/COFF image is accepted and never run.
+extern "efiapi" fn load_image(
+    _boot_policy: bool,
+    _parent_image_handle: EfiHandle,
+    _device_path: Option<NonNull<EfiDevicePathProtocol>>,
+    source_buffer: Option<NonNull<EfiVoid>>,
+    source_size: usize,
+    mut image_handle: NonNull<EfiHandle>,
+) -> EfiStatus {
+    let Some(source_buffer) = source_buffer else {
+        return EfiStatus::NOT_FOUND;
+    };
+    let source =
+        unsafe { core::slice::from_raw_parts(source_buffer.as_ptr().cast::<u8>(), source_size) };
+    if !source.starts_with(b"MZ") {
+        return EfiStatus::LOAD_ERROR;
+    }
+
+    let image = with_state(|state| {
+        let image = state.new_id();
+        state.images.push(ImageEntry {
+            image,
+            exit_status: EfiStatus::SUCCESS,
+            exit_data: None,
+        });
+        image
+    });
+    let image = match image {
+        Ok(image) => image,
+        Err(status) => return status,
+    };
+    unsafe { *image_handle.as_mut() = handle_of(image) };
+    EfiStatus::SUCCESS
+}
+
+/// Returns the exit set with [`super::MockFirmware::set_image_exit`], with the exit data in a pool buffer.
+extern "efiapi" fn start_image(
+    image_handle: EfiHandle,
+    mut exit_data_size: NonNull<usize>,
+    exit_data: Option<NonNull<*mut U16Str>>,
+) -> EfiStatus {
+    let image = id(image_handle.as_ptr());
+    let exit = with_state(|state| {
+        let entry = state.images.iter().find(|entry| entry.image == image)?;
+        Some((entry.exit_status, entry.exit_data.clone()))
+    });
+    let (status, data) = match exit {
+        Ok(Some(exit)) => exit,
+        Ok(None) => return EfiStatus::INVALID_PARAMETER,
+        Err(status) => return status,
+    };
+
+    unsafe { *exit_data_size.as_mut() = 0 };
+    // The firmware frees the exit data of callers that do not take it.
+    if let (Some(data), Some(mut exit_data)) = (data, exit_data) {
+        let pool = match pool_copy(&data) {
+            Ok(pool) => pool,
+            Err(status) => return status,
+        };
+        unsafe {
+            *exit_data_size.as_mut() = size_of_val(data.as_slice());
+            *exit_data.as_mut() = pool.as_ptr() as *mut U16Str;
+        }
+    }
+    status
+}
+
+extern "efiapi" fn exit(
+    _image_handle: EfiHandle,
+    _exit_status: EfiStatus,
+    _exit_data_size: usize,
+    _exit_data: Option<NonNull<U16Str>>,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+extern "efiapi" fn unload_image(image_handle: EfiHandle) -> EfiStatus {
+    let image = id(image_handle.as_ptr());
+    with_state(|state| {
+        let Some(position) = state.images.iter().position(|entry| entry.image == image) else {
+            return EfiStatus::INVALID_PARAMETER;
+        };
+        state.images.remove(position);
+        EfiStatus::SUCCESS
+    })
+    .unwrap_or_else(identity)
+}
+
+extern "efiapi" fn exit_boot_services(image_handle: EfiHandle, map_key: usize) -> EfiStatus {
+    let image_handle = id(image_handle.as_ptr());
+    let status = with_state(|state| {
+        if state.handle(image_handle).is_none() || map_key != state.map_key {
+            return EfiStatus::INVALID_PARAMETER;
+        }
+        state.exited_boot_services = true;
+        EfiStatus::SUCCESS
+    })
+    .unwrap_or_else(identity);
+    if status != EfiStatus::SUCCESS {
+        return status;
+    }
+
+    let events = with_state(|state| {
+        state
+            .events
+            .iter()
+            .filter(|entry| entry.group == Some(event_group::EXIT_BOOT_SERVICES))
+            .map(|entry| entry.event)
+            .collect::<Vec<_>>()
+    });
+    for event in events.unwrap_or_default() {
+        if let Ok(Some(runnable)) = with_state(|state| state.signal(event)) {
+            dispatch(runnable);
+        }
+    }
+    EfiStatus::SUCCESS
+}
+
+//
+// Miscellaneous Services
+//
+
+extern "efiapi" fn get_next_monotonic_count(mut count: NonNull<u64>) -> EfiStatus {
+    let next = with_state(|state| {
+        state.monotonic_count += 1;
+        state.monotonic_count
+    });
+    match next {
+        Ok(next) => unsafe { *count.as_mut() = next },
+        Err(status) => return status,
+    }
+    EfiStatus::SUCCESS
+}
+
+/// Returns immediately and only accumulates the time in [`super::MockFirmware::stalled`].
+extern "efiapi" fn stall(microseconds: usize) -> EfiStatus {
+    match with_state(|state| state.stalled += Duration::from_micros(microseconds as u64)) {
+        Ok(()) => EfiStatus::SUCCESS,
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn set_watchdog_timer(
+    timeout: usize,
+    _watchdog_code: u64,
+    _data_size: usize,
+    _watchdog_data: Option<NonNull<U16Str>>,
+) -> EfiStatus {
+    let timeout = (timeout != 0).then(|| Duration::from_secs(timeout as u64));
+    match with_state(|state| state.watchdog_timeout = timeout) {
+        Ok(()) => EfiStatus::SUCCESS,
+        Err(status) => status,
+    }
+}
+
+//
+// DriverSupport Services
+//
+
+extern "efiapi" fn connect_controller(
+    _controller_handle: EfiHandle,
+    _driver_image_handle: Option<NonNull<EfiHandle>>,
+    _remaining_device_path: Option<NonNull<EfiDevicePathProtocol>>,
+    _recursive: bool,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+extern "efiapi" fn disconnect_controller(
+    _controller_handle: EfiHandle,
+    _driver_image_handle: Option<EfiHandle>,
+    _child_handle: Option<EfiHandle>,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+//
+// Open and Close Protocol Services
+//
+
+/// Opens are not recorded, so OpenProtocolInformation always reports no agents.
+extern "efiapi" fn open_protocol(
+    handle: EfiHandle,
+    protocol: NonNull<EfiGuid>,
+    interface: Option<NonNull<*mut EfiVoid>>,
+    agent_handle: EfiHandle,
+    controller_handle: EfiHandle,
+    attributes: EfiOpenProtocolAttributes,
+) -> EfiStatus {
+    let handle = id(handle.as_ptr());
+    let protocol = unsafe { protocol.as_ref() };
+    let found = match with_state(|state| state.interface(handle, protocol)) {
+        Ok(Some(found)) => found,
+        Ok(None) => return EfiStatus::UNSUPPORTED,
+        Err(status) => return status,
+    };
+
+    if attributes == EfiOpenProtocolAttributes::TEST_PROTOCOL {
+        return EfiStatus::SUCCESS;
+    }
+    let Some(mut interface) = interface else {
+        return EfiStatus::INVALID_PARAMETER;
+    };
+    unsafe { *interface.as_mut() = found };
+
+    // Opening again with the same agent, controller and attributes only counts up.
+    let (agent, controller) = (id(agent_handle.as_ptr()), id(controller_handle.as_ptr()));
+    let recorded = with_state(|state| {
+        let same = |open: &&mut OpenEntry| {
+            open.handle == handle
+                && open.protocol == *protocol
+                && open.agent == agent
+                && open.controller == controller
+                && open.attributes == attributes
+        };
+        match state.opens.iter_mut().find(same) {
+            Some(open) => open.open_count += 1,
+            None => state.opens.push(OpenEntry {
+                handle,
+                protocol: *protocol,
+                agent,
+                controller,
+                attributes,
+                open_count: 1,
+            }),
+        }
+    });
+    match recorded {
+        Ok(()) => EfiStatus::SUCCESS,
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn close_protocol(
+    handle: EfiHandle,
+    protocol: NonNull<EfiGuid>,
+    agent_handle: EfiHandle,
+    controller_handle: EfiHandle,
+) -> EfiStatus {
+    let handle = id(handle.as_ptr());
+    let protocol = unsafe { protocol.as_ref() };
+    let (agent, controller) = (id(agent_handle.as_ptr()), id(controller_handle.as_ptr()));
+    with_state(|state| {
+        let opens = state.opens.len();
+        state.opens.retain(|open| {
+            open.handle != handle
+                || open.protocol != *protocol
+                || open.agent != agent
+                || open.controller != controller
+        });
+        if state.opens.len() == opens {
+            EfiStatus::NOT_FOUND
+        } else {
+            EfiStatus::SUCCESS
+        }
+    })
+    .unwrap_or_else(identity)
+}
+
+extern "efiapi" fn open_protocol_information(
+    handle: EfiHandle,
+    protocol: NonNull<EfiGuid>,
+    mut entry_buffer: NonNull<*mut EfiOpenProtocolInformationEntry>,
+    mut entry_count: NonNull<usize>,
+) -> EfiStatus {
+    let handle = id(handle.as_ptr());
+    let protocol = unsafe { protocol.as_ref() };
+    let entries = with_state(|state| {
+        state.interface(handle, protocol)?;
+        Some(
+            state
+                .opens
+                .iter()
+                .filter(|open| open.handle == handle && open.protocol == *protocol)
+                .map(|open| EfiOpenProtocolInformationEntry {
+                    agent_handle: Some(handle_of(open.agent)),
+                    controller_handle: Some(handle_of(open.controller)),
+                    attributes: open.attributes,
+                    open_count: open.open_count,
+                })
+                .collect::<Vec<_>>(),
+        )
+    });
+    let entries = match entries {
+        Ok(Some(entries)) => entries,
+        Ok(None) => return EfiStatus::NOT_FOUND,
+        Err(status) => return status,
+    };
+
+    let pool = match pool_copy(&entries) {
+        Ok(pool) => pool,
+        Err(status) => return status,
+    };
+    unsafe {
+        *entry_buffer.as_mut() = pool.as_ptr();
+        *entry_count.as_mut() = entries.len();
+    }
+    EfiStatus::SUCCESS
+}
+
+//
+// Library Services
+//
+
+extern "efiapi" fn protocols_per_handle(
+    handle: EfiHandle,
+    mut protocol_buffer: NonNull<*mut *mut EfiGuid>,
+    mut protocol_buffer_count: NonNull<usize>,
+) -> EfiStatus {
+    let handle = id(handle.as_ptr());
+    let guids = with_state(|state| {
+        let entry = state.handle(handle)?;
+        Some(
+            entry
+                .protocols
+                .iter()
+                .map(|(guid, _)| &**guid as *const EfiGuid as *mut EfiGuid)
+                .collect::<Vec<_>>(),
+        )
+    });
+    let guids = match guids {
+        Ok(Some(guids)) => guids,
+        Ok(None) => return EfiStatus::INVALID_PARAMETER,
+        Err(status) => return status,
+    };
+
+    let pool = match pool_copy(&guids) {
+        Ok(pool) => pool,
+        Err(status) => return status,
+    };
+    unsafe {
+        *protocol_buffer.as_mut() = pool.as_ptr();
+        *protocol_buffer_count.as_mut() = guids.len();
+    }
+    EfiStatus::SUCCESS
+}
+
+extern "efiapi" fn multiple_protocol_interfaces_stub(_handle: NonNull<EfiHandle>) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+/// Defining variadic functions is unstable, so the variadic slots get a stub that ignores the varargs.
+fn multiple_protocol_interfaces() -> EfiInstallMultipleProtocolInterfaces {
+    let stub: extern "efiapi" fn(NonNull<EfiHandle>) -> EfiStatus =
+        multiple_protocol_interfaces_stub;
+    unsafe { core::mem::transmute(stub) }
+}
+
+//
+// 32-bit CRC Services
+//
+
+extern "efiapi" fn calculate_crc32(
+    data: NonNull<EfiVoid>,
+    data_size: usize,
+    mut crc32: NonNull<u32>,
+) -> EfiStatus {
+    // Like EDK II, which rejects empty data.
+    if data_size == 0 {
+        return EfiStatus::INVALID_PARAMETER;
+    }
+
+    let data = unsafe { core::slice::from_raw_parts(data.as_ptr().cast::<u8>(), data_size) };
+    unsafe { *crc32.as_mut() = crate::crc32::crc32(data) };
+    EfiStatus::SUCCESS
+}
+
+//
+// Miscellaneous Services
+//
+
+extern "efiapi" fn copy_mem(
+    destination: NonNull<EfiVoid>,
+    source: NonNull<EfiVoid>,
+    length: usize,
+) {
+    unsafe {
+        core::ptr::copy(
+            source.as_ptr().cast::<u8>(),
+            destination.as_ptr().cast::<u8>(),
+            length,
+        )
+    };
+}
+
+extern "efiapi" fn set_mem(buffer: NonNull<EfiVoid>, size: usize, value: u8) {
+    unsafe { buffer.as_ptr().cast::<u8>().write_bytes(value, size) };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::MockFirmware;
+
+    #[test]
+    fn without_state() {
+        assert_eq!(stall(1), EfiStatus::NOT_STARTED);
+
+        let firmware = MockFirmware::new();
+        assert_eq!(with_state(|_| stall(1)), Ok(EfiStatus::ACCESS_DENIED));
+        assert_eq!(stall(1), EfiStatus::SUCCESS);
+        assert_eq!(firmware.stalled(), Duration::from_micros(1));
+    }
+}
@@ -0,0 +1,201 @@
+//! MP services of the mock firmware. The APs run procedures one after another on the calling thread.
+
+use core::{ptr::NonNull, time::Duration};
+
+use crate::{
+    protocol::mp_services::{
+        EfiApProcedure, EfiCpuPhysicalLocation, EfiMpServicesProtocol, EfiProcessorInformation,
+    },
+    EfiEvent, EfiStatus, EfiVoid,
+};
+
+use super::with_state;
+
+pub(super) fn protocol() -> EfiMpServicesProtocol {
+    EfiMpServicesProtocol {
+        get_number_of_processors,
+        get_processor_info,
+        startup_all_aps,
+        startup_this_ap,
+        switch_bsp,
+        enable_disable_ap,
+        who_am_i,
+    }
+}
+
+extern "efiapi" fn get_number_of_processors(
+    _this: NonNull<EfiMpServicesProtocol>,
+    mut number_of_processors: NonNull<usize>,
+    mut number_of_enabled_processors: NonNull<usize>,
+) -> EfiStatus {
+    match with_state(|state| state.processors) {
+        Ok(processors) => {
+            unsafe {
+                *number_of_processors.as_mut() = processors;
+                *number_of_enabled_processors.as_mut() = processors;
+            }
+            EfiStatus::SUCCESS
+        }
+        Err(status) => status,
+    }
+}
+
+/// Processor `n` has APIC ID `n` and is core `n` of package 0. Processor 0 is the BSP.
+extern "efiapi" fn get_processor_info(
+    _this: NonNull<EfiMpServicesProtocol>,
+    processor_number: usize,
+    mut processor_info_buffer: NonNull<EfiProcessorInformation>,
+) -> EfiStatus {
+    let processors = match with_state(|state| state.processors) {
+        Ok(processors) => processors,
+        Err(status) => return status,
+    };
+    if processor_number >= processors {
+        return EfiStatus::NOT_FOUND;
+    }
+
+    let mut status_flag = EfiProcessorInformation::PROCESSOR_ENABLED_BIT
+        | EfiProcessorInformation::PROCESSOR_HEALTH_STATUS_BIT;
+    if processor_number == 0 {
+        status_flag |= EfiProcessorInformation::PROCESSOR_AS_BSP_BIT;
+    }
+    unsafe {
+        *processor_info_buffer.as_mut() = EfiProcessorInformation {
+            processor_id: processor_number as u64,
+            status_flag,
+            location: EfiCpuPhysicalLocation {
+                package: 0,
+                core: processor_number as u32,
+                thread: 0,
+            },
+            _extended_information: [0; 6],
+        };
+    }
+    EfiStatus::SUCCESS
+}
+
+/// Only blocking calls are emulated, without the list of failed APs.
+/// Waiting forever for a hung AP returns TIMEOUT instead of hanging the test.
+extern "efiapi" fn startup_all_aps(
+    _this: NonNull<EfiMpServicesProtocol>,
+    procedure: EfiApProcedure,
+    _single_thread: bool,
+    wait_event: EfiEvent,
+    timeout_in_micro_seconds: usize,
+    procedure_argument: NonNull<EfiVoid>,
+    failed_cpu_list: Option<NonNull<*mut usize>>,
+) -> EfiStatus {
+    if !wait_event.as_ptr().is_null() || failed_cpu_list.is_some() {
+        return EfiStatus::UNSUPPORTED;
+    }
+    let processors = match with_state(|state| state.processors) {
+        Ok(processors) => processors,
+        Err(status) => return status,
+    };
+    if processors < 2 {
+        return EfiStatus::NOT_STARTED;
+    }
+
+    let mut status = EfiStatus::SUCCESS;
+    for processor_number in 1..processors {
+        let result = run(
+            processor_number,
+            procedure,
+            timeout_in_micro_seconds,
+            procedure_argument,
+        );
+        if result != EfiStatus::SUCCESS {
+            status = result;
+        }
+    }
+    status
+}
+
+/// Only blocking calls are emulated, like [`startup_all_aps`].
+extern "efiapi" fn startup_this_ap(
+    _this: NonNull<EfiMpServicesProtocol>,
+    procedure: EfiApProcedure,
+    processor_number: usize,
+    wait_event: EfiEvent,
+    timeout_in_micro_seconds: usize,
+    procedure_argument: NonNull<EfiVoid>,
+    finished: Option<NonNull<bool>>,
+) -> EfiStatus {
+    if !wait_event.as_ptr().is_null() || finished.is_some() {
+        return EfiStatus::UNSUPPORTED;
+    }
+    let processors = match with_state(|state| state.processors) {
+        Ok(processors) => processors,
+        Err(status) => return status,
+    };
+    match processor_number {
+        0 => EfiStatus::INVALID_PARAMETER,
+        _ if processor_number >= processors => EfiStatus::NOT_FOUND,
+        _ => run(
+            processor_number,
+            procedure,
+            timeout_in_micro_seconds,
+            procedure_argument,
+        ),
+    }
+}
+
+/// Runs `procedure` as the AP `processor_number`, without holding the state.
+/// A hung AP does not run it, and the timeout passes as a stall.
+fn run(
+    processor_number: usize,
+    procedure: EfiApProcedure,
+    timeout_in_micro_seconds: usize,
+    procedure_argument: NonNull<EfiVoid>,
+) -> EfiStatus {
+    let hung = with_state(|state| {
+        let hung = state.hung_processors.contains(&processor_number);
+        if hung {
+            state.stalled += Duration::from_micros(timeout_in_micro_seconds as u64);
+        } else {
+            state.current_processor = processor_number;
+        }
+        hung
+    });
+    match hung {
+        Ok(true) => EfiStatus::TIMEOUT,
+        Ok(false) => {
+            procedure(procedure_argument);
+            match with_state(|state| state.current_processor = 0) {
+                Ok(()) => EfiStatus::SUCCESS,
+                Err(status) => status,
+            }
+        }
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn switch_bsp(
+    _this: NonNull<EfiMpServicesProtocol>,
+    _processor_number: usize,
+    _enable_old_bsp: bool,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+extern "efiapi" fn enable_disable_ap(
+    _this: NonNull<EfiMpServicesProtocol>,
+    _processor_number: usize,
+    _enable_ap: bool,
+    _health_flag: Option<NonNull<u32>>,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+extern "efiapi" fn who_am_i(
+    _this: NonNull<EfiMpServicesProtocol>,
+    mut processor_number: NonNull<usize>,
+) -> EfiStatus {
+    match with_state(|state| state.current_processor) {
+        Ok(current_processor) => {
+            unsafe { *processor_number.as_mut() = current_processor };
+            EfiStatus::SUCCESS
+        }
+        Err(status) => status,
+    }
+}
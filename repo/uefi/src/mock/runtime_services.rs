@@ -0,0 +1,180 @@
+//! Runtime services of the mock firmware. Only the time services are emulated.
+
+use core::ptr::NonNull;
+
+use crate::{
+    efi_boot_services::{EfiMemoryDescriptor, EfiPhysicalAddress},
+    efi_runtime_services::{EfiResetType, EfiTime, EfiTimeCapabilities},
+    EfiGuid, EfiRuntimeServices, EfiStatus, EfiVoid, U16Str,
+};
+
+use super::{header, table_crc32, with_state, EFI_RUNTIME_SERVICES_SIGNATURE};
+
+pub(super) fn table() -> EfiRuntimeServices {
+    let mut table = EfiRuntimeServices {
+        hdr: header::<EfiRuntimeServices>(EFI_RUNTIME_SERVICES_SIGNATURE),
+        get_time,
+        set_time,
+        get_wakeup_time,
+        set_wakeup_time,
+        set_virtual_address_map,
+        convert_pointer,
+        get_variable,
+        get_next_variable_name,
+        set_variable,
+        get_next_high_monotonic_count,
+        reset_system,
+        update_capsule,
+        query_capsule_capabilities,
+        query_variable_info,
+    };
+    table.hdr.crc32 = table_crc32(&table);
+    table
+}
+
+//
+// Time Services
+//
+
+extern "efiapi" fn get_time(
+    mut time: NonNull<EfiTime>,
+    capabilities: Option<NonNull<EfiTimeCapabilities>>,
+) -> EfiStatus {
+    match with_state(|state| state.time) {
+        Ok(now) => unsafe { *time.as_mut() = now },
+        Err(status) => return status,
+    }
+    if let Some(mut capabilities) = capabilities {
+        unsafe {
+            *capabilities.as_mut() = EfiTimeCapabilities {
+                resolution: 1,
+                accuracy: 50_000_000,
+                sets_to_zero: false,
+            }
+        };
+    }
+    EfiStatus::SUCCESS
+}
+
+extern "efiapi" fn set_time(time: NonNull<EfiTime>) -> EfiStatus {
+    let time = unsafe { *time.as_ref() };
+    if time.validate().is_err() {
+        return EfiStatus::INVALID_PARAMETER;
+    }
+
+    match with_state(|state| state.time = time) {
+        Ok(()) => EfiStatus::SUCCESS,
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn get_wakeup_time(
+    _enabled: NonNull<bool>,
+    _pending: NonNull<bool>,
+    _time: NonNull<EfiTime>,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+extern "efiapi" fn set_wakeup_time(_enable: bool, _time: Option<NonNull<EfiTime>>) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+//
+// Virtual Memory Services
+//
+
+extern "efiapi" fn set_virtual_address_map(
+    _memory_map_size: usize,
+    _descriptor_size: usize,
+    _descriptor_version: u32,
+    _virtual_map: NonNull<EfiMemoryDescriptor>,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+extern "efiapi" fn convert_pointer(
+    _debug_disposition: usize,
+    _address: NonNull<*mut EfiVoid>,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+//
+// Variable Services
+//
+
+extern "efiapi" fn get_variable(
+    _variable_name: NonNull<U16Str>,
+    _vendor_guid: NonNull<EfiGuid>,
+    _attributes: Option<NonNull<u32>>,
+    _data_size: NonNull<usize>,
+    _data: Option<NonNull<EfiVoid>>,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+extern "efiapi" fn get_next_variable_name(
+    _variable_name_size: NonNull<usize>,
+    _variable_name: NonNull<u16>,
+    _vendor_guid: NonNull<EfiGuid>,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+extern "efiapi" fn set_variable(
+    _variable_name: NonNull<U16Str>,
+    _vendor_guid: NonNull<EfiGuid>,
+    _attributes: u32,
+    _data_size: usize,
+    _data: Option<NonNull<EfiVoid>>,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+extern "efiapi" fn query_variable_info(
+    _attributes: u32,
+    _maximum_variable_storage_size: NonNull<u64>,
+    _remaining_variable_storage_size: NonNull<u64>,
+    _maximum_variable_size: NonNull<u64>,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+//
+// Miscellaneous Services
+//
+
+extern "efiapi" fn get_next_high_monotonic_count(_high_count: NonNull<u32>) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+/// There is no platform to reset. Aborts the test process, since panics cannot unwind out of efiapi functions.
+extern "efiapi" fn reset_system(
+    reset_type: EfiResetType,
+    reset_status: EfiStatus,
+    _data_size: usize,
+    _reset_data: Option<NonNull<EfiVoid>>,
+) -> ! {
+    panic!(
+        "ResetSystem({:?}, {:?}) on the mock firmware",
+        reset_type, reset_status
+    )
+}
+
+extern "efiapi" fn update_capsule(
+    _capsule_header_array: NonNull<NonNull<EfiVoid>>,
+    _capsule_count: usize,
+    _scatter_gather_list: EfiPhysicalAddress,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
+
+extern "efiapi" fn query_capsule_capabilities(
+    _capsule_header_array: NonNull<NonNull<EfiVoid>>,
+    _capsule_count: usize,
+    _maximum_capsule_size: NonNull<u64>,
+    _reset_type: NonNull<EfiResetType>,
+) -> EfiStatus {
+    EfiStatus::UNSUPPORTED
+}
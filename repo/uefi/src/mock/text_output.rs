@@ -0,0 +1,159 @@
+//! Console output of the mock firmware, captured into a string.
+
+use core::ptr::NonNull;
+
+use crate::{
+    protocol::simple_text::{SimpleTextOutputMode, SimpleTextOutputProtocol},
+    EfiStatus, U16Str,
+};
+
+use super::with_state;
+
+/// Only mode 0 exists, like a serial console.
+const COLUMNS: usize = 80;
+const ROWS: usize = 25;
+
+pub(super) fn mode() -> SimpleTextOutputMode {
+    SimpleTextOutputMode {
+        max_mode: 1,
+        mode: 0,
+        attribute: 0x07,
+        cursor_column: 0,
+        cursor_row: 0,
+        cursor_visible: true,
+    }
+}
+
+pub(super) fn protocol(mode: NonNull<SimpleTextOutputMode>) -> SimpleTextOutputProtocol {
+    SimpleTextOutputProtocol {
+        reset,
+        output_string,
+        test_string,
+        query_mode,
+        set_mode,
+        set_attribute,
+        clear_screen,
+        set_cursor_position,
+        enable_cursor,
+        mode,
+    }
+}
+
+fn mode_of(this: NonNull<SimpleTextOutputProtocol>) -> &'static mut SimpleTextOutputMode {
+    let mut mode = unsafe { this.as_ref().mode };
+    unsafe { mode.as_mut() }
+}
+
+extern "efiapi" fn reset(
+    this: NonNull<SimpleTextOutputProtocol>,
+    _extended_verification: bool,
+) -> EfiStatus {
+    clear_screen(this)
+}
+
+/// Appends the string to the captured output, with unpaired surrogates replaced.
+extern "efiapi" fn output_string(
+    this: NonNull<SimpleTextOutputProtocol>,
+    string: NonNull<U16Str>,
+) -> EfiStatus {
+    let mut units = string.cast::<u16>().as_ptr();
+    let mode = mode_of(this);
+    let written = with_state(|state| {
+        let decoded = core::iter::from_fn(|| {
+            let unit = unsafe { *units };
+            if unit == 0 {
+                return None;
+            }
+            units = unsafe { units.add(1) };
+            Some(unit)
+        });
+        for c in char::decode_utf16(decoded) {
+            let c = c.unwrap_or(char::REPLACEMENT_CHARACTER);
+            match c {
+                '\r' => mode.cursor_column = 0,
+                '\n' => mode.cursor_row = (mode.cursor_row + 1).min(ROWS as i32 - 1),
+                _ => mode.cursor_column = (mode.cursor_column + 1) % COLUMNS as i32,
+            }
+            state.output.push(c);
+        }
+    });
+    match written {
+        Ok(()) => EfiStatus::SUCCESS,
+        Err(status) => status,
+    }
+}
+
+extern "efiapi" fn test_string(
+    _this: NonNull<SimpleTextOutputProtocol>,
+    _string: NonNull<U16Str>,
+) -> EfiStatus {
+    EfiStatus::SUCCESS
+}
+
+extern "efiapi" fn query_mode(
+    _this: NonNull<SimpleTextOutputProtocol>,
+    mode_number: usize,
+    mut columns: NonNull<usize>,
+    mut rows: NonNull<usize>,
+) -> EfiStatus {
+    if mode_number != 0 {
+        return EfiStatus::UNSUPPORTED;
+    }
+
+    unsafe {
+        *columns.as_mut() = COLUMNS;
+        *rows.as_mut() = ROWS;
+    }
+    EfiStatus::SUCCESS
+}
+
+extern "efiapi" fn set_mode(
+    this: NonNull<SimpleTextOutputProtocol>,
+    mode_number: usize,
+) -> EfiStatus {
+    if mode_number != 0 {
+        return EfiStatus::UNSUPPORTED;
+    }
+    clear_screen(this)
+}
+
+extern "efiapi" fn set_attribute(
+    this: NonNull<SimpleTextOutputProtocol>,
+    attribute: usize,
+) -> EfiStatus {
+    if attribute > 0x7F {
+        return EfiStatus::UNSUPPORTED;
+    }
+    mode_of(this).attribute = attribute as i32;
+    EfiStatus::SUCCESS
+}
+
+extern "efiapi" fn clear_screen(this: NonNull<SimpleTextOutputProtocol>) -> EfiStatus {
+    let mode = mode_of(this);
+    mode.cursor_column = 0;
+    mode.cursor_row = 0;
+    EfiStatus::SUCCESS
+}
+
+extern "efiapi" fn set_cursor_position(
+    this: NonNull<SimpleTextOutputProtocol>,
+    column: usize,
+    row: usize,
+) -> EfiStatus {
+    if column >= COLUMNS || row >= ROWS {
+        return EfiStatus::UNSUPPORTED;
+    }
+
+    let mode = mode_of(this);
+    mode.cursor_column = column as i32;
+    mode.cursor_row = row as i32;
+    EfiStatus::SUCCESS
+}
+
+extern "efiapi" fn enable_cursor(
+    this: NonNull<SimpleTextOutputProtocol>,
+    visible: bool,
+) -> EfiStatus {
+    mode_of(this).cursor_visible = visible;
+    EfiStatus::SUCCESS
+}
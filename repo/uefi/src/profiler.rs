@@ -0,0 +1,298 @@
+//! Durations of boot phases, e.g. locating GOP or getting the memory map.
+//!
+//! Time is read from EFI_TIMESTAMP_PROTOCOL when the firmware provides it, otherwise from the TSC
+//! calibrated against Stall. Since the protocol is gone after ExitBootServices, switch to the TSC
+//! with [`BootProfiler::use_tsc`] before measuring across it.
+
+use core::{arch::x86_64::_rdtsc, fmt, time::Duration};
+
+use crate::{protocol::timestamp::EfiTimestampProtocol, EfiBootServices, EfiResult};
+
+/// Maximum number of spans kept by [`BootProfiler`]. Further spans are dropped.
+pub const MAX_SPANS: usize = 32;
+
+const CALIBRATION_PERIOD: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockSource {
+    Timestamp,
+    Tsc,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Counter<'a> {
+    Timestamp(&'a EfiTimestampProtocol, u64),
+    Tsc,
+}
+
+impl<'a> Counter<'a> {
+    fn read(&self) -> u64 {
+        match self {
+            Counter::Timestamp(protocol, _) => protocol.get_timestamp(),
+            Counter::Tsc => unsafe { _rdtsc() },
+        }
+    }
+
+    /// Value just before the counter rolls over to 0.
+    fn end_value(&self) -> u64 {
+        match self {
+            Counter::Timestamp(_, end_value) => *end_value,
+            Counter::Tsc => u64::MAX,
+        }
+    }
+}
+
+/// A named phase, with times relative to the creation of the profiler.
+#[derive(Debug, Clone, Copy)]
+pub struct Span {
+    name: &'static str,
+    start: Duration,
+    end: Option<Duration>,
+}
+
+impl Span {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn start(&self) -> Duration {
+        self.start
+    }
+
+    /// `None` while the span is still open.
+    pub fn end(&self) -> Option<Duration> {
+        self.end
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        self.end.map(|end| end.saturating_sub(self.start))
+    }
+}
+
+/// Identifies a span opened by [`BootProfiler::begin`].
+#[derive(Debug, Clone, Copy)]
+pub struct SpanId(usize);
+
+pub struct BootProfiler<'a> {
+    counter: Counter<'a>,
+    frequency: u64,
+    origin: u64,
+    // Time already elapsed when `origin` was taken, for switches of the counter.
+    offset: Duration,
+    spans: [Option<Span>; MAX_SPANS],
+    len: usize,
+}
+
+impl<'a> BootProfiler<'a> {
+    /// Starts the clock. Boot services are needed to find the timestamp protocol or to calibrate the TSC.
+    pub fn new(boot_services: &'a EfiBootServices) -> EfiResult<Self> {
+        let timestamp = boot_services
+            .locate_protocol::<EfiTimestampProtocol>()
+            .and_then(|protocol| Ok((protocol, protocol.get_properties()?)));
+
+        let (counter, frequency) = match timestamp {
+            Ok((protocol, properties)) => (
+                Counter::Timestamp(protocol, properties.end_value()),
+                properties.frequency(),
+            ),
+            Err(_) => (Counter::Tsc, calibrate_tsc(boot_services)?),
+        };
+
+        Ok(Self {
+            counter,
+            frequency,
+            origin: counter.read(),
+            offset: Duration::ZERO,
+            spans: [None; MAX_SPANS],
+            len: 0,
+        })
+    }
+
+    pub fn clock_source(&self) -> ClockSource {
+        match self.counter {
+            Counter::Timestamp(..) => ClockSource::Timestamp,
+            Counter::Tsc => ClockSource::Tsc,
+        }
+    }
+
+    /// Ticks per second of the current clock source.
+    pub fn frequency(&self) -> u64 {
+        self.frequency
+    }
+
+    /// Time since the profiler was created.
+    pub fn elapsed(&self) -> Duration {
+        let ticks = ticks_between(self.origin, self.counter.read(), self.counter.end_value());
+        self.offset + ticks_to_duration(ticks, self.frequency)
+    }
+
+    /// Continues on the TSC, so that the profiler keeps working after ExitBootServices.
+    /// Does nothing if the TSC is already in use.
+    pub fn use_tsc(&mut self, boot_services: &EfiBootServices) -> EfiResult<()> {
+        if self.clock_source() == ClockSource::Tsc {
+            return Ok(());
+        }
+
+        let frequency = calibrate_tsc(boot_services)?;
+        self.offset = self.elapsed();
+        self.counter = Counter::Tsc;
+        self.frequency = frequency;
+        self.origin = self.counter.read();
+        Ok(())
+    }
+
+    pub fn begin(&mut self, name: &'static str) -> SpanId {
+        let id = SpanId(self.len);
+        self.len = (self.len + 1).min(MAX_SPANS + 1);
+        let start = self.elapsed();
+        if let Some(span) = self.spans.get_mut(id.0) {
+            *span = Some(Span {
+                name,
+                start,
+                end: None,
+            });
+        }
+        id
+    }
+
+    pub fn end(&mut self, id: SpanId) {
+        let end = self.elapsed();
+        if let Some(Some(span)) = self.spans.get_mut(id.0) {
+            span.end = Some(end);
+        }
+    }
+
+    /// Runs `f` in a span named `name`.
+    pub fn measure<R>(&mut self, name: &'static str, f: impl FnOnce() -> R) -> R {
+        let id = self.begin(name);
+        let result = f();
+        self.end(id);
+        result
+    }
+
+    /// Whether spans were dropped because more than [`MAX_SPANS`] were begun.
+    pub fn overflowed(&self) -> bool {
+        self.len > MAX_SPANS
+    }
+
+    pub fn spans(&self) -> impl Iterator<Item = &Span> {
+        self.spans.iter().flatten()
+    }
+
+    /// Copies the recorded spans out, detached from boot services.
+    pub fn timings(&self) -> BootTimings {
+        BootTimings {
+            clock_source: self.clock_source(),
+            spans: self.spans,
+            overflowed: self.overflowed(),
+        }
+    }
+}
+
+/// Spans recorded by [`BootProfiler`], handed over to the kernel.
+#[derive(Debug, Clone, Copy)]
+pub struct BootTimings {
+    clock_source: ClockSource,
+    spans: [Option<Span>; MAX_SPANS],
+    overflowed: bool,
+}
+
+impl BootTimings {
+    /// Clock source at the time the timings were taken.
+    pub fn clock_source(&self) -> ClockSource {
+        self.clock_source
+    }
+
+    pub fn spans(&self) -> impl Iterator<Item = &Span> {
+        self.spans.iter().flatten()
+    }
+
+    pub fn overflowed(&self) -> bool {
+        self.overflowed
+    }
+}
+
+impl fmt::Display for BootTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "boot timings ({:?}):", self.clock_source)?;
+        for span in self.spans() {
+            let start = span.start();
+            write!(
+                f,
+                "  {:>6}.{:03} ms  {:<24}",
+                start.as_millis(),
+                start.subsec_micros() % 1000,
+                span.name()
+            )?;
+            match span.duration() {
+                Some(duration) => writeln!(
+                    f,
+                    " {:>6}.{:03} ms",
+                    duration.as_millis(),
+                    duration.subsec_micros() % 1000
+                )?,
+                None => writeln!(f, " (not ended)")?,
+            }
+        }
+        if self.overflowed {
+            writeln!(f, "  (more spans were dropped)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Measures TSC ticks per second across a Stall.
+pub fn calibrate_tsc(boot_services: &EfiBootServices) -> EfiResult<u64> {
+    let start = unsafe { _rdtsc() };
+    boot_services.stall(CALIBRATION_PERIOD)?;
+    let end = unsafe { _rdtsc() };
+
+    let ticks = u128::from(end.wrapping_sub(start));
+    let frequency = ticks * 1_000_000_000 / CALIBRATION_PERIOD.as_nanos();
+    match u64::try_from(frequency) {
+        Ok(frequency) if frequency != 0 => Ok(frequency),
+        _ => Err("Failed to calibrate TSC"),
+    }
+}
+
+fn ticks_between(from: u64, to: u64, end_value: u64) -> u64 {
+    if from <= to {
+        to - from
+    } else {
+        end_value - from + to + 1
+    }
+}
+
+fn ticks_to_duration(ticks: u64, frequency: u64) -> Duration {
+    let nanos = u128::from(ticks) * 1_000_000_000 / u128::from(frequency);
+    Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ticks_to_duration_is_exact() {
+        assert_eq!(ticks_to_duration(0, 1_000), Duration::ZERO);
+        assert_eq!(
+            ticks_to_duration(1_500, 1_000),
+            Duration::from_millis(1_500)
+        );
+        assert_eq!(
+            ticks_to_duration(3_000_000_000, 3_000_000_000),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            ticks_to_duration(u64::MAX, u64::MAX),
+            Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn counter_rollover() {
+        assert_eq!(ticks_between(10, 15, u64::MAX), 5);
+        assert_eq!(ticks_between(u64::MAX, 4, u64::MAX), 5);
+        assert_eq!(ticks_between(0xFFFF_FFF0, 0x10, 0xFFFF_FFFF), 0x20);
+    }
+}
@@ -2,6 +2,8 @@
 
 use crate::EfiGuid;
 
+use super::Protocol;
+
 pub const EFI_DEVICE_PATH_PROTOCOL_GUID: EfiGuid = EfiGuid(
     0x09576e91,
     0x6d3f,
@@ -9,8 +11,29 @@ pub const EFI_DEVICE_PATH_PROTOCOL_GUID: EfiGuid = EfiGuid(
     [0x8e, 0x39, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
 );
 
+#[repr(C)]
+#[derive(Debug)]
 pub struct EfiDevicePathProtocol {
     ty: u8,
     subty: u8,
     length: [u8; 2],
 }
+
+unsafe impl Protocol for EfiDevicePathProtocol {
+    const GUID: EfiGuid = EFI_DEVICE_PATH_PROTOCOL_GUID;
+}
+
+impl EfiDevicePathProtocol {
+    pub fn device_type(&self) -> u8 {
+        self.ty
+    }
+
+    pub fn sub_type(&self) -> u8 {
+        self.subty
+    }
+
+    /// Length of this node in bytes, including the header.
+    pub fn length(&self) -> u16 {
+        u16::from_le_bytes(self.length)
+    }
+}
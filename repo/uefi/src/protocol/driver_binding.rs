@@ -0,0 +1,334 @@
+//! REF: https://uefi.org/specs/UEFI/2.10/11_Protocols_UEFI_Driver_Model.html#efi-driver-binding-protocol
+
+use core::{
+    ptr::{null_mut, NonNull},
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use crate::{EfiBootServices, EfiGuid, EfiHandle, EfiResult, EfiStatus, EfiVoid};
+
+use super::{device_path::EfiDevicePathProtocol, Protocol};
+
+pub const EFI_DRIVER_BINDING_PROTOCOL_GUID: EfiGuid = EfiGuid(
+    0x18a031ab,
+    0xb443,
+    0x4d1a,
+    [0xa5, 0xc0, 0x0c, 0x09, 0x26, 0x1e, 0x9f, 0x71],
+);
+
+/// REF: https://uefi.org/specs/UEFI/2.10/11_Protocols_UEFI_Driver_Model.html#efi-driver-binding-protocol-supported
+pub type EfiDriverBindingSupported = extern "efiapi" fn(
+    this: NonNull<EfiDriverBindingProtocol>,
+    controller_handle: EfiHandle,
+    remaining_device_path: Option<NonNull<EfiDevicePathProtocol>>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/11_Protocols_UEFI_Driver_Model.html#efi-driver-binding-protocol-start
+pub type EfiDriverBindingStart = extern "efiapi" fn(
+    this: NonNull<EfiDriverBindingProtocol>,
+    controller_handle: EfiHandle,
+    remaining_device_path: Option<NonNull<EfiDevicePathProtocol>>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/11_Protocols_UEFI_Driver_Model.html#efi-driver-binding-protocol-stop
+pub type EfiDriverBindingStop = extern "efiapi" fn(
+    this: NonNull<EfiDriverBindingProtocol>,
+    controller_handle: EfiHandle,
+    number_of_children: usize,
+    child_handle_buffer: Option<NonNull<EfiHandle>>,
+) -> EfiStatus;
+
+/// EFI_HANDLE member set by the driver when it installs the protocol, null before.
+/// Atomic, since APs started through MP services can reach a driver binding in a static.
+#[repr(transparent)]
+#[derive(Debug)]
+struct HandleField(AtomicPtr<EfiVoid>);
+
+impl HandleField {
+    const fn new() -> Self {
+        Self(AtomicPtr::new(null_mut()))
+    }
+
+    fn get(&self) -> Option<EfiHandle> {
+        NonNull::new(self.0.load(Ordering::Acquire)).map(EfiHandle::from_ptr)
+    }
+
+    fn set(&self, handle: Option<EfiHandle>) {
+        let ptr = handle.map_or(null_mut(), |handle| handle.as_ptr());
+        self.0.store(ptr, Ordering::Release);
+    }
+
+    /// Sets `handle` unless a handle is already set. Returns whether it did.
+    fn claim(&self, handle: EfiHandle) -> bool {
+        self.0
+            .compare_exchange(
+                null_mut(),
+                handle.as_ptr(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_ok()
+    }
+}
+
+/// REF: https://uefi.org/specs/UEFI/2.10/11_Protocols_UEFI_Driver_Model.html#efi-driver-binding-protocol
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiDriverBindingProtocol {
+    supported: EfiDriverBindingSupported,
+    start: EfiDriverBindingStart,
+    stop: EfiDriverBindingStop,
+    version: u32,
+    image_handle: HandleField,
+    driver_binding_handle: HandleField,
+}
+
+unsafe impl Protocol for EfiDriverBindingProtocol {
+    const GUID: EfiGuid = EFI_DRIVER_BINDING_PROTOCOL_GUID;
+}
+
+impl EfiDriverBindingProtocol {
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Image that produced this driver binding. Available once installed.
+    pub fn image_handle(&self) -> Option<EfiHandle> {
+        self.image_handle.get()
+    }
+
+    /// Handle this driver binding is installed on. Drivers use it as the agent handle of OpenProtocol.
+    pub fn driver_binding_handle(&self) -> Option<EfiHandle> {
+        self.driver_binding_handle.get()
+    }
+}
+
+/// Driver implemented in Rust, called back through the driver binding protocol.
+pub trait DriverBinding {
+    /// Tests whether the driver supports `controller_handle`. Must not modify the controller.
+    fn supported(
+        &self,
+        binding: &EfiDriverBindingProtocol,
+        controller_handle: EfiHandle,
+        remaining_device_path: Option<&EfiDevicePathProtocol>,
+    ) -> EfiStatus;
+
+    /// Starts managing `controller_handle`.
+    fn start(
+        &self,
+        binding: &EfiDriverBindingProtocol,
+        controller_handle: EfiHandle,
+        remaining_device_path: Option<&EfiDevicePathProtocol>,
+    ) -> EfiStatus;
+
+    /// Stops managing `controller_handle` and destroys `child_handles` created by [`DriverBinding::start`].
+    fn stop(
+        &self,
+        binding: &EfiDriverBindingProtocol,
+        controller_handle: EfiHandle,
+        child_handles: &[EfiHandle],
+    ) -> EfiStatus;
+}
+
+/// Driver binding protocol followed by the driver it dispatches to.
+/// The firmware only sees the leading [`EfiDriverBindingProtocol`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiDriverBinding<D: DriverBinding> {
+    protocol: EfiDriverBindingProtocol,
+    driver: D,
+}
+
+unsafe impl<D: DriverBinding> Protocol for EfiDriverBinding<D> {
+    const GUID: EfiGuid = EFI_DRIVER_BINDING_PROTOCOL_GUID;
+}
+
+impl<D: DriverBinding> EfiDriverBinding<D> {
+    /// `version` decides the order of drivers tried by ConnectController; higher is tried first.
+    pub const fn new(driver: D, version: u32) -> Self {
+        Self {
+            protocol: EfiDriverBindingProtocol {
+                supported: supported::<D>,
+                start: start::<D>,
+                stop: stop::<D>,
+                version,
+                image_handle: HandleField::new(),
+                driver_binding_handle: HandleField::new(),
+            },
+            driver,
+        }
+    }
+
+    pub fn protocol(&self) -> &EfiDriverBindingProtocol {
+        &self.protocol
+    }
+
+    pub fn driver(&self) -> &D {
+        &self.driver
+    }
+
+    unsafe fn from_protocol<'a>(this: NonNull<EfiDriverBindingProtocol>) -> &'a Self {
+        this.cast::<Self>().as_ref()
+    }
+}
+
+extern "efiapi" fn supported<D: DriverBinding>(
+    this: NonNull<EfiDriverBindingProtocol>,
+    controller_handle: EfiHandle,
+    remaining_device_path: Option<NonNull<EfiDevicePathProtocol>>,
+) -> EfiStatus {
+    let binding = unsafe { EfiDriverBinding::<D>::from_protocol(this) };
+    let remaining_device_path = remaining_device_path.map(|p| unsafe { p.as_ref() });
+    binding
+        .driver
+        .supported(&binding.protocol, controller_handle, remaining_device_path)
+}
+
+extern "efiapi" fn start<D: DriverBinding>(
+    this: NonNull<EfiDriverBindingProtocol>,
+    controller_handle: EfiHandle,
+    remaining_device_path: Option<NonNull<EfiDevicePathProtocol>>,
+) -> EfiStatus {
+    let binding = unsafe { EfiDriverBinding::<D>::from_protocol(this) };
+    let remaining_device_path = remaining_device_path.map(|p| unsafe { p.as_ref() });
+    binding
+        .driver
+        .start(&binding.protocol, controller_handle, remaining_device_path)
+}
+
+extern "efiapi" fn stop<D: DriverBinding>(
+    this: NonNull<EfiDriverBindingProtocol>,
+    controller_handle: EfiHandle,
+    number_of_children: usize,
+    child_handle_buffer: Option<NonNull<EfiHandle>>,
+) -> EfiStatus {
+    let binding = unsafe { EfiDriverBinding::<D>::from_protocol(this) };
+    let child_handles = match child_handle_buffer {
+        Some(buffer) => unsafe { core::slice::from_raw_parts(buffer.as_ptr(), number_of_children) },
+        None => &[],
+    };
+    binding
+        .driver
+        .stop(&binding.protocol, controller_handle, child_handles)
+}
+
+impl EfiBootServices {
+    /// Installs the driver binding on the driver's image handle, so that ConnectController can use the driver.
+    pub fn install_driver_binding<D: DriverBinding>(
+        &self,
+        image_handle: EfiHandle,
+        binding: &'static EfiDriverBinding<D>,
+    ) -> EfiResult<()> {
+        // The handles are set first, since the firmware may call the driver during the install.
+        let protocol = &binding.protocol;
+        if !protocol.driver_binding_handle.claim(image_handle) {
+            return Err("Driver binding is already installed");
+        }
+        protocol.image_handle.set(Some(image_handle));
+
+        if let Err(error) = self.install_protocol_interface(Some(image_handle), binding) {
+            protocol.image_handle.set(None);
+            protocol.driver_binding_handle.set(None);
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    pub fn uninstall_driver_binding<D: DriverBinding>(
+        &self,
+        binding: &'static EfiDriverBinding<D>,
+    ) -> EfiResult<()> {
+        let handle = binding
+            .protocol
+            .driver_binding_handle
+            .get()
+            .ok_or("Driver binding is not installed")?;
+        self.uninstall_protocol_interface(handle, binding)?;
+        binding.protocol.image_handle.set(None);
+        binding.protocol.driver_binding_handle.set(None);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::MockFirmware;
+
+    struct NullDriver;
+
+    impl DriverBinding for NullDriver {
+        fn supported(
+            &self,
+            _binding: &EfiDriverBindingProtocol,
+            _controller_handle: EfiHandle,
+            _remaining_device_path: Option<&EfiDevicePathProtocol>,
+        ) -> EfiStatus {
+            EfiStatus::UNSUPPORTED
+        }
+
+        fn start(
+            &self,
+            _binding: &EfiDriverBindingProtocol,
+            _controller_handle: EfiHandle,
+            _remaining_device_path: Option<&EfiDevicePathProtocol>,
+        ) -> EfiStatus {
+            EfiStatus::UNSUPPORTED
+        }
+
+        fn stop(
+            &self,
+            _binding: &EfiDriverBindingProtocol,
+            _controller_handle: EfiHandle,
+            _child_handles: &[EfiHandle],
+        ) -> EfiStatus {
+            EfiStatus::UNSUPPORTED
+        }
+    }
+
+    fn handles(binding: &EfiDriverBinding<NullDriver>) -> [Option<*mut EfiVoid>; 2] {
+        let protocol = binding.protocol();
+        [protocol.image_handle(), protocol.driver_binding_handle()]
+            .map(|handle| handle.map(|handle| handle.as_ptr()))
+    }
+
+    #[test]
+    fn install_and_uninstall() {
+        static BINDING: EfiDriverBinding<NullDriver> = EfiDriverBinding::new(NullDriver, 0x10);
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+        let image = firmware.image_handle();
+
+        boot_services
+            .install_driver_binding(image, &BINDING)
+            .unwrap();
+        assert_eq!(handles(&BINDING), [Some(image.as_ptr()); 2]);
+        let installed: &EfiDriverBinding<NullDriver> =
+            boot_services.handle_protocol(image).unwrap();
+        assert_eq!(installed.protocol().version(), 0x10);
+
+        // A second install leaves the first one alone.
+        assert!(boot_services
+            .install_driver_binding(image, &BINDING)
+            .is_err());
+        assert_eq!(handles(&BINDING), [Some(image.as_ptr()); 2]);
+
+        boot_services.uninstall_driver_binding(&BINDING).unwrap();
+        assert_eq!(handles(&BINDING), [None; 2]);
+        assert!(boot_services.uninstall_driver_binding(&BINDING).is_err());
+    }
+
+    #[test]
+    fn failed_install() {
+        static BINDING: EfiDriverBinding<NullDriver> = EfiDriverBinding::new(NullDriver, 0x10);
+        let firmware = MockFirmware::new();
+        let boot_services = firmware.boot_services();
+
+        // Not a handle of the firmware.
+        let handle = EfiHandle::from_ptr(NonNull::dangling());
+        assert!(boot_services
+            .install_driver_binding(handle, &BINDING)
+            .is_err());
+        assert_eq!(handles(&BINDING), [None; 2]);
+    }
+}
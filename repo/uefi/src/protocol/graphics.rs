@@ -0,0 +1,169 @@
+//! REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-graphics-output-protocol
+
+use core::{mem::size_of, ptr::NonNull};
+
+use crate::EfiGuid;
+
+use super::Protocol;
+
+pub const EFI_GRAPHICS_OUTPUT_PROTOCOL_GUID: EfiGuid = EfiGuid(
+    0x9042a9de,
+    0x23dc,
+    0x4a38,
+    [0x96, 0xfb, 0x7a, 0xde, 0xd0, 0x80, 0x51, 0x6a],
+);
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiGraphicsOutputProtocol {
+    _reserved: [usize; 3],
+    mode: NonNull<EfiGraphicsOutputProtocolMode>,
+}
+
+unsafe impl Protocol for EfiGraphicsOutputProtocol {
+    const GUID: EfiGuid = EFI_GRAPHICS_OUTPUT_PROTOCOL_GUID;
+}
+
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-graphics-output-protocol
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiGraphicsOutputProtocolMode {
+    max_mode: u32,
+    mode: u32,
+    info: NonNull<EfiGraphicsOutputModeInformation>,
+    size_of_info: usize,
+    frame_buffer_base: usize,
+    frame_buffer_size: usize,
+}
+
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-graphics-output-protocol-querymode
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiGraphicsOutputModeInformation {
+    version: u32,
+    horizontal_resolution: u32,
+    vertical_resolution: u32,
+    // Not EfiGraphicsPixelFormat since the firmware may report values beyond PixelFormatMax.
+    pixel_format: u32,
+    pixel_information: EfiPixelBitmask,
+    pixels_per_scan_line: u32,
+}
+
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-graphics-output-protocol-querymode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum EfiGraphicsPixelFormat {
+    /// Byte 0 is red, byte 1 is green, byte 2 is blue and byte 3 is reserved.
+    PixelRedGreenBlueReserved8BitPerColor,
+    /// Byte 0 is blue, byte 1 is green, byte 2 is red and byte 3 is reserved.
+    PixelBlueGreenRedReserved8BitPerColor,
+    /// The pixel is defined by the pixel information bit masks.
+    PixelBitMask,
+    /// There is no frame buffer; only Blt() can draw.
+    PixelBltOnly,
+}
+
+impl TryFrom<u32> for EfiGraphicsPixelFormat {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        const FORMATS: [EfiGraphicsPixelFormat; 4] = [
+            EfiGraphicsPixelFormat::PixelRedGreenBlueReserved8BitPerColor,
+            EfiGraphicsPixelFormat::PixelBlueGreenRedReserved8BitPerColor,
+            EfiGraphicsPixelFormat::PixelBitMask,
+            EfiGraphicsPixelFormat::PixelBltOnly,
+        ];
+
+        FORMATS.get(value as usize).copied().ok_or(value)
+    }
+}
+
+/// Bits of a 32-bit pixel used by each color, for [`EfiGraphicsPixelFormat::PixelBitMask`].
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-graphics-output-protocol-querymode
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EfiPixelBitmask {
+    red_mask: u32,
+    green_mask: u32,
+    blue_mask: u32,
+    reserved_mask: u32,
+}
+
+impl EfiPixelBitmask {
+    pub fn red_mask(&self) -> u32 {
+        self.red_mask
+    }
+
+    pub fn green_mask(&self) -> u32 {
+        self.green_mask
+    }
+
+    pub fn blue_mask(&self) -> u32 {
+        self.blue_mask
+    }
+
+    pub fn reserved_mask(&self) -> u32 {
+        self.reserved_mask
+    }
+}
+
+const _: () = assert!(size_of::<EfiGraphicsOutputModeInformation>() == 36);
+
+impl EfiGraphicsOutputProtocol {
+    pub fn mode(&self) -> &EfiGraphicsOutputProtocolMode {
+        unsafe { self.mode.as_ref() }
+    }
+}
+
+impl EfiGraphicsOutputProtocolMode {
+    /// Number of modes supported by QueryMode and SetMode.
+    pub fn max_mode(&self) -> u32 {
+        self.max_mode
+    }
+
+    /// Current mode number.
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+
+    pub fn info(&self) -> &EfiGraphicsOutputModeInformation {
+        unsafe { self.info.as_ref() }
+    }
+
+    /// Physical address of the linear frame buffer.
+    pub fn frame_buffer_base(&self) -> usize {
+        self.frame_buffer_base
+    }
+
+    pub fn frame_buffer_size(&self) -> usize {
+        self.frame_buffer_size
+    }
+}
+
+impl EfiGraphicsOutputModeInformation {
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn horizontal_resolution(&self) -> u32 {
+        self.horizontal_resolution
+    }
+
+    pub fn vertical_resolution(&self) -> u32 {
+        self.vertical_resolution
+    }
+
+    pub fn pixel_format(&self) -> Result<EfiGraphicsPixelFormat, u32> {
+        EfiGraphicsPixelFormat::try_from(self.pixel_format)
+    }
+
+    /// Meaningful only for [`EfiGraphicsPixelFormat::PixelBitMask`].
+    pub fn pixel_information(&self) -> EfiPixelBitmask {
+        self.pixel_information
+    }
+
+    /// Pixels per row in the frame buffer, which may exceed the horizontal resolution.
+    pub fn pixels_per_scan_line(&self) -> u32 {
+        self.pixels_per_scan_line
+    }
+}
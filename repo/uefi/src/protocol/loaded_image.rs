@@ -0,0 +1,103 @@
+//! REF: https://uefi.org/specs/UEFI/2.10/09_Protocols_EFI_Loaded_Image.html#efi-loaded-image-protocol
+
+use core::ptr::NonNull;
+
+use crate::{
+    efi_boot_services::EfiMemoryType, EfiGuid, EfiHandle, EfiStatus, EfiSystemTable, EfiVoid,
+};
+
+use super::{device_path::EfiDevicePathProtocol, Protocol};
+
+pub const EFI_LOADED_IMAGE_PROTOCOL_GUID: EfiGuid = EfiGuid(
+    0x5b1b31a1,
+    0x9562,
+    0x11d2,
+    [0x8e, 0x3f, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
+);
+
+/// REF: https://uefi.org/specs/UEFI/2.10/09_Protocols_EFI_Loaded_Image.html#efi-loaded-image-protocol-unload
+pub type EfiImageUnload = extern "efiapi" fn(image_handle: EfiHandle) -> EfiStatus;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiLoadedImageProtocol {
+    pub(crate) revision: u32,
+    pub(crate) parent_handle: Option<EfiHandle>,
+    pub(crate) system_table: NonNull<EfiSystemTable>,
+
+    // Source location of the image
+    pub(crate) device_handle: Option<EfiHandle>,
+    pub(crate) file_path: Option<NonNull<EfiDevicePathProtocol>>,
+    pub(crate) _reserved: *mut EfiVoid,
+
+    // Image's load options
+    pub(crate) load_options_size: u32,
+    pub(crate) load_options: *mut EfiVoid,
+
+    // Location where image was loaded
+    pub(crate) image_base: *mut EfiVoid,
+    pub(crate) image_size: u64,
+    // Not EfiMemoryType since the firmware may report OEM defined types.
+    pub(crate) image_code_type: u32,
+    pub(crate) image_data_type: u32,
+    pub(crate) unload: Option<EfiImageUnload>,
+}
+
+unsafe impl Protocol for EfiLoadedImageProtocol {
+    const GUID: EfiGuid = EFI_LOADED_IMAGE_PROTOCOL_GUID;
+}
+
+impl EfiLoadedImageProtocol {
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+
+    /// Image that loaded this image. `None` for images loaded by the firmware itself.
+    pub fn parent_handle(&self) -> Option<EfiHandle> {
+        self.parent_handle
+    }
+
+    pub fn system_table(&self) -> &EfiSystemTable {
+        unsafe { self.system_table.as_ref() }
+    }
+
+    /// Device the image was loaded from.
+    pub fn device_handle(&self) -> Option<EfiHandle> {
+        self.device_handle
+    }
+
+    /// File path of the image, relative to [`Self::device_handle`].
+    pub fn file_path(&self) -> Option<&EfiDevicePathProtocol> {
+        self.file_path.map(|path| unsafe { path.as_ref() })
+    }
+
+    /// Options given by the loader, e.g. the command line of the shell or the optional data of Boot####.
+    pub fn load_options(&self) -> &[u8] {
+        if self.load_options.is_null() {
+            return &[];
+        }
+
+        unsafe {
+            core::slice::from_raw_parts(
+                self.load_options as *const u8,
+                self.load_options_size as usize,
+            )
+        }
+    }
+
+    pub fn image_base(&self) -> *mut EfiVoid {
+        self.image_base
+    }
+
+    pub fn image_size(&self) -> u64 {
+        self.image_size
+    }
+
+    pub fn image_code_type(&self) -> Result<EfiMemoryType, u32> {
+        EfiMemoryType::try_from(self.image_code_type)
+    }
+
+    pub fn image_data_type(&self) -> Result<EfiMemoryType, u32> {
+        EfiMemoryType::try_from(self.image_data_type)
+    }
+}
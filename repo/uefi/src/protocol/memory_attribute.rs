@@ -0,0 +1,145 @@
+//! REF: https://uefi.org/specs/UEFI/2.10/37_Secure_Technologies.html#efi-memory-attribute-protocol
+
+use core::{mem::MaybeUninit, ptr::NonNull};
+
+use crate::{
+    efi_boot_services::{EfiMemoryAttribute, EfiMemoryDescriptor, EfiPhysicalAddress},
+    EfiGuid, EfiResult, EfiStatus,
+};
+
+use super::Protocol;
+
+pub const EFI_MEMORY_ATTRIBUTE_PROTOCOL_GUID: EfiGuid = EfiGuid(
+    0xf4560cf6,
+    0x40ec,
+    0x4b4a,
+    [0xa1, 0x92, 0xbf, 0x1d, 0x57, 0xd0, 0xb1, 0x89],
+);
+
+/// REF: https://uefi.org/specs/UEFI/2.10/37_Secure_Technologies.html#efi-memory-attribute-protocol-getmemoryattributes
+pub type EfiGetMemoryAttributes = extern "efiapi" fn(
+    this: NonNull<EfiMemoryAttributeProtocol>,
+    base_address: EfiPhysicalAddress,
+    length: u64,
+    attributes: NonNull<EfiMemoryAttribute>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/37_Secure_Technologies.html#efi-memory-attribute-protocol-setmemoryattributes
+pub type EfiSetMemoryAttributes = extern "efiapi" fn(
+    this: NonNull<EfiMemoryAttributeProtocol>,
+    base_address: EfiPhysicalAddress,
+    length: u64,
+    attributes: EfiMemoryAttribute,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/37_Secure_Technologies.html#efi-memory-attribute-protocol-clearmemoryattributes
+pub type EfiClearMemoryAttributes = extern "efiapi" fn(
+    this: NonNull<EfiMemoryAttributeProtocol>,
+    base_address: EfiPhysicalAddress,
+    length: u64,
+    attributes: EfiMemoryAttribute,
+) -> EfiStatus;
+
+/// Page permissions of the memory used during boot services.
+/// Only [`EfiMemoryAttribute::MEMORY_RP`], [`EfiMemoryAttribute::MEMORY_XP`] and [`EfiMemoryAttribute::MEMORY_RO`] are accepted.
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiMemoryAttributeProtocol {
+    get_memory_attributes: EfiGetMemoryAttributes,
+    set_memory_attributes: EfiSetMemoryAttributes,
+    clear_memory_attributes: EfiClearMemoryAttributes,
+}
+
+unsafe impl Protocol for EfiMemoryAttributeProtocol {
+    const GUID: EfiGuid = EFI_MEMORY_ATTRIBUTE_PROTOCOL_GUID;
+}
+
+impl EfiMemoryAttributeProtocol {
+    /// Returns the attributes of the page aligned range. Fails if the pages have different attributes.
+    pub fn get_memory_attributes(
+        &self,
+        base_address: EfiPhysicalAddress,
+        length: u64,
+    ) -> EfiResult<EfiMemoryAttribute> {
+        check_range(base_address, length)?;
+
+        let mut attributes = MaybeUninit::<EfiMemoryAttribute>::uninit();
+        let status = (self.get_memory_attributes)(
+            NonNull::from(self),
+            base_address,
+            length,
+            NonNull::from(&mut attributes).cast(),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to get memory attributes");
+        }
+
+        Ok(unsafe { attributes.assume_init() })
+    }
+
+    /// Adds `attributes` to the page aligned range, keeping the other attributes.
+    ///
+    /// # Safety
+    /// Making code or data in use non-executable, read-only or read-protected faults on the next access.
+    pub unsafe fn set_memory_attributes(
+        &self,
+        base_address: EfiPhysicalAddress,
+        length: u64,
+        attributes: EfiMemoryAttribute,
+    ) -> EfiResult<()> {
+        check_range(base_address, length)?;
+        check_attributes(attributes)?;
+
+        let status =
+            (self.set_memory_attributes)(NonNull::from(self), base_address, length, attributes);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to set memory attributes");
+        }
+
+        Ok(())
+    }
+
+    /// Removes `attributes` from the page aligned range, keeping the other attributes.
+    pub fn clear_memory_attributes(
+        &self,
+        base_address: EfiPhysicalAddress,
+        length: u64,
+        attributes: EfiMemoryAttribute,
+    ) -> EfiResult<()> {
+        check_range(base_address, length)?;
+        check_attributes(attributes)?;
+
+        let status =
+            (self.clear_memory_attributes)(NonNull::from(self), base_address, length, attributes);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to clear memory attributes");
+        }
+
+        Ok(())
+    }
+}
+
+fn check_range(base_address: EfiPhysicalAddress, length: u64) -> EfiResult<()> {
+    let base_address = u64::from(base_address);
+    if length == 0
+        || base_address % EfiMemoryDescriptor::PAGE_SIZE != 0
+        || length % EfiMemoryDescriptor::PAGE_SIZE != 0
+    {
+        return Err("Memory range must be non-empty and page aligned");
+    }
+
+    Ok(())
+}
+
+fn check_attributes(attributes: EfiMemoryAttribute) -> EfiResult<()> {
+    if attributes == EfiMemoryAttribute::empty()
+        || !EfiMemoryAttribute::MEMORY_ACCESS_MASK.contains(attributes)
+    {
+        return Err("Only RP, XP and RO memory attributes are accepted");
+    }
+
+    Ok(())
+}
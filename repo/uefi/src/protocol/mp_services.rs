@@ -0,0 +1,418 @@
+//! REF: https://uefi.org/specs/PI/1.8/V2_DXE_Boot_Services_Protocols.html#efi-mp-services-protocol
+
+use core::{mem::MaybeUninit, ptr::NonNull, time::Duration};
+
+use crate::{EfiEvent, EfiGuid, EfiResult, EfiStatus, EfiVoid};
+
+use super::Protocol;
+
+pub const EFI_MP_SERVICES_PROTOCOL_GUID: EfiGuid = EfiGuid(
+    0x3fdda605,
+    0xa76e,
+    0x4f46,
+    [0xad, 0x29, 0x12, 0xf4, 0x53, 0x1b, 0x3d, 0x08],
+);
+
+/// REF: https://uefi.org/specs/PI/1.8/V2_DXE_Boot_Services_Protocols.html#efi-mp-services-protocol-startupallaps
+pub type EfiApProcedure = extern "efiapi" fn(procedure_argument: NonNull<EfiVoid>);
+
+/// REF: https://uefi.org/specs/PI/1.8/V2_DXE_Boot_Services_Protocols.html#efi-mp-services-protocol-getnumberofprocessors
+pub type EfiMpServicesGetNumberOfProcessors = extern "efiapi" fn(
+    this: NonNull<EfiMpServicesProtocol>,
+    number_of_processors: NonNull<usize>,
+    number_of_enabled_processors: NonNull<usize>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/PI/1.8/V2_DXE_Boot_Services_Protocols.html#efi-mp-services-protocol-getprocessorinfo
+pub type EfiMpServicesGetProcessorInfo = extern "efiapi" fn(
+    this: NonNull<EfiMpServicesProtocol>,
+    processor_number: usize,
+    processor_info_buffer: NonNull<EfiProcessorInformation>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/PI/1.8/V2_DXE_Boot_Services_Protocols.html#efi-mp-services-protocol-startupallaps
+pub type EfiMpServicesStartupAllAps = extern "efiapi" fn(
+    this: NonNull<EfiMpServicesProtocol>,
+    procedure: EfiApProcedure,
+    single_thread: bool,
+    wait_event: EfiEvent,
+    timeout_in_micro_seconds: usize,
+    procedure_argument: NonNull<EfiVoid>,
+    failed_cpu_list: Option<NonNull<*mut usize>>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/PI/1.8/V2_DXE_Boot_Services_Protocols.html#efi-mp-services-protocol-startupthisap
+pub type EfiMpServicesStartupThisAp = extern "efiapi" fn(
+    this: NonNull<EfiMpServicesProtocol>,
+    procedure: EfiApProcedure,
+    processor_number: usize,
+    wait_event: EfiEvent,
+    timeout_in_micro_seconds: usize,
+    procedure_argument: NonNull<EfiVoid>,
+    finished: Option<NonNull<bool>>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/PI/1.8/V2_DXE_Boot_Services_Protocols.html#efi-mp-services-protocol-switchbsp
+pub type EfiMpServicesSwitchBsp = extern "efiapi" fn(
+    this: NonNull<EfiMpServicesProtocol>,
+    processor_number: usize,
+    enable_old_bsp: bool,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/PI/1.8/V2_DXE_Boot_Services_Protocols.html#efi-mp-services-protocol-enabledisableap
+pub type EfiMpServicesEnableDisableAp = extern "efiapi" fn(
+    this: NonNull<EfiMpServicesProtocol>,
+    processor_number: usize,
+    enable_ap: bool,
+    health_flag: Option<NonNull<u32>>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/PI/1.8/V2_DXE_Boot_Services_Protocols.html#efi-mp-services-protocol-whoami
+pub type EfiMpServicesWhoAmI = extern "efiapi" fn(
+    this: NonNull<EfiMpServicesProtocol>,
+    processor_number: NonNull<usize>,
+) -> EfiStatus;
+
+/// Runs procedures on the application processors (APs) while boot services are available.
+/// Procedures run on APs must not call boot services other than this protocol's [`EfiMpServicesProtocol::who_am_i`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiMpServicesProtocol {
+    pub(crate) get_number_of_processors: EfiMpServicesGetNumberOfProcessors,
+    pub(crate) get_processor_info: EfiMpServicesGetProcessorInfo,
+    pub(crate) startup_all_aps: EfiMpServicesStartupAllAps,
+    pub(crate) startup_this_ap: EfiMpServicesStartupThisAp,
+    pub(crate) switch_bsp: EfiMpServicesSwitchBsp,
+    pub(crate) enable_disable_ap: EfiMpServicesEnableDisableAp,
+    pub(crate) who_am_i: EfiMpServicesWhoAmI,
+}
+
+unsafe impl Protocol for EfiMpServicesProtocol {
+    const GUID: EfiGuid = EFI_MP_SERVICES_PROTOCOL_GUID;
+}
+
+/// REF: https://uefi.org/specs/PI/1.8/V2_DXE_Boot_Services_Protocols.html#efi-mp-services-protocol-getprocessorinfo
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct EfiProcessorInformation {
+    pub(crate) processor_id: u64,
+    pub(crate) status_flag: u32,
+    pub(crate) location: EfiCpuPhysicalLocation,
+    // Filled only when requested with CPU_V2_EXTENDED_TOPOLOGY, which is not used.
+    pub(crate) _extended_information: [u32; 6],
+}
+
+impl EfiProcessorInformation {
+    pub(crate) const PROCESSOR_AS_BSP_BIT: u32 = 0x1;
+    pub(crate) const PROCESSOR_ENABLED_BIT: u32 = 0x2;
+    pub(crate) const PROCESSOR_HEALTH_STATUS_BIT: u32 = 0x4;
+
+    /// APIC ID of the processor.
+    pub fn processor_id(&self) -> u64 {
+        self.processor_id
+    }
+
+    pub fn is_bsp(&self) -> bool {
+        self.status_flag & Self::PROCESSOR_AS_BSP_BIT != 0
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.status_flag & Self::PROCESSOR_ENABLED_BIT != 0
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.status_flag & Self::PROCESSOR_HEALTH_STATUS_BIT != 0
+    }
+
+    pub fn location(&self) -> &EfiCpuPhysicalLocation {
+        &self.location
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EfiCpuPhysicalLocation {
+    pub(crate) package: u32,
+    pub(crate) core: u32,
+    pub(crate) thread: u32,
+}
+
+impl EfiCpuPhysicalLocation {
+    pub fn package(&self) -> u32 {
+        self.package
+    }
+
+    pub fn core(&self) -> u32 {
+        self.core
+    }
+
+    pub fn thread(&self) -> u32 {
+        self.thread
+    }
+}
+
+impl EfiMpServicesProtocol {
+    /// Returns the number of logical processors and how many of them are enabled, BSP included.
+    pub fn get_number_of_processors(&self) -> EfiResult<(usize, usize)> {
+        let mut number_of_processors = 0;
+        let mut number_of_enabled_processors = 0;
+        let status = (self.get_number_of_processors)(
+            NonNull::from(self),
+            NonNull::from(&mut number_of_processors),
+            NonNull::from(&mut number_of_enabled_processors),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to get number of processors");
+        }
+
+        Ok((number_of_processors, number_of_enabled_processors))
+    }
+
+    /// `processor_number` ranges from 0 to the number of processors - 1.
+    pub fn get_processor_info(
+        &self,
+        processor_number: usize,
+    ) -> EfiResult<EfiProcessorInformation> {
+        let mut info = MaybeUninit::<EfiProcessorInformation>::zeroed();
+        let status = (self.get_processor_info)(
+            NonNull::from(self),
+            processor_number,
+            NonNull::from(&mut info).cast(),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to get processor information");
+        }
+
+        Ok(unsafe { info.assume_init() })
+    }
+
+    /// Returns the number of the calling processor. This may be called from APs.
+    pub fn who_am_i(&self) -> EfiResult<usize> {
+        let mut processor_number = 0;
+        let status = (self.who_am_i)(NonNull::from(self), NonNull::from(&mut processor_number));
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to get processor number");
+        }
+
+        Ok(processor_number)
+    }
+
+    /// Runs `procedure` on every enabled AP with the processor number and waits for all of them.
+    /// With `single_thread`, the APs run one after another in ascending processor number.
+    /// `None` as `timeout` waits forever. The BSP does not run `procedure`.
+    /// Succeeds without running anything when there are no enabled APs.
+    ///
+    /// Boot services are not MP-safe, so `procedure` must not call any of them but [`Self::who_am_i`].
+    /// That includes calls through a captured `&EfiBootServices` or protocol, such as writing to the console.
+    pub fn startup_all_aps<F: Fn(usize) + Sync>(
+        &self,
+        single_thread: bool,
+        timeout: Option<Duration>,
+        procedure: &F,
+    ) -> EfiResult<()> {
+        let context = AllApsContext {
+            protocol: self,
+            procedure,
+        };
+        // Blocking call since no WaitEvent is given, so `context` outlives the APs.
+        let status = (self.startup_all_aps)(
+            NonNull::from(self),
+            all_aps_procedure::<F>,
+            single_thread,
+            EfiEvent::NULL,
+            timeout_in_micro_seconds(timeout),
+            NonNull::from(&context).cast(),
+            None,
+        );
+
+        match status {
+            EfiStatus::SUCCESS | EfiStatus::NOT_STARTED => Ok(()),
+            EfiStatus::TIMEOUT => Err("Timed out waiting for APs"),
+            _ => Err("Failed to start up APs"),
+        }
+    }
+
+    /// Runs `procedure` on the AP `processor_number` and returns its result.
+    /// `None` as `timeout` waits forever.
+    ///
+    /// Boot services are not MP-safe, so `procedure` must not call any of them but [`Self::who_am_i`].
+    /// That includes calls through a captured `&EfiBootServices` or protocol, such as writing to the console.
+    pub fn startup_this_ap<F: FnOnce() -> R + Send, R: Send>(
+        &self,
+        processor_number: usize,
+        timeout: Option<Duration>,
+        procedure: F,
+    ) -> EfiResult<R> {
+        let mut context = ThisApContext {
+            procedure: Some(procedure),
+            result: None,
+        };
+        // Blocking call since no WaitEvent is given, so `context` outlives the AP.
+        let status = (self.startup_this_ap)(
+            NonNull::from(self),
+            this_ap_procedure::<F, R>,
+            processor_number,
+            EfiEvent::NULL,
+            timeout_in_micro_seconds(timeout),
+            NonNull::from(&mut context).cast(),
+            None,
+        );
+
+        match status {
+            EfiStatus::SUCCESS => context.result.ok_or("AP did not run the procedure"),
+            EfiStatus::TIMEOUT => Err("Timed out waiting for AP"),
+            _ => Err("Failed to start up AP"),
+        }
+    }
+}
+
+/// Zero means infinity for the firmware.
+fn timeout_in_micro_seconds(timeout: Option<Duration>) -> usize {
+    match timeout {
+        Some(timeout) => usize::try_from(timeout.as_micros())
+            .unwrap_or(usize::MAX)
+            .max(1),
+        None => 0,
+    }
+}
+
+struct AllApsContext<'a, F> {
+    protocol: &'a EfiMpServicesProtocol,
+    procedure: &'a F,
+}
+
+extern "efiapi" fn all_aps_procedure<F: Fn(usize) + Sync>(argument: NonNull<EfiVoid>) {
+    let context = unsafe { argument.cast::<AllApsContext<F>>().as_ref() };
+    if let Ok(processor_number) = context.protocol.who_am_i() {
+        (context.procedure)(processor_number);
+    }
+}
+
+struct ThisApContext<F, R> {
+    procedure: Option<F>,
+    result: Option<R>,
+}
+
+extern "efiapi" fn this_ap_procedure<F: FnOnce() -> R + Send, R: Send>(argument: NonNull<EfiVoid>) {
+    // The BSP waits without touching the context until the AP finishes.
+    let context = unsafe { argument.cast::<ThisApContext<F, R>>().as_mut() };
+    if let Some(procedure) = context.procedure.take() {
+        context.result = Some(procedure());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::MockFirmware;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn processors() {
+        let firmware = MockFirmware::new();
+        firmware.set_processors(4);
+        let mp_services = firmware
+            .boot_services()
+            .locate_protocol::<EfiMpServicesProtocol>()
+            .unwrap();
+
+        assert_eq!(mp_services.get_number_of_processors(), Ok((4, 4)));
+        assert_eq!(mp_services.who_am_i(), Ok(0));
+        let bsp = mp_services.get_processor_info(0).unwrap();
+        assert!(bsp.is_bsp() && bsp.is_enabled() && bsp.is_healthy());
+        let ap = mp_services.get_processor_info(3).unwrap();
+        assert!(!ap.is_bsp());
+        assert_eq!(ap.processor_id(), 3);
+        assert!(mp_services.get_processor_info(4).is_err());
+    }
+
+    #[test]
+    fn startup_all_aps() {
+        let firmware = MockFirmware::new();
+        firmware.set_processors(4);
+        let mp_services = firmware
+            .boot_services()
+            .locate_protocol::<EfiMpServicesProtocol>()
+            .unwrap();
+
+        let ran = AtomicUsize::new(0);
+        mp_services
+            .startup_all_aps(true, None, &|processor_number| {
+                ran.fetch_or(1 << processor_number, Ordering::Relaxed);
+            })
+            .unwrap();
+        assert_eq!(ran.load(Ordering::Relaxed), 0b1110);
+        assert_eq!(mp_services.who_am_i(), Ok(0));
+    }
+
+    #[test]
+    fn startup_all_aps_without_aps() {
+        let firmware = MockFirmware::new();
+        let mp_services = firmware
+            .boot_services()
+            .locate_protocol::<EfiMpServicesProtocol>()
+            .unwrap();
+
+        // NOT_STARTED from the firmware: there is nothing to run.
+        let ran = AtomicUsize::new(0);
+        mp_services
+            .startup_all_aps(false, None, &|_| {
+                ran.fetch_add(1, Ordering::Relaxed);
+            })
+            .unwrap();
+        assert_eq!(ran.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn startup_all_aps_timeout() {
+        let firmware = MockFirmware::new();
+        firmware.set_processors(3);
+        firmware.hang_processor(2);
+        let mp_services = firmware
+            .boot_services()
+            .locate_protocol::<EfiMpServicesProtocol>()
+            .unwrap();
+
+        let ran = AtomicUsize::new(0);
+        assert_eq!(
+            mp_services.startup_all_aps(false, Some(Duration::from_millis(10)), &|_| {
+                ran.fetch_add(1, Ordering::Relaxed);
+            }),
+            Err("Timed out waiting for APs")
+        );
+        assert_eq!(ran.load(Ordering::Relaxed), 1);
+        assert_eq!(firmware.stalled(), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn startup_this_ap() {
+        let firmware = MockFirmware::new();
+        firmware.set_processors(3);
+        firmware.hang_processor(2);
+        let mp_services = firmware
+            .boot_services()
+            .locate_protocol::<EfiMpServicesProtocol>()
+            .unwrap();
+
+        assert_eq!(
+            mp_services.startup_this_ap(1, None, || mp_services.who_am_i()),
+            Ok(Ok(1))
+        );
+        assert_eq!(
+            mp_services.startup_this_ap(2, Some(Duration::from_millis(10)), || ()),
+            Err("Timed out waiting for AP")
+        );
+        // The BSP and processors that do not exist are not APs.
+        assert_eq!(
+            mp_services.startup_this_ap(0, None, || ()),
+            Err("Failed to start up AP")
+        );
+        assert_eq!(
+            mp_services.startup_this_ap(3, None, || ()),
+            Err("Failed to start up AP")
+        );
+    }
+}
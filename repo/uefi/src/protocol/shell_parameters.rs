@@ -0,0 +1,41 @@
+//! REF: UEFI Shell Specification 2.2, 2.3 EFI_SHELL_PARAMETERS_PROTOCOL
+
+use core::ptr::NonNull;
+
+use crate::{EfiGuid, EfiVoid, U16Str};
+
+use super::Protocol;
+
+pub const EFI_SHELL_PARAMETERS_PROTOCOL_GUID: EfiGuid = EfiGuid(
+    0x752f3136,
+    0x4e16,
+    0x4fdc,
+    [0xa2, 0x2a, 0xe5, 0xf4, 0x68, 0x12, 0xf4, 0xca],
+);
+
+/// Installed on the image handle by the shell for the images it starts.
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiShellParametersProtocol {
+    argv: NonNull<NonNull<U16Str>>,
+    argc: usize,
+    std_in: *mut EfiVoid,
+    std_out: *mut EfiVoid,
+    std_err: *mut EfiVoid,
+}
+
+unsafe impl Protocol for EfiShellParametersProtocol {
+    const GUID: EfiGuid = EFI_SHELL_PARAMETERS_PROTOCOL_GUID;
+}
+
+impl EfiShellParametersProtocol {
+    /// Arguments split by the shell with quotes removed. The first one is the image path.
+    pub fn argv(&self) -> impl Iterator<Item = &U16Str> {
+        let argv = unsafe { core::slice::from_raw_parts(self.argv.as_ptr(), self.argc) };
+        argv.iter().map(|arg| unsafe { arg.as_ref() })
+    }
+
+    pub fn argc(&self) -> usize {
+        self.argc
+    }
+}
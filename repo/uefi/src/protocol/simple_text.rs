@@ -1,5 +1,336 @@
+//! REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#simple-text-output-protocol
+
+use core::{fmt, ptr::NonNull};
+
+use crate::{EfiGuid, EfiResult, EfiStatus, U16Str};
+
+use super::Protocol;
+
+pub const EFI_SIMPLE_TEXT_INPUT_PROTOCOL_GUID: EfiGuid = EfiGuid(
+    0x387477c1,
+    0x69c7,
+    0x11d2,
+    [0x8e, 0x39, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
+);
+
+pub const EFI_SIMPLE_TEXT_OUTPUT_PROTOCOL_GUID: EfiGuid = EfiGuid(
+    0x387477c2,
+    0x69c7,
+    0x11d2,
+    [0x8e, 0x39, 0x00, 0xa0, 0xc9, 0x69, 0x72, 0x3b],
+);
+
 #[derive(Debug)]
 pub struct SimpleTextInputProtocol {}
 
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-simple-text-output-protocol-reset
+pub type EfiTextReset = extern "efiapi" fn(
+    this: NonNull<SimpleTextOutputProtocol>,
+    extended_verification: bool,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-simple-text-output-protocol-outputstring
+pub type EfiTextString = extern "efiapi" fn(
+    this: NonNull<SimpleTextOutputProtocol>,
+    string: NonNull<U16Str>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-simple-text-output-protocol-teststring
+pub type EfiTextTestString = extern "efiapi" fn(
+    this: NonNull<SimpleTextOutputProtocol>,
+    string: NonNull<U16Str>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-simple-text-output-protocol-querymode
+pub type EfiTextQueryMode = extern "efiapi" fn(
+    this: NonNull<SimpleTextOutputProtocol>,
+    mode_number: usize,
+    columns: NonNull<usize>,
+    rows: NonNull<usize>,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-simple-text-output-protocol-setmode
+pub type EfiTextSetMode =
+    extern "efiapi" fn(this: NonNull<SimpleTextOutputProtocol>, mode_number: usize) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-simple-text-output-protocol-setattribute
+pub type EfiTextSetAttribute =
+    extern "efiapi" fn(this: NonNull<SimpleTextOutputProtocol>, attribute: usize) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-simple-text-output-protocol-clearscreen
+pub type EfiTextClearScreen =
+    extern "efiapi" fn(this: NonNull<SimpleTextOutputProtocol>) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-simple-text-output-protocol-setcursorposition
+pub type EfiTextSetCursorPosition = extern "efiapi" fn(
+    this: NonNull<SimpleTextOutputProtocol>,
+    column: usize,
+    row: usize,
+) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-simple-text-output-protocol-enablecursor
+pub type EfiTextEnableCursor =
+    extern "efiapi" fn(this: NonNull<SimpleTextOutputProtocol>, visible: bool) -> EfiStatus;
+
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#simple-text-output-protocol
+#[repr(C)]
 #[derive(Debug)]
-pub struct SimpleTextOutputProtocol {}
+pub struct SimpleTextOutputProtocol {
+    pub(crate) reset: EfiTextReset,
+    pub(crate) output_string: EfiTextString,
+    pub(crate) test_string: EfiTextTestString,
+    pub(crate) query_mode: EfiTextQueryMode,
+    pub(crate) set_mode: EfiTextSetMode,
+    pub(crate) set_attribute: EfiTextSetAttribute,
+    pub(crate) clear_screen: EfiTextClearScreen,
+    pub(crate) set_cursor_position: EfiTextSetCursorPosition,
+    pub(crate) enable_cursor: EfiTextEnableCursor,
+    pub(crate) mode: NonNull<SimpleTextOutputMode>,
+}
+
+unsafe impl Protocol for SimpleTextOutputProtocol {
+    const GUID: EfiGuid = EFI_SIMPLE_TEXT_OUTPUT_PROTOCOL_GUID;
+}
+
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#simple-text-output-protocol
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct SimpleTextOutputMode {
+    pub(crate) max_mode: i32,
+    pub(crate) mode: i32,
+    pub(crate) attribute: i32,
+    pub(crate) cursor_column: i32,
+    pub(crate) cursor_row: i32,
+    pub(crate) cursor_visible: bool,
+}
+
+impl SimpleTextOutputMode {
+    /// Number of modes supported by QueryMode and SetMode.
+    pub fn max_mode(&self) -> i32 {
+        self.max_mode
+    }
+
+    pub fn mode(&self) -> i32 {
+        self.mode
+    }
+
+    pub fn attribute(&self) -> i32 {
+        self.attribute
+    }
+
+    pub fn cursor_column(&self) -> i32 {
+        self.cursor_column
+    }
+
+    pub fn cursor_row(&self) -> i32 {
+        self.cursor_row
+    }
+
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+}
+
+/// Foreground and background colors of the text.
+/// REF: https://uefi.org/specs/UEFI/2.10/12_Protocols_Console_Support.html#efi-simple-text-output-protocol-setattribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct EfiTextAttribute(usize);
+
+impl EfiTextAttribute {
+    pub const BLACK: usize = 0x00;
+    pub const BLUE: usize = 0x01;
+    pub const GREEN: usize = 0x02;
+    pub const CYAN: usize = 0x03;
+    pub const RED: usize = 0x04;
+    pub const MAGENTA: usize = 0x05;
+    pub const BROWN: usize = 0x06;
+    pub const LIGHTGRAY: usize = 0x07;
+    pub const DARKGRAY: usize = 0x08;
+    pub const LIGHTBLUE: usize = 0x09;
+    pub const LIGHTGREEN: usize = 0x0A;
+    pub const LIGHTCYAN: usize = 0x0B;
+    pub const LIGHTRED: usize = 0x0C;
+    pub const LIGHTMAGENTA: usize = 0x0D;
+    pub const YELLOW: usize = 0x0E;
+    pub const WHITE: usize = 0x0F;
+
+    /// `background` must be one of the first 8 colors.
+    pub const fn new(foreground: usize, background: usize) -> Self {
+        Self((foreground & 0x0F) | ((background & 0x07) << 4))
+    }
+}
+
+impl SimpleTextOutputProtocol {
+    pub fn mode(&self) -> &SimpleTextOutputMode {
+        unsafe { self.mode.as_ref() }
+    }
+
+    pub fn reset(&self, extended_verification: bool) -> EfiResult<()> {
+        let status = (self.reset)(NonNull::from(self), extended_verification);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to reset text output");
+        }
+
+        Ok(())
+    }
+
+    /// Writes `string` at the cursor. Line breaks need `"\r\n"`.
+    pub fn output_string(&self, string: &U16Str) -> EfiResult<()> {
+        let status = (self.output_string)(NonNull::from(self), NonNull::from(string));
+
+        // Warnings tell that some characters were skipped, which is not fatal for output.
+        if status.is_error() {
+            return Err("Failed to output string");
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of columns and rows of `mode_number`.
+    pub fn query_mode(&self, mode_number: usize) -> EfiResult<(usize, usize)> {
+        let mut columns = 0;
+        let mut rows = 0;
+        let status = (self.query_mode)(
+            NonNull::from(self),
+            mode_number,
+            NonNull::from(&mut columns),
+            NonNull::from(&mut rows),
+        );
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to query text mode");
+        }
+
+        Ok((columns, rows))
+    }
+
+    pub fn set_mode(&self, mode_number: usize) -> EfiResult<()> {
+        let status = (self.set_mode)(NonNull::from(self), mode_number);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to set text mode");
+        }
+
+        Ok(())
+    }
+
+    pub fn set_attribute(&self, attribute: EfiTextAttribute) -> EfiResult<()> {
+        let status = (self.set_attribute)(NonNull::from(self), attribute.0);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to set text attribute");
+        }
+
+        Ok(())
+    }
+
+    pub fn clear_screen(&self) -> EfiResult<()> {
+        let status = (self.clear_screen)(NonNull::from(self));
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to clear screen");
+        }
+
+        Ok(())
+    }
+
+    pub fn set_cursor_position(&self, column: usize, row: usize) -> EfiResult<()> {
+        let status = (self.set_cursor_position)(NonNull::from(self), column, row);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to set cursor position");
+        }
+
+        Ok(())
+    }
+
+    pub fn enable_cursor(&self, visible: bool) -> EfiResult<()> {
+        let status = (self.enable_cursor)(NonNull::from(self), visible);
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to enable cursor");
+        }
+
+        Ok(())
+    }
+}
+
+/// Converts UTF-8 to UCS-2 in chunks, with `"\n"` written as `"\r\n"`.
+/// Characters outside the BMP are written as U+FFFD.
+impl fmt::Write for &SimpleTextOutputProtocol {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        const CAPACITY: usize = 128;
+        let mut buffer = [0u16; CAPACITY + 1];
+        let mut len = 0;
+
+        let flush = |buffer: &mut [u16; CAPACITY + 1], len: &mut usize| {
+            buffer[*len] = 0;
+            *len = 0;
+            let string = unsafe { &*U16Str::from_raw_parts(buffer.as_ptr()) };
+            self.output_string(string).map_err(|_| fmt::Error)
+        };
+
+        for c in s.chars() {
+            // Leave room for "\r\n".
+            if len + 2 > CAPACITY {
+                flush(&mut buffer, &mut len)?;
+            }
+            if c == '\n' {
+                buffer[len] = u16::from(b'\r');
+                len += 1;
+            }
+            let mut units = [0; 2];
+            buffer[len] = match c.encode_utf16(&mut units) {
+                [unit] => *unit,
+                _ => char::REPLACEMENT_CHARACTER as u16,
+            };
+            len += 1;
+        }
+
+        if len > 0 {
+            flush(&mut buffer, &mut len)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::MockFirmware;
+    use core::fmt::Write;
+
+    #[test]
+    fn write_fmt() {
+        let firmware = MockFirmware::new();
+        let mut console_out = firmware.system_table().console_out();
+
+        let long = "x".repeat(300);
+        write!(console_out, "{}\nü🦀\n{}", 42, long).unwrap();
+        let expected = std::format!("42\r\nü\u{FFFD}\r\n{}", long);
+        assert_eq!(firmware.take_output(), expected);
+        assert_eq!(console_out.mode().cursor_row(), 2);
+    }
+
+    #[test]
+    fn modes() {
+        let firmware = MockFirmware::new();
+        let console_out = firmware.system_table().console_out();
+        assert_eq!(console_out.query_mode(0), Ok((80, 25)));
+        assert!(console_out.query_mode(1).is_err());
+
+        console_out
+            .set_attribute(EfiTextAttribute::new(
+                EfiTextAttribute::YELLOW,
+                EfiTextAttribute::BLUE,
+            ))
+            .unwrap();
+        assert_eq!(console_out.mode().attribute(), 0x1E);
+        console_out.set_cursor_position(10, 5).unwrap();
+        assert_eq!(console_out.mode().cursor_column(), 10);
+        assert_eq!(console_out.mode().cursor_row(), 5);
+    }
+}
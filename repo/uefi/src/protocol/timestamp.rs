@@ -0,0 +1,72 @@
+//! REF: UEFI Specification 2.10, 39.1 EFI Timestamp Protocol
+
+use core::{mem::MaybeUninit, ptr::NonNull};
+
+use crate::{EfiGuid, EfiResult, EfiStatus};
+
+use super::Protocol;
+
+pub const EFI_TIMESTAMP_PROTOCOL_GUID: EfiGuid = EfiGuid(
+    0xafbfde41,
+    0x2e6e,
+    0x4262,
+    [0xba, 0x65, 0x62, 0xb9, 0x23, 0x6e, 0x54, 0x95],
+);
+
+pub type EfiTimestampGet = extern "efiapi" fn() -> u64;
+
+pub type EfiTimestampGetProperties =
+    extern "efiapi" fn(properties: NonNull<EfiTimestampProperties>) -> EfiStatus;
+
+/// Platform counter that is available while boot services are running.
+#[repr(C)]
+#[derive(Debug)]
+pub struct EfiTimestampProtocol {
+    get_timestamp: EfiTimestampGet,
+    get_properties: EfiTimestampGetProperties,
+}
+
+unsafe impl Protocol for EfiTimestampProtocol {
+    const GUID: EfiGuid = EFI_TIMESTAMP_PROTOCOL_GUID;
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct EfiTimestampProperties {
+    frequency: u64,
+    end_value: u64,
+}
+
+impl EfiTimestampProperties {
+    /// Ticks per second.
+    pub fn frequency(&self) -> u64 {
+        self.frequency
+    }
+
+    /// Value of the counter just before it rolls over to 0.
+    pub fn end_value(&self) -> u64 {
+        self.end_value
+    }
+}
+
+impl EfiTimestampProtocol {
+    pub fn get_timestamp(&self) -> u64 {
+        (self.get_timestamp)()
+    }
+
+    pub fn get_properties(&self) -> EfiResult<EfiTimestampProperties> {
+        let mut properties = MaybeUninit::<EfiTimestampProperties>::uninit();
+        let status = (self.get_properties)(NonNull::from(&mut properties).cast());
+
+        if status != EfiStatus::SUCCESS {
+            return Err("Failed to get timestamp properties");
+        }
+
+        let properties = unsafe { properties.assume_init() };
+        if properties.frequency == 0 {
+            return Err("Timestamp frequency is zero");
+        }
+
+        Ok(properties)
+    }
+}
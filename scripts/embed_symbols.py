@@ -0,0 +1,101 @@
+#!/usr/bin/env python3
+"""Appends a `.symbols` section to a PE image, for moos to name functions in backtraces.
+
+The symbols come from the procedures in the PDB that the linker writes next to the image, as dumped
+by llvm-pdbutil. The section holds, in little endian:
+
+    b"MSYM", count: u32
+    count * (start: u32, size: u32, name_offset: u32, name_length: u32), sorted by start
+    names in UTF-8
+
+where start is relative to the image base and name_offset to the end of the entries.
+It must stay in sync with `SymbolMap` in repo/moos/src/backtrace.rs.
+
+Usage: embed_symbols.py <image.efi> <image.pdb>
+"""
+
+import re
+import struct
+import subprocess
+import sys
+
+SECTION_NAME = b".symbols"
+MAGIC = b"MSYM"
+# IMAGE_SCN_CNT_INITIALIZED_DATA | IMAGE_SCN_MEM_READ
+CHARACTERISTICS = 0x40000040
+
+PROCEDURE = re.compile(r"S_[GL]PROC32 \[size = \d+\] `(.*)`")
+ADDRESS = re.compile(r"addr = (\d+):(\d+), code size = (\d+)")
+
+
+def align(value, alignment):
+    return (value + alignment - 1) // alignment * alignment
+
+
+def read_procedures(pdb):
+    dump = subprocess.run(
+        ["llvm-pdbutil", "dump", "-symbols", pdb], check=True, capture_output=True, text=True
+    ).stdout
+    name = None
+    for line in dump.splitlines():
+        if match := PROCEDURE.search(line):
+            name = match.group(1)
+        elif name is not None and (match := ADDRESS.search(line)):
+            section, offset, size = map(int, match.groups())
+            yield name, section, offset, size
+            name = None
+
+
+def build_section(procedures, section_addresses):
+    symbols = {}
+    for name, section, offset, size in procedures:
+        if 1 <= section <= len(section_addresses) and size > 0:
+            symbols.setdefault(section_addresses[section - 1] + offset, (size, name))
+
+    entries = bytearray()
+    names = bytearray()
+    for start, (size, name) in sorted(symbols.items()):
+        encoded = name.encode()
+        entries += struct.pack("<IIII", start, size, len(names), len(encoded))
+        names += encoded
+    return MAGIC + struct.pack("<I", len(symbols)) + entries + names
+
+
+def main(image_path, pdb_path):
+    image = bytearray(open(image_path, "rb").read())
+
+    pe = struct.unpack_from("<I", image, 0x3C)[0]
+    section_count, = struct.unpack_from("<H", image, pe + 6)
+    optional_size, = struct.unpack_from("<H", image, pe + 20)
+    optional = pe + 24
+    section_alignment, file_alignment = struct.unpack_from("<II", image, optional + 32)
+    headers_size, = struct.unpack_from("<I", image, optional + 60)
+
+    headers = optional + optional_size
+    sections = [struct.unpack_from("<8sIIII", image, headers + 40 * i) for i in range(section_count)]
+    if any(name == SECTION_NAME for name, *_ in sections):
+        sys.exit(f"{image_path} already has symbols")
+    new_header = headers + 40 * section_count
+    if new_header + 40 > headers_size:
+        sys.exit(f"No room for another section header in {image_path}")
+
+    data = build_section(read_procedures(pdb_path), [address for _, _, address, _, _ in sections])
+    address = align(max(address + size for _, size, address, _, _ in sections), section_alignment)
+    offset = align(len(image), file_alignment)
+    raw_size = align(len(data), file_alignment)
+
+    struct.pack_into(
+        "<8sIIIIIIHHI", image, new_header,
+        SECTION_NAME, len(data), address, raw_size, offset, 0, 0, 0, 0, CHARACTERISTICS,
+    )
+    struct.pack_into("<H", image, pe + 6, section_count + 1)
+    struct.pack_into("<I", image, optional + 56, align(address + len(data), section_alignment))
+    image += bytes(offset - len(image)) + data + bytes(raw_size - len(data))
+
+    open(image_path, "wb").write(image)
+
+
+if __name__ == "__main__":
+    if len(sys.argv) != 3:
+        sys.exit(__doc__)
+    main(*sys.argv[1:])
@@ -3,14 +3,44 @@ cd "$WORKSPACE"
 
 PATH_TO_EFI=$1
 
+# `cargo test` builds its binaries under deps/. They run without a display and report through
+# isa-debug-exit, which makes QEMU exit with (code << 1) | 1.
+QEMU_TEST_ARGS=()
+if [[ "${PATH_TO_EFI}" == */deps/* ]]; then
+    QEMU_TEST_ARGS=(-display none)
+fi
+
 rm -rf .cache/mnt
 mkdir -p .cache/mnt/EFI/BOOT
 cp ${PATH_TO_EFI} .cache/mnt/EFI/BOOT/BOOTX64.EFI
 
+# Names for backtraces, which otherwise only show offsets into the image.
+PATH_TO_PDB="${PATH_TO_EFI%.efi}.pdb"
+if [[ -f "${PATH_TO_PDB}" ]] && command -v llvm-pdbutil > /dev/null; then
+    python3 scripts/embed_symbols.py .cache/mnt/EFI/BOOT/BOOTX64.EFI "${PATH_TO_PDB}"
+else
+    echo "Skipping symbols for backtraces: needs ${PATH_TO_PDB} and llvm-pdbutil" >&2
+fi
+
 cd ".cache"
 
+set +e
 qemu-system-x86_64 \
-    -m 4G \
+    -m 4G -smp 4 \
     -bios $WORKSPACE/third_party/ovmf/RELEASEX64_OVMF.fd \
     -drive format=raw,file=fat:rw:mnt \
-    -device isa-debug-exit,iobase=0xf4,iosize=0x01
\ No newline at end of file
+    -device isa-debug-exit,iobase=0xf4,iosize=0x01 \
+    -serial stdio \
+    "${QEMU_TEST_ARGS[@]}"
+STATUS=$?
+set -e
+
+if [[ ${#QEMU_TEST_ARGS[@]} -eq 0 ]]; then
+    exit ${STATUS}
+fi
+# Success is 0x10 and failure 0x11 in moos::qemu::ExitCode.
+case ${STATUS} in
+    33) exit 0 ;;
+    35) exit 1 ;;
+    *) echo "QEMU exited with ${STATUS} before the tests finished" >&2; exit 1 ;;
+esac